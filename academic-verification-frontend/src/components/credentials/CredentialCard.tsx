@@ -31,6 +31,8 @@ export interface Credential {
   issuedAt: number;
   expiresAt?: number;
   revoked: boolean;
+  expired?: boolean;
+  suspended?: boolean;
   metadata?: string;
 }
 
@@ -76,7 +78,16 @@ export default function CredentialCard({
       };
     }
 
-    if (credential.expiresAt && credential.expiresAt < Date.now()) {
+    if (credential.suspended) {
+      return {
+        label: 'Suspended',
+        variant: 'warning' as const,
+        icon: AlertCircle,
+        color: 'text-orange-600'
+      };
+    }
+
+    if (credential.expired || (credential.expiresAt && credential.expiresAt < Date.now())) {
       return {
         label: 'Expired',
         variant: 'warning' as const,
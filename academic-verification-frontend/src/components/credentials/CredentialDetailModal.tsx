@@ -97,6 +97,7 @@ export default function CredentialDetailModal({
       issuedAt: credential.issuedAt,
       expiresAt: credential.expiresAt,
       revoked: credential.revoked,
+      suspended: credential.suspended,
       metadata: credential.metadata,
       verificationUrl: `${window.location.origin}/verify?hash=${credential.credentialHash}`,
     };
@@ -118,7 +119,10 @@ export default function CredentialDetailModal({
     if (credential.revoked) {
       return { label: 'Revoked', variant: 'error' as const, icon: AlertCircle };
     }
-    if (credential.expiresAt && credential.expiresAt < Date.now()) {
+    if (credential.suspended) {
+      return { label: 'Suspended', variant: 'warning' as const, icon: AlertCircle };
+    }
+    if (credential.expired || (credential.expiresAt && credential.expiresAt < Date.now())) {
       return { label: 'Expired', variant: 'warning' as const, icon: AlertCircle };
     }
     return { label: 'Active', variant: 'success' as const, icon: CheckCircle2 };
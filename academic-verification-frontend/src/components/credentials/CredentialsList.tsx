@@ -81,13 +81,13 @@ export default function CredentialsList({
     if (filterStatus !== 'all') {
       result = result.filter(cred => {
         if (filterStatus === 'active') {
-          return !cred.revoked && (!cred.expiresAt || cred.expiresAt > Date.now());
+          return !cred.revoked && !cred.expired && !cred.suspended && (!cred.expiresAt || cred.expiresAt > Date.now());
         }
         if (filterStatus === 'revoked') {
           return cred.revoked;
         }
         if (filterStatus === 'expired') {
-          return !cred.revoked && cred.expiresAt && cred.expiresAt < Date.now();
+          return !cred.revoked && (cred.expired || (cred.expiresAt && cred.expiresAt < Date.now()));
         }
         return true;
       });
@@ -121,9 +121,9 @@ export default function CredentialsList({
   const counts = useMemo(() => {
     return {
       total: decodedCredentials.length,
-      active: decodedCredentials.filter(c => !c.revoked && (!c.expiresAt || c.expiresAt > Date.now())).length,
+      active: decodedCredentials.filter(c => !c.revoked && !c.expired && !c.suspended && (!c.expiresAt || c.expiresAt > Date.now())).length,
       revoked: decodedCredentials.filter(c => c.revoked).length,
-      expired: decodedCredentials.filter(c => !c.revoked && c.expiresAt && c.expiresAt < Date.now()).length,
+      expired: decodedCredentials.filter(c => !c.revoked && (c.expired || (c.expiresAt && c.expiresAt < Date.now()))).length,
     };
   }, [decodedCredentials]);
 
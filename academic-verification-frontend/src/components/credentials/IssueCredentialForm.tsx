@@ -119,8 +119,9 @@ export default function IssueCredentialForm({ onSuccess, onCancel }: IssueCreden
     try {
       console.log('🔍 Searching for DID:', recipientDID);
 
-      // Query blockchain for DID
-      const didDoc = await queries.did.getDID(recipientDID);
+      // Query blockchain for the DID, or the DID the address is linked to
+      const did = await queries.did.resolveDid(recipientDID);
+      const didDoc = did ? await queries.did.getDID(did) : null;
       
       if (!didDoc) {
         setRecipientInfo({ status: 'not_found' });
@@ -251,10 +252,15 @@ export default function IssueCredentialForm({ onSuccess, onCancel }: IssueCreden
     setIsSubmitting(true);
     
     try {
-      if (!transactions || !account || !api) {
+      if (!transactions || !account || !api || !queries) {
         throw new Error('Blockchain not connected');
       }
 
+      const holder = await queries.did.resolveDid(data.recipientDID);
+      if (!holder) {
+        throw new Error('Recipient has no DID');
+      }
+
       console.log('🚀 Issuing credential on blockchain...');
 
       // Prepare metadata as JSON
@@ -270,17 +276,8 @@ export default function IssueCredentialForm({ onSuccess, onCancel }: IssueCreden
         notes: data.notes,
       });
 
-      // Convert expiration date to block number (if provided)
-      let expiresAtBlock: number | null = null;
-      if (data.expirationDate) {
-        const currentBlock = await api.query.system.number();
-        const expirationDate = new Date(data.expirationDate);
-        const currentDate = new Date();
-        const daysDiff = Math.ceil((expirationDate.getTime() - currentDate.getTime()) / (1000 * 60 * 60 * 24));
-        // Assuming ~6 second blocks, roughly 14,400 blocks per day
-        const blocksToAdd = daysDiff * 14400;
-        expiresAtBlock = currentBlock.toNumber() + blocksToAdd;
-      }
+      // Expiration dates are checked against the chain's timestamp (milliseconds)
+      const validUntil = data.expirationDate ? new Date(data.expirationDate).getTime() : null;
 
       // Show transaction progress
       const statusToast = toast.loading('Preparing transaction...');
@@ -288,11 +285,13 @@ export default function IssueCredentialForm({ onSuccess, onCancel }: IssueCreden
       // Issue credential transaction
       const result = await transactions.credential.issueCredential(
         account,
-        data.recipientDID,
+        holder,
         documentHash,
         data.credentialType,
         metadata,
-        expiresAtBlock,
+        null,
+        null,
+        validUntil,
         (status) => {
           if (status.status === 'signing') {
             toast.loading('Waiting for signature...', { id: statusToast });
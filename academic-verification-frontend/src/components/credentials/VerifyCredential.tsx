@@ -44,6 +44,7 @@ interface VerificationResult {
     issuedAt: number;
     expiresAt?: number;
     revoked: boolean;
+    suspended?: boolean;
     metadata?: string;
   };
   blockNumber?: number;
@@ -302,6 +303,7 @@ export default function VerifyCredential({ initialHash }: VerifyCredentialProps
           issuedAt: credential.issuedAt,
           expiresAt: credential.expiresAt,
           revoked: credential.revoked,
+          suspended: credential.suspended,
           metadata: credential.metadata,
         },
       };
@@ -392,6 +394,17 @@ export default function VerifyCredential({ initialHash }: VerifyCredentialProps
       };
     }
 
+    if (cred.suspended) {
+      return {
+        icon: AlertCircle,
+        title: 'Credential Suspended',
+        description: 'The issuing institution has suspended this credential pending review',
+        color: 'text-orange-600',
+        bgColor: 'bg-orange-50 dark:bg-orange-900/20',
+        borderColor: 'border-orange-200 dark:border-orange-800',
+      };
+    }
+
     if (cred.expiresAt && cred.expiresAt < Date.now()) {
       return {
         icon: AlertCircle,
@@ -182,11 +182,20 @@ export default function CreateDIDForm({ onSuccess, onCancel }: CreateDIDFormProp
         keyType: data.keyType,
       });
 
+      // Prove possession of the new key by signing the chain's challenge
+      const keyring = new Keyring({
+        type: data.keyType.toLowerCase() as 'ed25519' | 'sr25519' | 'ecdsa'
+      });
+      const pair = keyring.addFromMnemonic(generatedKeys.mnemonic);
+      const payload = await transactions.did.keyPossessionPayload(account.address);
+      const proof = pair.sign(payload);
+
       // Call the blockchain transaction with status updates
       const result = await transactions.did.createDID(
         account,
         publicKeyBytes,
         data.keyType,
+        proof,
         (status) => {
           console.log('Transaction status:', status);
           setTransactionStatus(status.message);
@@ -36,7 +36,15 @@ const institutionSchema = z.object({
   description: z.string()
     .max(500, 'Description must be less than 500 characters')
     .optional(),
-  country: z.string().min(2, 'Please select a country'),
+  country: z.string()
+    .regex(/^[A-Z]{2}$/, 'Please enter a two-letter country code (ISO 3166-1)'),
+  legalIdentifier: z.string()
+    .max(128, 'Legal identifier must be less than 128 characters')
+    .regex(/^[A-Za-z0-9 \-/.]*$/, 'Only letters, digits, spaces, "-", "/" and "." are allowed')
+    .optional(),
+  sectors: z.string()
+    .max(300, 'Sectors must be less than 300 characters')
+    .optional(),
   website: z.string().url('Please enter a valid URL'),
   
   // Contact information
@@ -130,7 +138,7 @@ export default function InstitutionRegistration({ onSuccess, onCancel }: Institu
     let fieldsToValidate: (keyof InstitutionFormData)[] = [];
     
     if (step === 1) {
-      fieldsToValidate = ['name', 'type', 'description', 'country', 'website'];
+      fieldsToValidate = ['name', 'type', 'description', 'country', 'legalIdentifier', 'sectors', 'website'];
     } else if (step === 3) {
       fieldsToValidate = ['contactName', 'contactEmail', 'contactPhone', 'address'];
     }
@@ -159,6 +167,11 @@ export default function InstitutionRegistration({ onSuccess, onCancel }: Institu
       return;
     }
 
+    if (!didAddress) {
+      toast.error('Please create a DID first');
+      return;
+    }
+
     setIsSubmitting(true);
     
     try {
@@ -179,6 +192,32 @@ export default function InstitutionRegistration({ onSuccess, onCancel }: Institu
 
       if (result.success) {
         console.log('✅ Institution registered successfully');
+
+        // Publish the structured profile alongside the registration
+        const profileResult = await transactions.did.updateInstitutionProfile(
+          account,
+          didAddress,
+          {
+            country: data.country,
+            legalIdentifier: data.legalIdentifier || null,
+            website: new URL(data.website).hostname.toLowerCase(),
+            logoHash: null,
+            accreditations: [],
+            sectors: [...new Set(
+              (data.sectors ?? '')
+                .split(',')
+                .map((sector) => sector.trim().toLowerCase())
+                .filter(Boolean)
+            )],
+          },
+          (status) => setTxStatus(status.message)
+        );
+
+        if (!profileResult.success) {
+          toast.warning('Institution registered, but its profile could not be saved', {
+            description: profileResult.error,
+          });
+        }
         
         // Update local store AFTER successful blockchain transaction
         useDIDStore.getState().setInstitution(data.name);
@@ -255,7 +294,7 @@ export default function InstitutionRegistration({ onSuccess, onCancel }: Institu
           </label>
           <Input
             {...register('country')}
-            placeholder="e.g., United States"
+            placeholder="e.g., US"
             className={errors.country ? 'border-red-500' : ''}
           />
           {errors.country && (
@@ -263,6 +302,34 @@ export default function InstitutionRegistration({ onSuccess, onCancel }: Institu
           )}
         </div>
 
+        <div>
+          <label className="block text-sm font-semibold mb-2">
+            Legal Identifier
+          </label>
+          <Input
+            {...register('legalIdentifier')}
+            placeholder="e.g., national registry number"
+            className={errors.legalIdentifier ? 'border-red-500' : ''}
+          />
+          {errors.legalIdentifier && (
+            <p className="text-sm text-red-600 mt-1">{errors.legalIdentifier.message}</p>
+          )}
+        </div>
+
+        <div>
+          <label className="block text-sm font-semibold mb-2">
+            Sectors
+          </label>
+          <Input
+            {...register('sectors')}
+            placeholder="e.g., medicine, law"
+            className={errors.sectors ? 'border-red-500' : ''}
+          />
+          {errors.sectors && (
+            <p className="text-sm text-red-600 mt-1">{errors.sectors.message}</p>
+          )}
+        </div>
+
         <div>
           <label className="block text-sm font-semibold mb-2">
             <Globe className="inline h-4 w-4 mr-1" />
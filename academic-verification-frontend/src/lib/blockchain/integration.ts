@@ -84,8 +84,10 @@ export function formatCredentialData(rawCredential: any) {
     metadata: rawCredential.metadata ? Buffer.from(rawCredential.metadata).toString('utf-8') : undefined,
     // Use normalized timestamp, or current time as fallback for display purposes
     issuedAt: issuedAtTimestamp || Date.now(),
-    expiresAt: normalizeTimestamp(rawCredential.expiresAt),
+    expiresAt: normalizeTimestamp(rawCredential.validUntil) ?? normalizeTimestamp(rawCredential.expiresAt),
     revoked: rawCredential.status === 'Revoked',
+    expired: rawCredential.status === 'Expired',
+    suspended: rawCredential.status === 'Suspended',
   };
 }
 
@@ -8,6 +8,24 @@ import { formatCredentialData, checkPalletAvailability } from './integration';
 export class RealDIDQueries {
   constructor(private api: ApiPromise) { }
 
+  /**
+   * DID identifier given either the identifier itself (0x-prefixed hex) or
+   * the address of an account linked to the DID
+   */
+  async resolveDid(didOrAddress: string): Promise<string | null> {
+    if (/^0x[0-9a-fA-F]{64}$/.test(didOrAddress)) {
+      return didOrAddress;
+    }
+
+    try {
+      const linkedDid = await this.api.query.did.accountDids(didOrAddress);
+      return linkedDid.isEmpty ? null : linkedDid.toString();
+    } catch (error) {
+      console.error('Error resolving DID:', error);
+      return null;
+    }
+  }
+
   async getDID(address: string) {
     try {
       const hasPallet = await checkPalletAvailability(this.api, 'did');
@@ -58,6 +76,7 @@ export class RealDIDQueries {
         did: data.did,
         verified: data.verified,
         registeredAt: data.registeredAt,
+        accreditor: data.accreditor ?? null,
       };
     } catch (error) {
       console.error('Error fetching institution:', error);
@@ -94,6 +113,7 @@ export class RealDIDQueries {
             name: data.name,
             verified: data.verified,
             registeredAt: data.registeredAt,
+            accreditor: data.accreditor ?? null,
           };
         })
         .filter(Boolean);
@@ -102,6 +122,20 @@ export class RealDIDQueries {
       return [];
     }
   }
+
+  async getInstitutionsByAccreditor(accreditor: string): Promise<string[]> {
+    try {
+      const hasPallet = await checkPalletAvailability(this.api, 'did');
+      if (!hasPallet) return [];
+
+      const entries = await this.api.query.did.accreditedInstitutions.entries(accreditor);
+
+      return entries.map(([key]) => key.args[1].toString());
+    } catch (error) {
+      console.error('Error fetching accredited institutions:', error);
+      return [];
+    }
+  }
 }
 
 /**
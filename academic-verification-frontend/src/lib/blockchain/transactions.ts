@@ -1,5 +1,6 @@
 // src/lib/blockchain/transactions.ts
 import type { ApiPromise } from '@polkadot/api';
+import type { SubmittableExtrinsic } from '@polkadot/api/types';
 import type { InjectedAccountWithMeta } from '@polkadot/extension-inject/types';
 import { web3FromAddress } from '@polkadot/extension-dapp';
 
@@ -130,13 +131,36 @@ export function submitTransaction(
 export class DIDTransactions {
   constructor(private api: ApiPromise) { }
 
+  /**
+   * Payload a new DID key must sign to prove the caller controls it
+   */
+  async keyPossessionPayload(did: string): Promise<Uint8Array> {
+    const nonce = await this.api.query.did.keyNonces(did);
+    return this.api
+      .createType('(Bytes, H256, AccountId, u64)', [
+        'academic-did/key-possession',
+        this.api.genesisHash,
+        did,
+        nonce,
+      ])
+      .toU8a();
+  }
+
+  private typed(keyType: 'Ed25519' | 'Sr25519' | 'ECDSA', value: Uint8Array) {
+    return { [keyType === 'ECDSA' ? 'Ecdsa' : keyType]: value };
+  }
+
   async createDID(
     account: InjectedAccountWithMeta | { address: string },
     publicKey: Uint8Array,
     keyType: 'Ed25519' | 'Sr25519' | 'ECDSA',
+    proof: Uint8Array,
     onStatusUpdate?: (status: TransactionStatus) => void
   ): Promise<TransactionResult> {
-    const tx = this.api.tx.did.createDid(publicKey, keyType);
+    const tx = this.api.tx.did.createDid(
+      this.typed(keyType, publicKey),
+      this.typed(keyType, proof)
+    );
     return submitTransaction(this.api, account, tx, onStatusUpdate);
   }
 
@@ -149,13 +173,134 @@ export class DIDTransactions {
     return submitTransaction(this.api, account, tx, onStatusUpdate);
   }
 
+  async updateInstitutionProfile(
+    account: InjectedAccountWithMeta | { address: string },
+    institutionDid: string,
+    profile: {
+      country: string | null;
+      legalIdentifier: string | null;
+      website: string | null;
+      logoHash: string | null;
+      accreditations: string[];
+      sectors: string[];
+    },
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.did.updateInstitutionProfile(institutionDid, profile);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
   async addPublicKey(
     account: InjectedAccountWithMeta | { address: string },
+    did: string,
     publicKey: Uint8Array,
     keyType: 'Ed25519' | 'Sr25519' | 'ECDSA',
+    proof: Uint8Array,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.did.addPublicKey(
+      did,
+      this.typed(keyType, publicKey),
+      this.typed(keyType, proof)
+    );
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  /**
+   * Payload a DID authentication key signs to let `account` act for the DID
+   */
+  async accountLinkPayload(did: string, account: string): Promise<Uint8Array> {
+    const nonce = await this.api.query.did.keyNonces(did);
+    return this.api
+      .createType('(Bytes, H256, AccountId, AccountId, u64)', [
+        'academic-did/account-link',
+        this.api.genesisHash,
+        did,
+        account,
+        nonce,
+      ])
+      .toU8a();
+  }
+
+  async linkAccount(
+    account: InjectedAccountWithMeta | { address: string },
+    did: string,
+    keyId: string,
+    keyType: 'Ed25519' | 'Sr25519' | 'ECDSA',
+    proof: Uint8Array,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.did.linkAccount(did, keyId, this.typed(keyType, proof));
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  async unlinkAccount(
+    account: InjectedAccountWithMeta | { address: string },
+    did: string,
+    linkedAccount: string,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.did.unlinkAccount(did, linkedAccount);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  /**
+   * Take a compromised key out of the caller's DID document, distrusting its
+   * signatures from block `compromisedSince` on
+   */
+  async reportKeyCompromised(
+    account: InjectedAccountWithMeta | { address: string },
+    keyId: string,
+    compromisedSince: number,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.did.reportKeyCompromised(keyId, compromisedSince);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  /**
+   * Payload a DID key signs to authorise `call` submitted by `submitter`
+   */
+  async didCallPayload(
+    did: string,
+    call: SubmittableExtrinsic<'promise'>,
+    submitter: string
+  ): Promise<{ payload: Uint8Array; nonce: number }> {
+    const nonce = (await this.api.query.did.didCallNonces(did)).toString();
+    const payload = this.api
+      .createType('(Bytes, H256, AccountId, Call, u64, AccountId)', [
+        'academic-did/did-call',
+        this.api.genesisHash,
+        did,
+        call.method,
+        nonce,
+        submitter,
+      ])
+      .toU8a();
+    return { payload, nonce: Number(nonce) };
+  }
+
+  /**
+   * Submit `call` on behalf of `did`, authorised by a signature from one of
+   * its authentication or assertion keys, with `account` paying the fees
+   */
+  async submitDidCall(
+    account: InjectedAccountWithMeta | { address: string },
+    did: string,
+    call: SubmittableExtrinsic<'promise'>,
+    nonce: number,
+    keyId: string,
+    keyType: 'Ed25519' | 'Sr25519' | 'ECDSA',
+    signature: Uint8Array,
     onStatusUpdate?: (status: TransactionStatus) => void
   ): Promise<TransactionResult> {
-    const tx = this.api.tx.did.addPublicKey(publicKey, keyType);
+    const tx = this.api.tx.did.submitDidCall(
+      did,
+      call.method,
+      nonce,
+      keyId,
+      this.typed(keyType, signature)
+    );
     return submitTransaction(this.api, account, tx, onStatusUpdate);
   }
 }
@@ -173,6 +318,8 @@ export class CredentialTransactions {
     credentialType: string,
     metadata: string,
     expiresAt: number | null,
+    validFrom: number | null,
+    validUntil: number | null,
     onStatusUpdate?: (status: TransactionStatus) => void
   ): Promise<TransactionResult> {
     // Map UI credential types to blockchain enum variants
@@ -195,7 +342,8 @@ export class CredentialTransactions {
       credentialHash,
       blockchainType, // Use mapped type
       metadata,
-      expiresAt
+      expiresAt,
+      { validFrom, validUntil }
     );
 
     return submitTransaction(this.api, account, tx, onStatusUpdate);
@@ -210,6 +358,34 @@ export class CredentialTransactions {
     return submitTransaction(this.api, account, tx, onStatusUpdate);
   }
 
+  async suspendCredential(
+    account: InjectedAccountWithMeta | { address: string },
+    credentialId: string,
+    reason: 'MisconductInvestigation' | 'IssuanceError' | 'Dispute' | 'Administrative' | 'Other',
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.suspendCredential(credentialId, reason);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  async reinstateCredential(
+    account: InjectedAccountWithMeta | { address: string },
+    credentialId: string,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.reinstateCredential(credentialId);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  async removeCredential(
+    account: InjectedAccountWithMeta | { address: string },
+    credentialId: string,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.removeCredential(credentialId);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
   async verifyCredential(
     account: InjectedAccountWithMeta | { address: string },
     credentialHash: string,
@@ -218,6 +394,54 @@ export class CredentialTransactions {
     const tx = this.api.tx.credential.verifyCredential(credentialHash);
     return submitTransaction(this.api, account, tx, onStatusUpdate);
   }
+
+  async setDelegate(
+    account: InjectedAccountWithMeta | { address: string },
+    delegate: string,
+    permissions: {
+      canIssue: boolean;
+      canRevoke: boolean;
+      canUpdateMetadata: boolean;
+      credentialTypes: string[];
+    },
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.setDelegate(delegate, permissions);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  async removeDelegate(
+    account: InjectedAccountWithMeta | { address: string },
+    delegate: string,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.removeDelegate(delegate);
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
+
+  async issueCredentialOnBehalf(
+    account: InjectedAccountWithMeta | { address: string },
+    institution: string,
+    holder: string,
+    credentialHash: string,
+    credentialType: string,
+    metadata: string,
+    expiresAt: number | null,
+    validFrom: number | null,
+    validUntil: number | null,
+    onStatusUpdate?: (status: TransactionStatus) => void
+  ): Promise<TransactionResult> {
+    const tx = this.api.tx.credential.issueCredentialOnBehalf(
+      institution,
+      holder,
+      credentialHash,
+      credentialType,
+      metadata,
+      expiresAt,
+      { validFrom, validUntil }
+    );
+    return submitTransaction(this.api, account, tx, onStatusUpdate);
+  }
 }
 
 /**
@@ -101,11 +101,13 @@ export default function AdminVerification() {
       // Get the signer
       const injector = await web3FromAddress(account.address);
 
-      // IMPORTANT: Wrap with sudo.sudo() since this requires root origin
+      // IMPORTANT: Verification requires a council majority, so propose it as a council motion
       const verifyTx = api.tx.did.verifyInstitution(institutionDid);
-      const tx = api.tx.sudo.sudo(verifyTx);
+      const members = (await api.query.council.members()) as unknown as unknown[];
+      const threshold = Math.max(1, Math.ceil(members.length / 2));
+      const tx = api.tx.council.propose(threshold, verifyTx, verifyTx.method.encodedLength);
 
-      console.log('📝 Created SUDO transaction, requesting signature...');
+      console.log('📝 Created council proposal, requesting signature...');
 
       // Sign and send
       await tx.signAndSend(
@@ -128,6 +130,12 @@ export default function AdminVerification() {
               }
             }
 
+            // A single-vote threshold executes immediately, otherwise the motion awaits votes
+            if (threshold > 1) {
+              toast.success('Verification proposed to the council');
+              return;
+            }
+
             // Success - update local state
             setInstitutions(prev =>
               prev.map(inst =>
@@ -166,7 +174,12 @@ export default function AdminVerification() {
       console.log('🔐 Starting revocation for:', institutionAddress);
 
       const injector = await web3FromAddress(account.address);
-      const tx = api.tx.did.revokeInstitution(institutionAddress);
+
+      // Revocation requires a third of the council
+      const revokeTx = api.tx.did.revokeInstitution(institutionAddress);
+      const members = (await api.query.council.members()) as unknown as unknown[];
+      const threshold = Math.max(1, Math.ceil(members.length / 3));
+      const tx = api.tx.council.propose(threshold, revokeTx, revokeTx.method.encodedLength);
 
       await tx.signAndSend(
         account.address,
@@ -176,7 +189,9 @@ export default function AdminVerification() {
             if (dispatchError) {
               toast.error('Revocation failed');
             } else {
-              toast.success('Institution revoked successfully');
+              toast.success(
+                threshold > 1 ? 'Revocation proposed to the council' : 'Institution revoked successfully'
+              );
               fetchInstitutions();
             }
             setRevoking(null);
@@ -220,7 +235,7 @@ export default function AdminVerification() {
               Please connect your wallet to access admin functions
             </p>
             <p className="text-xs text-muted-foreground">
-              Note: You need to use a sudo/root account to verify institutions
+              Note: You need to use a council member account to verify institutions
             </p>
           </CardContent>
         </Card>
@@ -250,8 +265,8 @@ export default function AdminVerification() {
           <div className="text-sm">
             <p className="font-semibold text-yellow-800 dark:text-yellow-400">Admin Access Required</p>
             <p className="text-yellow-700 dark:text-yellow-300 mt-1">
-              You must be using a sudo/root account (Alice, Bob, Charlie, etc.) to verify institutions.
-              Regular accounts cannot perform these operations.
+              You must be using a council member account to verify institutions.
+              Verification takes effect once a majority of the council approves the proposal.
             </p>
           </div>
         </div>
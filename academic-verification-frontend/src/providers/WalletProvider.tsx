@@ -111,7 +111,10 @@ export function WalletProvider({ children }: WalletProviderProps) {
     try {
       console.log('🔍 Checking for existing DID on blockchain for:', address);
 
-      const didDoc = await api.query.did?.didDocuments(address);
+      // Accounts act for the DID they are linked to
+      const linkedDid = await api.query.did?.accountDids(address);
+      const did = linkedDid && !linkedDid.isEmpty ? linkedDid.toString() : null;
+      const didDoc = did ? await api.query.did?.didDocuments(did) : null;
       
       if (didDoc && !didDoc.isEmpty) {
         const didData = didDoc.toJSON() as any;
@@ -124,11 +127,11 @@ export function WalletProvider({ children }: WalletProviderProps) {
           addedAt: didData.createdAt || Date.now(),
         })) || [];
 
-        useDIDStore.getState().setDID(address, publicKeys);
+        useDIDStore.getState().setDID(did!, publicKeys);
         useDIDStore.getState().setStatus(didData.active ? 'active' : 'inactive');
 
         // Check if this is an institution
-        const institution = await api.query.did?.institutions(address);
+        const institution = await api.query.did?.institutions(did!);
         if (institution && !institution.isEmpty) {
           const instData = institution.toJSON() as any;
           console.log('🏛️ Found institution data (raw):', instData);
@@ -10,6 +10,8 @@ interface Credential {
   issuedAt: number;
   expiresAt?: number;
   revoked: boolean;
+  expired?: boolean;
+  suspended?: boolean;
   metadata?: string;
 }
 
@@ -7,7 +7,19 @@
 
 use std::sync::Arc;
 
-use parachain_template_runtime::{opaque::Block, AccountId, Balance, Nonce};
+use did::{
+	runtime_api::{DidApi as DidRuntimeApi, DidResolution},
+	PublicKey, VerificationRelationship,
+};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{error::ErrorObject, ErrorObjectOwned},
+};
+use parachain_template_runtime::{
+	opaque::Block, AccountId, Balance, BlockNumber, DidIdentifier, Nonce,
+};
+use serde_json::{json, Value};
 
 use polkadot_sdk::*;
 
@@ -15,6 +27,8 @@ use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay};
+use sp_runtime::traits::Block as BlockT;
 
 /// A type representing all RPC extensions.
 pub type RpcExtension = jsonrpsee::RpcModule<()>;
@@ -41,6 +55,7 @@ where
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: DidRuntimeApi<Block, DidIdentifier, AccountId, BlockNumber>,
 	P: TransactionPool + Sync + Send + 'static,
 {
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
@@ -50,6 +65,316 @@ where
 	let FullDeps { client, pool } = deps;
 
 	module.merge(System::new(client.clone(), pool).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Did::new(client).into_rpc())?;
 	Ok(module)
 }
+
+/// DID method name used in identifiers, as in `did:academic:<ss58 identifier>`.
+pub const DID_METHOD: &str = "academic";
+
+/// DID resolution RPC methods.
+#[rpc(server)]
+pub trait DidRpcApi<BlockHash> {
+	/// Resolve a DID to a W3C DID resolution result.
+	///
+	/// `did` is either a `did:academic:<ss58>` identifier or the SS58 address of
+	/// an account, which resolves to the DID it is linked to. Passing `at`
+	/// resolves the DID as it was at that block. The document metadata gives the
+	/// blocks the DID was created and last updated at as `createdAtBlock` and
+	/// `updatedAtBlock`.
+	#[method(name = "did_resolve")]
+	fn resolve(&self, did: String, at: Option<BlockHash>) -> RpcResult<Value>;
+}
+
+/// Error codes returned by the DID RPC.
+pub enum DidRpcError {
+	/// The DID could not be parsed.
+	InvalidDid,
+	/// The call to the runtime failed.
+	RuntimeError,
+	/// No DID document exists for the subject.
+	NotFound,
+}
+
+impl From<DidRpcError> for i32 {
+	fn from(e: DidRpcError) -> i32 {
+		match e {
+			DidRpcError::InvalidDid => 1,
+			DidRpcError::RuntimeError => 2,
+			DidRpcError::NotFound => 3,
+		}
+	}
+}
+
+/// Resolves DID documents through the `DidApi` runtime API.
+pub struct Did<C> {
+	client: Arc<C>,
+}
+
+impl<C> Did<C> {
+	/// Create a new DID RPC handler.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+impl<C> DidRpcApiServer<<Block as BlockT>::Hash> for Did<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: DidRuntimeApi<Block, DidIdentifier, AccountId, BlockNumber>,
+{
+	fn resolve(&self, did: String, at: Option<<Block as BlockT>::Hash>) -> RpcResult<Value> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let runtime_error = |e: sp_api::ApiError| {
+			ErrorObject::owned(
+				DidRpcError::RuntimeError.into(),
+				"Unable to resolve DID.",
+				Some(e.to_string()),
+			)
+		};
+		let not_found =
+			|| ErrorObject::owned(DidRpcError::NotFound.into(), "DID not found.", Some(&did));
+
+		let api = self.client.runtime_api();
+		let subject = match parse_did(&did)? {
+			DidSubject::Did(subject) => subject,
+			DidSubject::Account(account) =>
+				api.did_by_account(at, account).map_err(runtime_error)?.ok_or_else(not_found)?,
+		};
+		let resolution = api.resolve(at, subject).map_err(runtime_error)?.ok_or_else(not_found)?;
+
+		// The controller is an account; the document names the DID it acts for
+		let controller =
+			api.did_by_account(at, resolution.controller.clone()).map_err(runtime_error)?;
+
+		Ok(did_resolution_json(resolution, controller))
+	}
+}
+
+/// What a string passed to `did_resolve` names.
+enum DidSubject {
+	/// A DID, by its identifier
+	Did(DidIdentifier),
+	/// An account, standing for the DID it is linked to
+	Account(AccountId),
+}
+
+/// Parse `did:academic:<ss58>` or a bare SS58 address.
+///
+/// DID identifiers are 32 bytes, and are SS58 encoded like account IDs.
+fn parse_did(did: &str) -> Result<DidSubject, ErrorObjectOwned> {
+	match did.strip_prefix("did:") {
+		Some(rest) => {
+			let identifier = rest
+				.strip_prefix(DID_METHOD)
+				.and_then(|rest| rest.strip_prefix(':'))
+				.ok_or_else(|| invalid_did(did))?;
+			let bytes = AccountId::from_ss58check(identifier).map_err(|_| invalid_did(did))?;
+			Ok(DidSubject::Did(DidIdentifier(bytes.into())))
+		},
+		None => AccountId::from_ss58check(did)
+			.map(DidSubject::Account)
+			.map_err(|_| invalid_did(did)),
+	}
+}
+
+fn invalid_did(did: &str) -> ErrorObjectOwned {
+	ErrorObject::owned(DidRpcError::InvalidDid.into(), "Invalid DID.", Some(did.to_string()))
+}
+
+fn did_identifier(did: &DidIdentifier) -> String {
+	format!("did:{}:{}", DID_METHOD, AccountId::from(did.0).to_ss58check())
+}
+
+/// Multibase (base58btc) encoding of a key prefixed with its multicodec code.
+fn public_key_multibase(public_key: &PublicKey) -> String {
+	let codec: &[u8] = match public_key {
+		PublicKey::Ed25519(_) => &[0xed, 0x01],
+		PublicKey::Sr25519(_) => &[0xef, 0x01],
+		PublicKey::Ecdsa(_) => &[0xe7, 0x01],
+		PublicKey::X25519(_) => &[0xec, 0x01],
+		PublicKey::P256(_) => &[0x80, 0x24],
+	};
+
+	let bytes = [codec, public_key.as_bytes()].concat();
+	format!("z{}", bs58::encode(bytes).into_string())
+}
+
+/// Render a runtime DID resolution as a W3C DID resolution result.
+///
+/// `controller` is the DID the controlling account is linked to; the document
+/// has no `controller` property when it is not linked to one.
+fn did_resolution_json(
+	resolution: DidResolution<DidIdentifier, AccountId, BlockNumber>,
+	controller: Option<DidIdentifier>,
+) -> Value {
+	let did = did_identifier(&resolution.did);
+	let method_id = |key_id: &[u8; 32]| format!("{}#{}", did, HexDisplay::from(key_id));
+
+	let verification_methods: Vec<Value> = resolution
+		.public_keys
+		.iter()
+		.map(|key| {
+			json!({
+				"id": method_id(&key.key_id),
+				"type": "Multikey",
+				"controller": &did,
+				"publicKeyMultibase": public_key_multibase(&key.public_key),
+			})
+		})
+		.collect();
+
+	let references = |relationship: VerificationRelationship| -> Vec<String> {
+		resolution
+			.public_keys
+			.iter()
+			.filter(|key| key.has_relationship(relationship))
+			.map(|key| method_id(&key.key_id))
+			.collect()
+	};
+
+	let services: Vec<Value> = resolution
+		.services
+		.iter()
+		.map(|service| {
+			json!({
+				"id": format!("{}#{}", did, String::from_utf8_lossy(&service.id)),
+				"type": String::from_utf8_lossy(&service.service_type),
+				"serviceEndpoint": String::from_utf8_lossy(&service.url),
+			})
+		})
+		.collect();
+
+	let mut document = json!({
+		"@context": [
+			"https://www.w3.org/ns/did/v1",
+			"https://w3id.org/security/multikey/v1",
+		],
+		"id": &did,
+		"verificationMethod": verification_methods,
+		"authentication": references(VerificationRelationship::Authentication),
+		"assertionMethod": references(VerificationRelationship::AssertionMethod),
+		"keyAgreement": references(VerificationRelationship::KeyAgreement),
+		"capabilityDelegation": references(VerificationRelationship::CapabilityDelegation),
+		"service": services,
+	});
+	if let Some(controller) = controller {
+		document["controller"] = json!(did_identifier(&controller));
+	}
+
+	json!({
+		"didDocument": document,
+		"didDocumentMetadata": {
+			"createdAtBlock": resolution.created_at,
+			"updatedAtBlock": resolution.updated_at,
+			"deactivated": !resolution.active,
+		},
+		"didResolutionMetadata": {
+			"contentType": "application/did+json",
+		},
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use did::{MaxRelationships, PublicKeyEntry};
+	use sp_runtime::BoundedVec;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	fn resolution(
+		public_keys: Vec<PublicKeyEntry>,
+	) -> DidResolution<DidIdentifier, AccountId, BlockNumber> {
+		DidResolution {
+			did: DidIdentifier([1u8; 32]),
+			controller: AccountId::from([2u8; 32]),
+			public_keys,
+			services: Vec::new(),
+			created_at: 3,
+			updated_at: 4,
+			active: true,
+		}
+	}
+
+	fn key_entry(
+		public_key: PublicKey,
+		relationships: Vec<VerificationRelationship>,
+	) -> PublicKeyEntry {
+		PublicKeyEntry {
+			key_id: public_key.key_id(),
+			public_key,
+			relationships: BoundedVec::<_, MaxRelationships>::truncate_from(relationships),
+		}
+	}
+
+	#[test]
+	fn parses_did_and_account() {
+		let alice = AccountId::from_ss58check(ALICE).unwrap();
+
+		let did = format!("did:{}:{}", DID_METHOD, ALICE);
+		assert!(matches!(
+			parse_did(&did),
+			Ok(DidSubject::Did(subject)) if subject == DidIdentifier(alice.clone().into())
+		));
+		assert!(matches!(parse_did(ALICE), Ok(DidSubject::Account(account)) if account == alice));
+
+		for invalid in ["did:other:".to_string() + ALICE, "did:academic".into(), "not-ss58".into()]
+		{
+			assert!(parse_did(&invalid).is_err(), "{invalid} should not parse");
+		}
+	}
+
+	#[test]
+	fn encodes_keys_as_multibase_multicodec() {
+		// Well-known prefixes of base58btc-encoded multicodec keys
+		assert_eq!(
+			public_key_multibase(&PublicKey::Ed25519([0u8; 32])),
+			"z6MkeTG3bFFSLYVU7VqhgZxqr6YzpaGrQtFMh1uvqGy1vDnP"
+		);
+		assert_eq!(
+			public_key_multibase(&PublicKey::X25519([1u8; 32])),
+			"z6LSbk6TfcGsgm1yEUdGxwqscTzF6JkKNfrySPPLYqh8Ti6U"
+		);
+
+		let encoded = public_key_multibase(&PublicKey::P256([2u8; 33]));
+		let decoded = bs58::decode(encoded.strip_prefix('z').unwrap()).into_vec().unwrap();
+		assert_eq!(decoded, [&[0x80, 0x24][..], &[2u8; 33]].concat());
+	}
+
+	#[test]
+	fn renders_keys_and_relationships() {
+		let key = PublicKey::Ed25519([5u8; 32]);
+		let entry = key_entry(key.clone(), vec![VerificationRelationship::Authentication]);
+		let method = format!(
+			"{}#{}",
+			did_identifier(&DidIdentifier([1u8; 32])),
+			HexDisplay::from(&entry.key_id)
+		);
+
+		let json = did_resolution_json(resolution(vec![entry]), None);
+		let document = &json["didDocument"];
+
+		assert_eq!(document["verificationMethod"][0]["id"], json!(method));
+		assert_eq!(
+			document["verificationMethod"][0]["publicKeyMultibase"],
+			json!(public_key_multibase(&key))
+		);
+		assert_eq!(document["authentication"], json!([method]));
+		assert_eq!(document["assertionMethod"], json!([]));
+		assert_eq!(json["didDocumentMetadata"]["createdAtBlock"], json!(3));
+		assert_eq!(json["didDocumentMetadata"]["updatedAtBlock"], json!(4));
+	}
+
+	#[test]
+	fn names_controller_did_only_when_linked() {
+		let json = did_resolution_json(resolution(Vec::new()), None);
+		assert!(json["didDocument"].get("controller").is_none());
+
+		let controller = DidIdentifier([9u8; 32]);
+		let json = did_resolution_json(resolution(Vec::new()), Some(controller));
+		assert_eq!(json["didDocument"]["controller"], json!(did_identifier(&controller)));
+	}
+}
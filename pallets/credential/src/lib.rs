@@ -12,18 +12,24 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod migrations;
 pub mod weights;
 pub use weights::*;
 
 #[frame::pallet]
 pub mod pallet {
-    use frame::{hashing, prelude::*};
+    use did::BalanceOf;
+    use frame::{hashing, prelude::*, traits::Time};
 
     pub use scale_info::prelude::vec::Vec;
 
     use crate::WeightInfo;
 
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     // ================== Credential Types ==================
@@ -57,6 +63,39 @@ pub mod pallet {
         Other,
     }
 
+    /// Maximum number of credential types a delegate can be restricted to
+    pub type MaxDelegateCredentialTypes = ConstU32<8>;
+
+    /// What a staff delegate may do on behalf of its institution
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+        DecodeWithMemTracking,
+    )]
+    pub struct DelegatePermissions {
+        /// May issue credentials in the institution's name
+        pub can_issue: bool,
+        /// May revoke the institution's credentials
+        pub can_revoke: bool,
+        /// May update the metadata of the institution's credentials
+        pub can_update_metadata: bool,
+        /// Credential types the permissions apply to; empty means all types
+        pub credential_types: BoundedVec<CredentialType, MaxDelegateCredentialTypes>,
+    }
+
+    impl DelegatePermissions {
+        /// Whether the permissions cover credentials of `credential_type`
+        pub fn covers(&self, credential_type: &CredentialType) -> bool {
+            self.credential_types.is_empty() || self.credential_types.contains(credential_type)
+        }
+    }
+
     /// Status of a credential
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub enum CredentialStatus {
@@ -66,6 +105,69 @@ pub mod pallet {
         Revoked,
         /// Credential has expired
         Expired,
+        /// Credential is suspended by its issuer pending review
+        Suspended,
+    }
+
+    /// Why an issuer suspended a credential
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+        DecodeWithMemTracking,
+    )]
+    pub enum SuspensionReason {
+        /// Academic-misconduct investigation into the holder
+        MisconductInvestigation,
+        /// Suspected error in the credential's issuance
+        IssuanceError,
+        /// Dispute over the credential raised with the issuer
+        Dispute,
+        /// Administrative hold, e.g. unpaid fees
+        Administrative,
+        /// Any other reason
+        Other,
+    }
+
+    /// Record of an ongoing suspension
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct Suspension<T: Config> {
+        /// Why the credential was suspended
+        pub reason: SuspensionReason,
+        /// Issuer that suspended the credential
+        pub suspended_by: T::DidIdentifier,
+        /// Block number when suspended
+        pub suspended_at: BlockNumberFor<T>,
+    }
+
+    /// Wall-clock moment of the configured time provider
+    pub type MomentOf<T> = <<T as Config>::TimeProvider as Time>::Moment;
+
+    /// Wall-clock window in which a credential is valid; either end may be open
+    #[derive(
+        Clone,
+        Copy,
+        Default,
+        Encode,
+        Decode,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+        DecodeWithMemTracking,
+    )]
+    pub struct ValidityWindow<Moment> {
+        /// Moment from which the credential is valid
+        pub valid_from: Option<Moment>,
+        /// Moment from which the credential is no longer valid
+        pub valid_until: Option<Moment>,
     }
 
     /// Core credential structure stored on-chain
@@ -77,9 +179,13 @@ pub mod pallet {
         /// Hash of the credential content (SHA256)
         pub credential_hash: [u8; 32],
         /// The holder of the credential
-        pub holder: T::AccountId,
+        pub holder: T::DidIdentifier,
         /// The issuer (must be a verified institution)
-        pub issuer: T::AccountId,
+        pub issuer: T::DidIdentifier,
+        /// Staff delegate that signed the issuance, if not the issuer itself
+        pub issued_by: Option<T::DidIdentifier>,
+        /// DID key that authorised the issuance, if issued through `submit_did_call`
+        pub signed_with: Option<[u8; 32]>,
         /// Type of credential
         pub credential_type: CredentialType,
         /// Optional metadata (e.g., degree name, field of study)
@@ -88,6 +194,10 @@ pub mod pallet {
         pub issued_at: BlockNumberFor<T>,
         /// Optional expiration block number
         pub expires_at: Option<BlockNumberFor<T>>,
+        /// Optional moment from which the credential is valid
+        pub valid_from: Option<MomentOf<T>>,
+        /// Optional moment from which the credential is no longer valid
+        pub valid_until: Option<MomentOf<T>>,
         /// Current status
         pub status: CredentialStatus,
     }
@@ -100,7 +210,9 @@ pub mod pallet {
     }
 
     #[pallet::config]
-    pub trait Config: frame_system::Config + did::Config {
+    pub trait Config:
+        frame_system::Config + did::Config<RuntimeHoldReason: From<HoldReason>>
+    {
         /// The overarching event type
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
@@ -112,10 +224,47 @@ pub mod pallet {
         #[pallet::constant]
         type MaxCredentialsPerHolder: Get<u32>;
 
+        /// Base deposit held from the issuer for each credential, on top of
+        /// the DID pallet's per-byte deposit
+        #[pallet::constant]
+        type CredentialDeposit: Get<BalanceOf<Self>>;
+
+        /// Base deposit held from an institution for each staff delegate, on
+        /// top of the DID pallet's per-byte deposit
+        #[pallet::constant]
+        type DelegateDeposit: Get<BalanceOf<Self>>;
+
+        /// Maximum number of staff delegates per institution
+        #[pallet::constant]
+        type MaxDelegates: Get<u32>;
+
+        /// Maximum number of credentials expired in `on_initialize`; any
+        /// remainder is expired in `on_idle` or the following blocks
+        #[pallet::constant]
+        type MaxExpiriesPerBlock: Get<u32>;
+
+        /// Source of wall-clock time for credential validity windows,
+        /// usually `pallet_timestamp`
+        type TimeProvider: Time;
+
+        /// Expected time between blocks, in `TimeProvider` units, used to
+        /// queue the end of a validity window in the expiry queue
+        #[pallet::constant]
+        type ExpectedBlockTime: Get<MomentOf<Self>>;
+
         ///Weight information for extrinsics
         type WeightInfo: WeightInfo;
     }
 
+    /// Reasons for this pallet placing a hold on funds
+    #[pallet::composite_enum]
+    pub enum HoldReason {
+        /// Deposit for a stored credential
+        CredentialDeposit,
+        /// Deposit for an institution's staff delegate
+        DelegateDeposit,
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -123,22 +272,61 @@ pub mod pallet {
         CredentialIssued {
             credential_id: [u8; 32],
             credential_hash: [u8; 32],
-            holder: T::AccountId,
-            issuer: T::AccountId,
+            holder: T::DidIdentifier,
+            issuer: T::DidIdentifier,
+            /// Staff delegate that signed on the issuer's behalf
+            issued_by: Option<T::DidIdentifier>,
             credential_type: CredentialType,
         },
         /// A credential was revoked
         CredentialRevoked {
             credential_id: [u8; 32],
-            revoked_by: T::AccountId,
+            revoked_by: T::DidIdentifier,
+        },
+        /// A revoked or expired credential was removed and its deposit released
+        CredentialRemoved {
+            credential_id: [u8; 32],
+            removed_by: T::DidIdentifier,
         },
         /// A credential was verified
         CredentialVerified {
             credential_id: [u8; 32],
             verified_by: T::AccountId,
+            /// Set when the issuer has since closed
+            issuer_closed_at: Option<BlockNumberFor<T>>,
         },
         /// Credential metadata was updated
-        CredentialMetadataUpdated { credential_id: [u8; 32] },
+        CredentialMetadataUpdated {
+            credential_id: [u8; 32],
+            updated_by: T::DidIdentifier,
+        },
+        /// An institution authorised or changed a staff delegate
+        DelegateSet {
+            institution: T::DidIdentifier,
+            delegate: T::DidIdentifier,
+            permissions: DelegatePermissions,
+        },
+        /// An institution removed a staff delegate
+        DelegateRemoved {
+            institution: T::DidIdentifier,
+            delegate: T::DidIdentifier,
+        },
+        /// A credential passed its expiration block
+        CredentialExpired {
+            credential_id: [u8; 32],
+            holder: T::DidIdentifier,
+        },
+        /// A credential was suspended by its issuer
+        CredentialSuspended {
+            credential_id: [u8; 32],
+            suspended_by: T::DidIdentifier,
+            reason: SuspensionReason,
+        },
+        /// A suspended credential was reinstated by its issuer
+        CredentialReinstated {
+            credential_id: [u8; 32],
+            reinstated_by: T::DidIdentifier,
+        },
     }
 
     #[pallet::error]
@@ -151,6 +339,12 @@ pub mod pallet {
         NotAuthorized,
         /// Issuer is not a verified institution
         IssuerNotVerified,
+        /// Issuer's institution verification has lapsed and must be renewed
+        IssuerAccreditationLapsed,
+        /// Issuer has closed and can no longer issue credentials
+        IssuerClosed,
+        /// Credential postdates the closure of its issuer
+        IssuedAfterIssuerClosure,
         /// Issuer has no DID
         IssuerHasNoDid,
         /// Holder has no DID
@@ -169,6 +363,28 @@ pub mod pallet {
         CredentialNotActive,
         /// DID is not active
         DidNotActive,
+        /// Account is not a registered institution
+        NotAnInstitution,
+        /// Delegate is the institution itself or has no DID
+        InvalidDelegate,
+        /// Delegate permissions grant nothing
+        InvalidDelegatePermissions,
+        /// Delegate not found
+        DelegateNotFound,
+        /// Institution already has the maximum number of delegates
+        TooManyDelegates,
+        /// Credential was authorised by a DID key after it was compromised
+        SigningKeyCompromised,
+        /// Credential validity window ends before it starts
+        InvalidValidityWindow,
+        /// Credential validity window has not started yet
+        CredentialNotYetValid,
+        /// Credential is suspended by its issuer
+        CredentialSuspended,
+        /// Credential is not suspended
+        CredentialNotSuspended,
+        /// Only revoked or expired credentials can be removed
+        CredentialNotRemovable,
     }
 
     // ================== Storage Items ==================
@@ -189,7 +405,7 @@ pub mod pallet {
     pub type CredentialsByHolder<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
-        T::AccountId,
+        T::DidIdentifier,
         BoundedVec<CredentialReference, T::MaxCredentialsPerHolder>,
         ValueQuery,
     >;
@@ -200,7 +416,7 @@ pub mod pallet {
     pub type CredentialsByIssuer<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
-        T::AccountId,
+        T::DidIdentifier,
         BoundedVec<[u8; 32], T::MaxCredentialsPerHolder>,
         ValueQuery,
     >;
@@ -215,31 +431,533 @@ pub mod pallet {
         [u8; 32], // credential_id
     >;
 
+    /// Deposits held for credentials: credential_id => deposit
+    #[pallet::storage]
+    #[pallet::getter(fn credential_deposits)]
+    pub type CredentialDeposits<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        [u8; 32], // credential_id
+        did::Deposit<T::AccountId, BalanceOf<T>>,
+    >;
+
+    /// Staff delegates: institution => delegate => permissions
+    #[pallet::storage]
+    #[pallet::getter(fn delegates)]
+    pub type Delegates<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier, // institution
+        Blake2_128Concat,
+        T::DidIdentifier, // delegate
+        DelegatePermissions,
+    >;
+
+    /// Deposits held for staff delegates: institution => delegate => deposit
+    #[pallet::storage]
+    #[pallet::getter(fn delegate_deposits)]
+    pub type DelegateDeposits<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier, // institution
+        Blake2_128Concat,
+        T::DidIdentifier, // delegate
+        did::Deposit<T::AccountId, BalanceOf<T>>,
+    >;
+
     /// Counter for generating unique credential IDs
     #[pallet::storage]
     #[pallet::getter(fn next_credential_id)]
     pub type NextCredentialId<T: Config> = StorageValue<_, u64, ValueQuery>;
 
+    /// Ongoing suspensions: credential_id => Suspension
+    #[pallet::storage]
+    #[pallet::getter(fn suspensions)]
+    pub type Suspensions<T: Config> =
+        StorageMap<_, Blake2_128Concat, [u8; 32], Suspension<T>, OptionQuery>;
+
+    /// Expiry queue: first block a credential is no longer valid => credential_id
+    #[pallet::storage]
+    #[pallet::getter(fn credential_expiries)]
+    pub type CredentialExpiries<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        [u8; 32], // credential_id
+        (),
+        OptionQuery,
+    >;
+
+    /// First block of the expiry queue not yet fully processed
+    #[pallet::storage]
+    #[pallet::getter(fn expiry_cursor)]
+    pub type ExpiryCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    // ================== Hooks ==================
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let limit = <T as Config>::WeightInfo::expire_credential()
+                .saturating_mul(T::MaxExpiriesPerBlock::get().into());
+            Self::process_expiries(now, limit)
+        }
+
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::process_expiries(now, remaining_weight)
+        }
+    }
+
     // ================== Dispatchable Functions ==================
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Issue a new credential
+        /// Issue a new credential from the DID the caller is linked to
+        ///
+        /// `expires_at` limits validity by block number and is enforced by the
+        /// expiry queue; `validity` limits it by timestamp and is checked when
+        /// the credential is verified.
         #[pallet::call_index(0)]
         #[pallet::weight(<T as Config>::WeightInfo::issue_credential())]
         pub fn issue_credential(
             origin: OriginFor<T>,
-            holder: T::AccountId,
+            holder: T::DidIdentifier,
             credential_hash: [u8; 32],
             credential_type: CredentialType,
             metadata: BoundedVec<u8, T::MaxMetadataSize>,
             expires_at: Option<BlockNumberFor<T>>,
+            validity: ValidityWindow<MomentOf<T>>,
+        ) -> DispatchResult {
+            let signed_with = did::Pallet::<T>::signing_key(&origin);
+            let issuer = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::IssuerHasNoDid)?;
+
+            Self::do_issue_credential(
+                issuer,
+                None,
+                signed_with,
+                holder,
+                credential_hash,
+                credential_type,
+                metadata,
+                expires_at,
+                validity,
+            )
+        }
+
+        /// Revoke a credential (only by the issuer, its successor or a
+        /// delegate allowed to revoke)
+        #[pallet::call_index(1)]
+        #[pallet::weight(<T as Config>::WeightInfo::revoke_credential())]
+        pub fn revoke_credential(origin: OriginFor<T>, credential_id: [u8; 32]) -> DispatchResult {
+            let revoker = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            // Get credential
+            let mut credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            Self::managing_institution(&revoker, &credential, |p| p.can_revoke)?;
+
+            // Ensure not already revoked
+            ensure!(
+                credential.status != CredentialStatus::Revoked,
+                Error::<T>::CredentialAlreadyRevoked
+            );
+
+            // Update status, ending any suspension
+            credential.status = CredentialStatus::Revoked;
+            Credentials::<T>::insert(credential_id, credential);
+            Suspensions::<T>::remove(credential_id);
+
+            // Emit event
+            Self::deposit_event(Event::CredentialRevoked {
+                credential_id,
+                revoked_by: revoker,
+            });
+
+            Ok(())
+        }
+
+        /// Verify a credential by hash (read-only verification, emits event)
+        #[pallet::call_index(2)]
+        #[pallet::weight(<T as Config>::WeightInfo::verify_credential())]
+        pub fn verify_credential(
+            origin: OriginFor<T>,
+            credential_hash: [u8; 32],
         ) -> DispatchResult {
-            let issuer = ensure_signed(origin)?;
+            let verifier = ensure_signed(origin)?;
+
+            // Look up credential by hash
+            let credential_id = CredentialByHash::<T>::get(credential_hash)
+                .ok_or(Error::<T>::CredentialNotFound)?;
+
+            let credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            // Check if credential is active
+            match credential.status {
+                CredentialStatus::Active => {}
+                CredentialStatus::Expired => return Err(Error::<T>::CredentialExpired.into()),
+                CredentialStatus::Suspended => return Err(Error::<T>::CredentialSuspended.into()),
+                _ => return Err(Error::<T>::CredentialNotActive.into()),
+            }
+
+            // Check expiration if set
+            if let Some(expires_at) = credential.expires_at {
+                let current_block = frame_system::Pallet::<T>::block_number();
+                ensure!(current_block <= expires_at, Error::<T>::CredentialExpired);
+            }
+
+            // Check the validity window if set
+            let now = T::TimeProvider::now();
+            if let Some(valid_from) = credential.valid_from {
+                ensure!(now >= valid_from, Error::<T>::CredentialNotYetValid);
+            }
+            if let Some(valid_until) = credential.valid_until {
+                ensure!(now < valid_until, Error::<T>::CredentialExpired);
+            }
+
+            // Credentials issued before the issuer closed remain valid
+            let issuer_closed_at = did::ClosedInstitutions::<T>::get(&credential.issuer);
+            if let Some(closed_at) = issuer_closed_at {
+                ensure!(
+                    credential.issued_at <= closed_at,
+                    Error::<T>::IssuedAfterIssuerClosure
+                );
+            }
+
+            // Signatures made before the signing key was compromised remain valid
+            if let Some(key_id) = credential.signed_with {
+                let signer = credential.issued_by.as_ref().unwrap_or(&credential.issuer);
+                if let Some(since) = did::Pallet::<T>::compromised_since(signer, &key_id) {
+                    ensure!(
+                        credential.issued_at < since,
+                        Error::<T>::SigningKeyCompromised
+                    );
+                }
+            }
+
+            // Emit verification event
+            Self::deposit_event(Event::CredentialVerified {
+                credential_id,
+                verified_by: verifier,
+                issuer_closed_at,
+            });
+
+            Ok(())
+        }
+
+        /// Update credential metadata (only by the issuer, its successor or a
+        /// delegate allowed to update metadata)
+        ///
+        /// The credential deposit moves to the controller of the institution
+        /// making the update.
+        #[pallet::call_index(3)]
+        #[pallet::weight(<T as Config>::WeightInfo::update_credential_metadata())]
+        pub fn update_credential_metadata(
+            origin: OriginFor<T>,
+            credential_id: [u8; 32],
+            new_metadata: BoundedVec<u8, T::MaxMetadataSize>,
+        ) -> DispatchResult {
+            let updater = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            // Get credential
+            let mut credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            let institution =
+                Self::managing_institution(&updater, &credential, |p| p.can_update_metadata)?;
+
+            // Ensure credential is active
+            ensure!(
+                credential.status == CredentialStatus::Active,
+                Error::<T>::CredentialNotActive
+            );
+
+            // Update metadata
+            credential.metadata = new_metadata;
+            Self::update_credential_deposit(&credential, &Self::controller_of(&institution)?)?;
+            Credentials::<T>::insert(credential_id, credential);
+
+            // Emit event
+            Self::deposit_event(Event::CredentialMetadataUpdated {
+                credential_id,
+                updated_by: updater,
+            });
+
+            Ok(())
+        }
+
+        /// Authorise the DID `delegate` to act for the caller's institution,
+        /// replacing any permissions it already has
+        ///
+        /// A deposit sized to the permissions is held from the institution's
+        /// controller.
+        #[pallet::call_index(4)]
+        #[pallet::weight(<T as Config>::WeightInfo::set_delegate())]
+        pub fn set_delegate(
+            origin: OriginFor<T>,
+            delegate: T::DidIdentifier,
+            permissions: DelegatePermissions,
+        ) -> DispatchResult {
+            let institution = did::Pallet::<T>::acting_did(origin)?
+                .filter(|did| did::Institutions::<T>::contains_key(did))
+                .ok_or(Error::<T>::NotAnInstitution)?;
+            ensure!(
+                !did::ClosedInstitutions::<T>::contains_key(&institution),
+                Error::<T>::IssuerClosed
+            );
+            ensure!(
+                delegate != institution && did::DidDocuments::<T>::contains_key(&delegate),
+                Error::<T>::InvalidDelegate
+            );
+            ensure!(
+                permissions.can_issue || permissions.can_revoke || permissions.can_update_metadata,
+                Error::<T>::InvalidDelegatePermissions
+            );
+            if !Delegates::<T>::contains_key(&institution, &delegate) {
+                ensure!(
+                    Delegates::<T>::iter_key_prefix(&institution).count()
+                        < T::MaxDelegates::get() as usize,
+                    Error::<T>::TooManyDelegates
+                );
+            }
+
+            let amount = did::Pallet::<T>::storage_deposit(
+                T::DelegateDeposit::get(),
+                permissions.encoded_size(),
+            );
+            let deposit = did::Pallet::<T>::update_deposit(
+                &HoldReason::DelegateDeposit.into(),
+                DelegateDeposits::<T>::get(&institution, &delegate),
+                &Self::controller_of(&institution)?,
+                amount,
+            )?;
+            DelegateDeposits::<T>::insert(&institution, &delegate, deposit);
+            Delegates::<T>::insert(&institution, &delegate, permissions.clone());
+
+            Self::deposit_event(Event::DelegateSet {
+                institution,
+                delegate,
+                permissions,
+            });
+
+            Ok(())
+        }
+
+        /// Remove a delegate of the caller's institution and release its deposit
+        #[pallet::call_index(5)]
+        #[pallet::weight(<T as Config>::WeightInfo::remove_delegate())]
+        pub fn remove_delegate(origin: OriginFor<T>, delegate: T::DidIdentifier) -> DispatchResult {
+            let institution =
+                did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAnInstitution)?;
+
+            Delegates::<T>::take(&institution, &delegate).ok_or(Error::<T>::DelegateNotFound)?;
+            if let Some(deposit) = DelegateDeposits::<T>::take(&institution, &delegate) {
+                did::Pallet::<T>::release_deposit(&HoldReason::DelegateDeposit.into(), deposit)?;
+            }
+
+            Self::deposit_event(Event::DelegateRemoved {
+                institution,
+                delegate,
+            });
+
+            Ok(())
+        }
+
+        /// Issue a credential on behalf of `institution` as one of its delegates
+        ///
+        /// The institution is recorded as the issuer and its controller pays the
+        /// deposit; the DID of the signing delegate is recorded in `issued_by`.
+        #[pallet::call_index(6)]
+        #[pallet::weight(<T as Config>::WeightInfo::issue_credential_on_behalf())]
+        #[allow(clippy::too_many_arguments)]
+        pub fn issue_credential_on_behalf(
+            origin: OriginFor<T>,
+            institution: T::DidIdentifier,
+            holder: T::DidIdentifier,
+            credential_hash: [u8; 32],
+            credential_type: CredentialType,
+            metadata: BoundedVec<u8, T::MaxMetadataSize>,
+            expires_at: Option<BlockNumberFor<T>>,
+            validity: ValidityWindow<MomentOf<T>>,
+        ) -> DispatchResult {
+            let signed_with = did::Pallet::<T>::signing_key(&origin);
+            let delegate =
+                did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            let permissions =
+                Delegates::<T>::get(&institution, &delegate).ok_or(Error::<T>::NotAuthorized)?;
+            ensure!(
+                permissions.can_issue && permissions.covers(&credential_type),
+                Error::<T>::NotAuthorized
+            );
+
+            Self::do_issue_credential(
+                institution,
+                Some(delegate),
+                signed_with,
+                holder,
+                credential_hash,
+                credential_type,
+                metadata,
+                expires_at,
+                validity,
+            )
+        }
+
+        /// Suspend an active credential pending review (only by the issuer or
+        /// its successor)
+        #[pallet::call_index(7)]
+        #[pallet::weight(<T as Config>::WeightInfo::suspend_credential())]
+        pub fn suspend_credential(
+            origin: OriginFor<T>,
+            credential_id: [u8; 32],
+            reason: SuspensionReason,
+        ) -> DispatchResult {
+            let issuer = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            let mut credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            // Delegates may not suspend
+            Self::managing_institution(&issuer, &credential, |_| false)?;
+
+            ensure!(
+                credential.status == CredentialStatus::Active,
+                Error::<T>::CredentialNotActive
+            );
+
+            credential.status = CredentialStatus::Suspended;
+            Credentials::<T>::insert(credential_id, credential);
+            Suspensions::<T>::insert(
+                credential_id,
+                Suspension {
+                    reason: reason.clone(),
+                    suspended_by: issuer.clone(),
+                    suspended_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::CredentialSuspended {
+                credential_id,
+                suspended_by: issuer,
+                reason,
+            });
+
+            Ok(())
+        }
+
+        /// Reinstate a suspended credential (only by the issuer or its successor)
+        ///
+        /// A credential whose expiration block or validity window passed while it
+        /// was suspended is marked as expired instead.
+        #[pallet::call_index(8)]
+        #[pallet::weight(<T as Config>::WeightInfo::reinstate_credential())]
+        pub fn reinstate_credential(
+            origin: OriginFor<T>,
+            credential_id: [u8; 32],
+        ) -> DispatchResult {
+            let issuer = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            let mut credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            Self::managing_institution(&issuer, &credential, |_| false)?;
+
+            ensure!(
+                credential.status == CredentialStatus::Suspended,
+                Error::<T>::CredentialNotSuspended
+            );
+
+            credential.status = CredentialStatus::Active;
+            let expired = Self::is_lapsed(&credential);
+            Credentials::<T>::insert(credential_id, credential);
+            Suspensions::<T>::remove(credential_id);
+
+            Self::deposit_event(Event::CredentialReinstated {
+                credential_id,
+                reinstated_by: issuer,
+            });
+
+            // The expiry queue skipped it while suspended
+            if expired {
+                Self::expire(credential_id);
+            }
+
+            Ok(())
+        }
+
+        /// Remove a revoked or expired credential and release its deposit
+        /// (only by the issuer or its successor)
+        #[pallet::call_index(9)]
+        #[pallet::weight(<T as Config>::WeightInfo::remove_credential())]
+        pub fn remove_credential(origin: OriginFor<T>, credential_id: [u8; 32]) -> DispatchResult {
+            let issuer = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NotAuthorized)?;
+
+            let credential =
+                Credentials::<T>::get(credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+
+            // Delegates may not remove
+            Self::managing_institution(&issuer, &credential, |_| false)?;
+
+            ensure!(
+                matches!(
+                    credential.status,
+                    CredentialStatus::Revoked | CredentialStatus::Expired
+                ),
+                Error::<T>::CredentialNotRemovable
+            );
+
+            Self::do_remove_credential(credential_id, &credential)?;
+
+            Self::deposit_event(Event::CredentialRemoved {
+                credential_id,
+                removed_by: issuer,
+            });
+
+            Ok(())
+        }
+    }
+
+    // ================== Helper Functions ==================
+
+    impl<T: Config> Pallet<T> {
+        /// Issue a credential from `issuer`, signed by the staff delegate
+        /// `issued_by` if set and authorised by the DID key `signed_with` if set
+        #[allow(clippy::too_many_arguments)]
+        fn do_issue_credential(
+            issuer: T::DidIdentifier,
+            issued_by: Option<T::DidIdentifier>,
+            signed_with: Option<[u8; 32]>,
+            holder: T::DidIdentifier,
+            credential_hash: [u8; 32],
+            credential_type: CredentialType,
+            metadata: BoundedVec<u8, T::MaxMetadataSize>,
+            expires_at: Option<BlockNumberFor<T>>,
+            validity: ValidityWindow<MomentOf<T>>,
+        ) -> DispatchResult {
+            let ValidityWindow {
+                valid_from,
+                valid_until,
+            } = validity;
+            if let (Some(from), Some(until)) = (valid_from, valid_until) {
+                ensure!(from < until, Error::<T>::InvalidValidityWindow);
+            }
 
             // Verify issuer is a verified institution
             let institution =
                 did::Institutions::<T>::get(&issuer).ok_or(Error::<T>::IssuerNotVerified)?;
+            ensure!(
+                !did::ClosedInstitutions::<T>::contains_key(&issuer),
+                Error::<T>::IssuerClosed
+            );
+            if let Some(verified_until) = institution.verified_until {
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() < verified_until,
+                    Error::<T>::IssuerAccreditationLapsed
+                );
+            }
             ensure!(institution.verified, Error::<T>::IssuerNotVerified);
 
             // Verify issuer has active DID
@@ -254,7 +972,7 @@ pub mod pallet {
 
             // Ensure credential hash doesn't already exist
             ensure!(
-                !CredentialByHash::<T>::contains_key(&credential_hash),
+                !CredentialByHash::<T>::contains_key(credential_hash),
                 Error::<T>::CredentialAlreadyExists
             );
 
@@ -269,15 +987,22 @@ pub mod pallet {
                 credential_hash,
                 holder: holder.clone(),
                 issuer: issuer.clone(),
+                issued_by: issued_by.clone(),
+                signed_with,
                 credential_type: credential_type.clone(),
                 metadata,
                 issued_at: frame_system::Pallet::<T>::block_number(),
                 expires_at,
+                valid_from,
+                valid_until,
                 status: CredentialStatus::Active,
             };
 
+            // Hold deposit from the issuer's controller
+            Self::update_credential_deposit(&credential, &issuer_did.controller)?;
+
             // Store credential
-            Credentials::<T>::insert(&credential_id, credential);
+            Credentials::<T>::insert(credential_id, credential);
 
             // Update holder index
             CredentialsByHolder::<T>::try_mutate(&holder, |credentials| -> DispatchResult {
@@ -300,7 +1025,17 @@ pub mod pallet {
             })?;
 
             // Store hash mapping
-            CredentialByHash::<T>::insert(&credential_hash, credential_id);
+            CredentialByHash::<T>::insert(credential_hash, credential_id);
+
+            // Queue the expiry, at the next block if already past
+            if let Some(expires_at) = expires_at {
+                let now = frame_system::Pallet::<T>::block_number();
+                let expiry = expires_at.max(now).saturating_add(One::one());
+                CredentialExpiries::<T>::insert(expiry, credential_id, ());
+            }
+            if let Some(valid_until) = valid_until {
+                Self::queue_validity_end(credential_id, valid_until);
+            }
 
             // Emit event
             Self::deposit_event(Event::CredentialIssued {
@@ -308,129 +1043,302 @@ pub mod pallet {
                 credential_hash,
                 holder,
                 issuer,
+                issued_by,
                 credential_type,
             });
 
             Ok(())
         }
 
-        /// Revoke a credential (only by issuer)
-        #[pallet::call_index(1)]
-        #[pallet::weight(<T as Config>::WeightInfo::revoke_credential())]
-        pub fn revoke_credential(origin: OriginFor<T>, credential_id: [u8; 32]) -> DispatchResult {
-            let revoker = ensure_signed(origin)?;
-
-            // Get credential
-            let mut credential =
-                Credentials::<T>::get(&credential_id).ok_or(Error::<T>::CredentialNotFound)?;
-
-            // Only issuer can revoke
-            ensure!(credential.issuer == revoker, Error::<T>::NotAuthorized);
-
-            // Ensure not already revoked
-            ensure!(
-                credential.status != CredentialStatus::Revoked,
-                Error::<T>::CredentialAlreadyRevoked
+        /// Resize `owner`'s deposit for `credential` to its encoded size
+        fn update_credential_deposit(
+            credential: &Credential<T>,
+            owner: &T::AccountId,
+        ) -> DispatchResult {
+            let amount = did::Pallet::<T>::storage_deposit(
+                T::CredentialDeposit::get(),
+                credential.encoded_size(),
             );
+            let deposit = did::Pallet::<T>::update_deposit(
+                &HoldReason::CredentialDeposit.into(),
+                CredentialDeposits::<T>::get(credential.credential_id),
+                owner,
+                amount,
+            )?;
+            CredentialDeposits::<T>::insert(credential.credential_id, deposit);
+            Ok(())
+        }
 
-            // Update status
-            credential.status = CredentialStatus::Revoked;
-            Credentials::<T>::insert(&credential_id, credential);
-
-            // Emit event
-            Self::deposit_event(Event::CredentialRevoked {
-                credential_id,
-                revoked_by: revoker,
+        /// Delete `credential` and every index entry pointing at it, releasing
+        /// its deposit
+        fn do_remove_credential(
+            credential_id: [u8; 32],
+            credential: &Credential<T>,
+        ) -> DispatchResult {
+            if let Some(deposit) = CredentialDeposits::<T>::take(credential_id) {
+                did::Pallet::<T>::release_deposit(&HoldReason::CredentialDeposit.into(), deposit)?;
+            }
+            Credentials::<T>::remove(credential_id);
+            CredentialByHash::<T>::remove(credential.credential_hash);
+            Suspensions::<T>::remove(credential_id);
+            CredentialsByHolder::<T>::mutate(&credential.holder, |credentials| {
+                credentials.retain(|reference| reference.credential_id != credential_id)
+            });
+            CredentialsByIssuer::<T>::mutate(&credential.issuer, |credentials| {
+                credentials.retain(|id| *id != credential_id)
             });
-
             Ok(())
         }
 
-        /// Verify a credential by hash (read-only verification, emits event)
-        #[pallet::call_index(2)]
-        #[pallet::weight(<T as Config>::WeightInfo::verify_credential())]
-        pub fn verify_credential(
-            origin: OriginFor<T>,
-            credential_hash: [u8; 32],
-        ) -> DispatchResult {
-            let verifier = ensure_signed(origin)?;
+        /// Hold `deposit` from `owner` instead, keeping its amount
+        fn transfer_deposit(
+            reason: &T::RuntimeHoldReason,
+            deposit: did::Deposit<T::AccountId, BalanceOf<T>>,
+            owner: &T::AccountId,
+        ) -> Result<did::Deposit<T::AccountId, BalanceOf<T>>, DispatchError> {
+            let amount = deposit.amount;
+            did::Pallet::<T>::update_deposit(reason, Some(deposit), owner, amount)
+        }
 
-            // Look up credential by hash
-            let credential_id = CredentialByHash::<T>::get(&credential_hash)
-                .ok_or(Error::<T>::CredentialNotFound)?;
+        /// Account currently controlling `did`, which pays deposits on its behalf
+        fn controller_of(did: &T::DidIdentifier) -> Result<T::AccountId, DispatchError> {
+            let did_doc = did::DidDocuments::<T>::get(did).ok_or(Error::<T>::IssuerHasNoDid)?;
+            Ok(did_doc.controller)
+        }
 
-            let credential =
-                Credentials::<T>::get(&credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+        /// Institution on whose behalf `who` manages `credential`
+        ///
+        /// Issuers, and institutions that took them over in a merger, manage
+        /// their credentials directly. Delegates of the issuer act for it when
+        /// `allowed` accepts their permissions for the credential's type.
+        fn managing_institution(
+            who: &T::DidIdentifier,
+            credential: &Credential<T>,
+            allowed: impl Fn(&DelegatePermissions) -> bool,
+        ) -> Result<T::DidIdentifier, DispatchError> {
+            if credential.issuer == *who || did::Pallet::<T>::is_successor(who, &credential.issuer)
+            {
+                return Ok(who.clone());
+            }
 
-            // Check if credential is active
+            let permissions =
+                Delegates::<T>::get(&credential.issuer, who).ok_or(Error::<T>::NotAuthorized)?;
             ensure!(
-                credential.status == CredentialStatus::Active,
-                Error::<T>::CredentialNotActive
+                allowed(&permissions) && permissions.covers(&credential.credential_type),
+                Error::<T>::NotAuthorized
             );
 
-            // Check expiration if set
-            if let Some(expires_at) = credential.expires_at {
-                let current_block = frame_system::Pallet::<T>::block_number();
-                ensure!(current_block <= expires_at, Error::<T>::CredentialExpired);
+            Ok(credential.issuer.clone())
+        }
+
+        /// Generate a unique credential ID
+        fn generate_credential_id(
+            counter: u64,
+            issuer: &T::DidIdentifier,
+            holder: &T::DidIdentifier,
+        ) -> [u8; 32] {
+            let mut data = Vec::new();
+            data.extend_from_slice(&counter.to_le_bytes());
+            data.extend_from_slice(&issuer.encode());
+            data.extend_from_slice(&holder.encode());
+            hashing::blake2_256(&data)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Expire the credentials queued up to block `now`, using at most `limit`
+        ///
+        /// Resumes from `ExpiryCursor`, so a backlog left by a busy block is
+        /// worked off in `on_idle` or later blocks.
+        fn process_expiries(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+            let db_weight = T::DbWeight::get();
+            let mut weight = db_weight.reads_writes(1, 1);
+            if weight.any_gt(limit) {
+                return Weight::zero();
             }
 
-            // Emit verification event
-            Self::deposit_event(Event::CredentialVerified {
-                credential_id,
-                verified_by: verifier,
+            let start = ExpiryCursor::<T>::get();
+            let mut cursor = start;
+            let expire_weight = <T as Config>::WeightInfo::expire_credential();
+
+            while cursor <= now && weight.saturating_add(expire_weight).all_lte(limit) {
+                match CredentialExpiries::<T>::iter_key_prefix(cursor).next() {
+                    Some(credential_id) => {
+                        CredentialExpiries::<T>::remove(cursor, credential_id);
+                        Self::expire(credential_id);
+                        weight.saturating_accrue(expire_weight);
+                    }
+                    None => {
+                        cursor.saturating_inc();
+                        weight.saturating_accrue(db_weight.reads(1));
+                    }
+                }
+            }
+
+            if cursor != start {
+                ExpiryCursor::<T>::put(cursor);
+            }
+
+            weight
+        }
+
+        /// Mark an active credential as expired once past its expiration block
+        /// or the end of its validity window
+        ///
+        /// Blocks can be slower than `ExpectedBlockTime`, so a window that has
+        /// not ended yet is queued again, including while it is suspended.
+        fn expire(credential_id: [u8; 32]) {
+            Credentials::<T>::mutate(credential_id, |credential_opt| {
+                let Some(credential) = credential_opt else {
+                    return;
+                };
+                let lapsed = Self::is_lapsed(credential);
+                match credential.status {
+                    CredentialStatus::Active if lapsed => {
+                        credential.status = CredentialStatus::Expired;
+                        Self::deposit_event(Event::CredentialExpired {
+                            credential_id,
+                            holder: credential.holder.clone(),
+                        });
+                    }
+                    // Suspended credentials stay queued, as reinstating only
+                    // catches those that have already lapsed
+                    CredentialStatus::Active | CredentialStatus::Suspended if !lapsed => {
+                        if let Some(valid_until) = credential.valid_until {
+                            Self::queue_validity_end(credential_id, valid_until);
+                        }
+                    }
+                    _ => {}
+                }
             });
+        }
 
-            Ok(())
+        /// Whether a credential is past its expiration block or validity window
+        fn is_lapsed(credential: &Credential<T>) -> bool {
+            credential
+                .expires_at
+                .is_some_and(|expires_at| frame_system::Pallet::<T>::block_number() > expires_at)
+                || credential
+                    .valid_until
+                    .is_some_and(|valid_until| T::TimeProvider::now() >= valid_until)
         }
 
-        /// Update credential metadata (only by issuer)
-        #[pallet::call_index(3)]
-        #[pallet::weight(<T as Config>::WeightInfo::update_credential_metadata())]
-        pub fn update_credential_metadata(
-            origin: OriginFor<T>,
-            credential_id: [u8; 32],
-            new_metadata: BoundedVec<u8, T::MaxMetadataSize>,
-        ) -> DispatchResult {
-            let updater = ensure_signed(origin)?;
+        /// Queue a credential to expire at the block expected to follow
+        /// `valid_until`, and no earlier than the next block
+        fn queue_validity_end(credential_id: [u8; 32], valid_until: MomentOf<T>) {
+            let remaining = valid_until.saturating_sub(T::TimeProvider::now());
+            let block_time = T::ExpectedBlockTime::get().max(One::one());
+            let blocks = remaining
+                .saturating_add(block_time.saturating_sub(One::one()))
+                .checked_div(&block_time)
+                .unwrap_or_default()
+                .max(One::one());
+            let expiry = frame_system::Pallet::<T>::block_number()
+                .saturating_add(blocks.saturated_into::<u64>().saturated_into());
+            CredentialExpiries::<T>::insert(expiry, credential_id, ());
+        }
+    }
 
-            // Get credential
-            let mut credential =
-                Credentials::<T>::get(&credential_id).ok_or(Error::<T>::CredentialNotFound)?;
+    impl<T: Config> did::IssuedCredentials<T::DidIdentifier> for Pallet<T> {
+        fn has_active_credentials(issuer: &T::DidIdentifier) -> bool {
+            let current_block = frame_system::Pallet::<T>::block_number();
+            let now = T::TimeProvider::now();
 
-            // Only issuer can update metadata
-            ensure!(credential.issuer == updater, Error::<T>::NotAuthorized);
+            CredentialsByIssuer::<T>::get(issuer)
+                .iter()
+                .any(|credential_id| {
+                    Credentials::<T>::get(credential_id).is_some_and(|credential| {
+                        // Suspended credentials can still be reinstated
+                        let in_force = matches!(
+                            credential.status,
+                            CredentialStatus::Active | CredentialStatus::Suspended
+                        );
+                        in_force
+                            && credential
+                                .expires_at
+                                .is_none_or(|expires_at| current_block <= expires_at)
+                            && credential
+                                .valid_until
+                                .is_none_or(|valid_until| now < valid_until)
+                    })
+                })
+        }
 
-            // Ensure credential is active
-            ensure!(
-                credential.status == CredentialStatus::Active,
-                Error::<T>::CredentialNotActive
-            );
+        fn transfer_deposits(issuer: &T::DidIdentifier) -> DispatchResult {
+            let controller = Self::controller_of(issuer)?;
 
-            // Update metadata
-            credential.metadata = new_metadata;
-            Credentials::<T>::insert(&credential_id, credential);
+            for credential_id in CredentialsByIssuer::<T>::get(issuer) {
+                if let Some(deposit) = CredentialDeposits::<T>::get(credential_id) {
+                    let deposit = Self::transfer_deposit(
+                        &HoldReason::CredentialDeposit.into(),
+                        deposit,
+                        &controller,
+                    )?;
+                    CredentialDeposits::<T>::insert(credential_id, deposit);
+                }
+            }
 
-            // Emit event
-            Self::deposit_event(Event::CredentialMetadataUpdated { credential_id });
+            // Collected first so the map is not written to while iterating it
+            let delegate_deposits: Vec<_> = DelegateDeposits::<T>::iter_prefix(issuer).collect();
+            for (delegate, deposit) in delegate_deposits {
+                let deposit = Self::transfer_deposit(
+                    &HoldReason::DelegateDeposit.into(),
+                    deposit,
+                    &controller,
+                )?;
+                DelegateDeposits::<T>::insert(issuer, delegate, deposit);
+            }
 
             Ok(())
         }
-    }
 
-    // ================== Helper Functions ==================
+        fn transfer_deposits_weight() -> Weight {
+            <T as Config>::WeightInfo::transfer_deposit().saturating_mul(
+                T::MaxCredentialsPerHolder::get()
+                    .saturating_add(T::MaxDelegates::get())
+                    .into(),
+            )
+        }
 
-    impl<T: Config> Pallet<T> {
-        /// Generate a unique credential ID
-        fn generate_credential_id(
-            counter: u64,
-            issuer: &T::AccountId,
-            holder: &T::AccountId,
-        ) -> [u8; 32] {
-            let mut data = Vec::new();
-            data.extend_from_slice(&counter.to_le_bytes());
-            data.extend_from_slice(&issuer.encode());
-            data.extend_from_slice(&holder.encode());
-            hashing::blake2_256(&data)
+        fn release_deposits(issuer: &T::DidIdentifier) -> DispatchResult {
+            // Only revoked or lapsed credentials are left once the DID may be deleted
+            for credential_id in CredentialsByIssuer::<T>::get(issuer) {
+                if let Some(credential) = Credentials::<T>::get(credential_id) {
+                    Self::do_remove_credential(credential_id, &credential)?;
+                    Self::deposit_event(Event::CredentialRemoved {
+                        credential_id,
+                        removed_by: issuer.clone(),
+                    });
+                }
+            }
+            CredentialsByIssuer::<T>::remove(issuer);
+
+            // Collected first so the map is not written to while iterating it
+            let delegates: Vec<_> = Delegates::<T>::iter_key_prefix(issuer).collect();
+            for delegate in delegates {
+                Delegates::<T>::remove(issuer, &delegate);
+                if let Some(deposit) = DelegateDeposits::<T>::take(issuer, &delegate) {
+                    did::Pallet::<T>::release_deposit(
+                        &HoldReason::DelegateDeposit.into(),
+                        deposit,
+                    )?;
+                }
+                Self::deposit_event(Event::DelegateRemoved {
+                    institution: issuer.clone(),
+                    delegate,
+                });
+            }
+
+            Ok(())
+        }
+
+        fn release_deposits_weight() -> Weight {
+            <T as Config>::WeightInfo::remove_credential()
+                .saturating_mul(T::MaxCredentialsPerHolder::get().into())
+                .saturating_add(
+                    <T as Config>::WeightInfo::remove_delegate()
+                        .saturating_mul(T::MaxDelegates::get().into()),
+                )
         }
     }
 }
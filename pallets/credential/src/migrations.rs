@@ -0,0 +1,474 @@
+// pallets/credential/src/migrations.rs
+use crate::{
+    pallet, Config, Credential, CredentialReference, CredentialStatus, CredentialType, Credentials,
+    DelegatePermissions,
+};
+use did::BalanceOf;
+#[cfg(feature = "try-runtime")]
+use frame::try_runtime::TryRuntimeError;
+use frame::{
+    deps::frame_support::migrations::VersionedMigration, prelude::*,
+    traits::UncheckedOnRuntimeUpgrade,
+};
+
+/// Decode the state `pre_upgrade` handed to `post_upgrade`
+#[cfg(feature = "try-runtime")]
+fn decode_state<S: Decode>(state: Vec<u8>) -> Result<S, TryRuntimeError> {
+    S::decode(&mut &state[..]).map_err(|_| "cannot decode pre-upgrade state".into())
+}
+
+/// Records which staff delegate issued each credential.
+pub mod v1 {
+    use super::*;
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct OldCredential<T: Config> {
+        pub credential_id: [u8; 32],
+        pub credential_hash: [u8; 32],
+        pub holder: T::AccountId,
+        pub issuer: T::AccountId,
+        pub credential_type: CredentialType,
+        pub metadata: BoundedVec<u8, T::MaxMetadataSize>,
+        pub issued_at: BlockNumberFor<T>,
+        pub expires_at: Option<BlockNumberFor<T>>,
+        pub status: CredentialStatus,
+    }
+
+    /// Credential layout from storage version 1 until version 2
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct CredentialV1<T: Config> {
+        pub credential_id: [u8; 32],
+        pub credential_hash: [u8; 32],
+        pub holder: T::AccountId,
+        pub issuer: T::AccountId,
+        pub issued_by: Option<T::AccountId>,
+        pub credential_type: CredentialType,
+        pub metadata: BoundedVec<u8, T::MaxMetadataSize>,
+        pub issued_at: BlockNumberFor<T>,
+        pub expires_at: Option<BlockNumberFor<T>>,
+        pub status: CredentialStatus,
+    }
+
+    #[frame::storage_alias]
+    pub type Credentials<T: Config> =
+        StorageMap<pallet::Pallet<T>, Blake2_128Concat, [u8; 32], CredentialV1<T>>;
+
+    pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            // Before delegates existed every credential was signed by its issuer
+            Credentials::<T>::translate::<OldCredential<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                Some(CredentialV1 {
+                    credential_id: old.credential_id,
+                    credential_hash: old.credential_hash,
+                    holder: old.holder,
+                    issuer: old.issuer,
+                    issued_by: None,
+                    credential_type: old.credential_type,
+                    metadata: old.metadata,
+                    issued_at: old.issued_at,
+                    expires_at: old.expires_at,
+                    status: old.status,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((Credentials::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let credentials: u64 = decode_state(state)?;
+            ensure!(
+                Credentials::<T>::iter_values().count() as u64 == credentials,
+                "v1: credentials lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 0 to version 1
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        InnerMigrateV0ToV1<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// References DIDs rather than the accounts they used to be keyed by.
+///
+/// Every entry is moved in the upgrade block; `pre_upgrade` checks that they
+/// fit in it.
+pub mod v2 {
+    use super::*;
+    use v1::CredentialV1;
+
+    /// Credential layout from storage version 2 until version 3
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct CredentialV2<T: Config> {
+        pub credential_id: [u8; 32],
+        pub credential_hash: [u8; 32],
+        pub holder: T::DidIdentifier,
+        pub issuer: T::DidIdentifier,
+        pub issued_by: Option<T::DidIdentifier>,
+        pub credential_type: CredentialType,
+        pub metadata: BoundedVec<u8, T::MaxMetadataSize>,
+        pub issued_at: BlockNumberFor<T>,
+        pub expires_at: Option<BlockNumberFor<T>>,
+        pub status: CredentialStatus,
+    }
+
+    #[frame::storage_alias]
+    pub type Credentials<T: Config> =
+        StorageMap<pallet::Pallet<T>, Blake2_128Concat, [u8; 32], CredentialV2<T>>;
+
+    #[frame::storage_alias]
+    pub type CredentialsByHolder<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<CredentialReference, <T as Config>::MaxCredentialsPerHolder>,
+    >;
+
+    #[frame::storage_alias]
+    pub type CredentialsByIssuer<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<[u8; 32], <T as Config>::MaxCredentialsPerHolder>,
+    >;
+
+    #[frame::storage_alias]
+    pub type Delegates<T: Config> = StorageDoubleMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        DelegatePermissions,
+    >;
+
+    #[frame::storage_alias]
+    pub type DelegateDeposits<T: Config> = StorageDoubleMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        did::Deposit<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+    >;
+
+    pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+    impl<T: Config> InnerMigrateV1ToV2<T> {
+        fn did_of(account: T::AccountId) -> T::DidIdentifier {
+            <T as did::Config>::DidIdentifierOf::convert(account)
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut moved = 0u64;
+
+            Credentials::<T>::translate::<CredentialV1<T>, _>(|_, old| {
+                moved.saturating_inc();
+
+                Some(CredentialV2 {
+                    credential_id: old.credential_id,
+                    credential_hash: old.credential_hash,
+                    holder: Self::did_of(old.holder),
+                    issuer: Self::did_of(old.issuer),
+                    issued_by: old.issued_by.map(Self::did_of),
+                    credential_type: old.credential_type,
+                    metadata: old.metadata,
+                    issued_at: old.issued_at,
+                    expires_at: old.expires_at,
+                    status: old.status,
+                })
+            });
+
+            // Drained first, as an identifier may encode to the same key as the
+            // account it replaces
+            let by_holder = CredentialsByHolder::<T>::drain().collect::<Vec<_>>();
+            for (account, references) in by_holder {
+                crate::CredentialsByHolder::<T>::insert(Self::did_of(account), references);
+                moved.saturating_inc();
+            }
+
+            let by_issuer = CredentialsByIssuer::<T>::drain().collect::<Vec<_>>();
+            for (account, credential_ids) in by_issuer {
+                crate::CredentialsByIssuer::<T>::insert(Self::did_of(account), credential_ids);
+                moved.saturating_inc();
+            }
+
+            let delegates = Delegates::<T>::drain().collect::<Vec<_>>();
+            for (institution, delegate, permissions) in delegates {
+                crate::Delegates::<T>::insert(
+                    Self::did_of(institution),
+                    Self::did_of(delegate),
+                    permissions,
+                );
+                moved.saturating_inc();
+            }
+
+            let deposits = DelegateDeposits::<T>::drain().collect::<Vec<_>>();
+            for (institution, delegate, deposit) in deposits {
+                crate::DelegateDeposits::<T>::insert(
+                    Self::did_of(institution),
+                    Self::did_of(delegate),
+                    deposit,
+                );
+                moved.saturating_inc();
+            }
+
+            T::DbWeight::get().reads_writes(moved, moved.saturating_mul(2))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let counts: Vec<u64> = vec![
+                v1::Credentials::<T>::iter_keys().count() as u64,
+                CredentialsByHolder::<T>::iter_keys().count() as u64,
+                CredentialsByIssuer::<T>::iter_keys().count() as u64,
+                Delegates::<T>::iter_keys().count() as u64,
+                DelegateDeposits::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                did::migrations::fits_in_block::<T, pallet::Pallet<T>>(counts.iter().sum()),
+                "v2: too many entries to re-key in one block"
+            );
+
+            Ok(counts.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before: Vec<u64> = decode_state(state)?;
+
+            // Two accounts deriving the same identifier would collapse entries
+            let after: Vec<u64> = vec![
+                Credentials::<T>::iter_values().count() as u64,
+                crate::CredentialsByHolder::<T>::iter_keys().count() as u64,
+                crate::CredentialsByIssuer::<T>::iter_keys().count() as u64,
+                crate::Delegates::<T>::iter_keys().count() as u64,
+                crate::DelegateDeposits::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                after == before,
+                "v2: entries lost re-keying by DID identifier"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 1 to version 2
+    pub type MigrateV1ToV2<T> = VersionedMigration<
+        1,
+        2,
+        InnerMigrateV1ToV2<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Records the DID key that authorised each credential's issuance.
+pub mod v3 {
+    use super::*;
+    use v2::CredentialV2;
+
+    /// Credential layout from storage version 3 until version 5
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct CredentialV3<T: Config> {
+        pub credential_id: [u8; 32],
+        pub credential_hash: [u8; 32],
+        pub holder: T::DidIdentifier,
+        pub issuer: T::DidIdentifier,
+        pub issued_by: Option<T::DidIdentifier>,
+        pub signed_with: Option<[u8; 32]>,
+        pub credential_type: CredentialType,
+        pub metadata: BoundedVec<u8, T::MaxMetadataSize>,
+        pub issued_at: BlockNumberFor<T>,
+        pub expires_at: Option<BlockNumberFor<T>>,
+        pub status: CredentialStatus,
+    }
+
+    #[frame::storage_alias]
+    pub type Credentials<T: Config> =
+        StorageMap<pallet::Pallet<T>, Blake2_128Concat, [u8; 32], CredentialV3<T>>;
+
+    pub struct InnerMigrateV2ToV3<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV2ToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            // Credentials could only be issued by accounts before DID keys could
+            // authorise calls
+            Credentials::<T>::translate::<CredentialV2<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                Some(CredentialV3 {
+                    credential_id: old.credential_id,
+                    credential_hash: old.credential_hash,
+                    holder: old.holder,
+                    issuer: old.issuer,
+                    issued_by: old.issued_by,
+                    signed_with: None,
+                    credential_type: old.credential_type,
+                    metadata: old.metadata,
+                    issued_at: old.issued_at,
+                    expires_at: old.expires_at,
+                    status: old.status,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((v2::Credentials::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let credentials: u64 = decode_state(state)?;
+            ensure!(
+                Credentials::<T>::iter_values().count() as u64 == credentials,
+                "v3: credentials lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 2 to version 3
+    pub type MigrateV2ToV3<T> = VersionedMigration<
+        2,
+        3,
+        InnerMigrateV2ToV3<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Queues the expiry of every active credential with an expiration block.
+pub mod v4 {
+    use super::*;
+    use crate::{CredentialExpiries, ExpiryCursor};
+    use v3::Credentials;
+
+    pub struct InnerMigrateV3ToV4<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV3ToV4<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut reads = 0u64;
+            let mut writes = 1u64;
+
+            for (credential_id, credential) in Credentials::<T>::iter() {
+                reads.saturating_inc();
+                if credential.status != CredentialStatus::Active {
+                    continue;
+                }
+                let Some(expires_at) = credential.expires_at else {
+                    continue;
+                };
+
+                // Credentials already past their expiration expire this block
+                let expiry = expires_at.saturating_add(One::one()).max(now);
+                CredentialExpiries::<T>::insert(expiry, credential_id, ());
+                writes.saturating_inc();
+            }
+
+            ExpiryCursor::<T>::put(now);
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// Migrate storage from version 3 to version 4
+    pub type MigrateV3ToV4<T> = VersionedMigration<
+        3,
+        4,
+        InnerMigrateV3ToV4<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Adds wall-clock validity windows; credentials keep their expiration block.
+pub mod v5 {
+    use super::*;
+    use v3::CredentialV3;
+
+    pub struct InnerMigrateV4ToV5<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV4ToV5<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            // Existing credentials stay bounded by their expiration block alone,
+            // which the expiry queue keeps enforcing
+            Credentials::<T>::translate::<CredentialV3<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                Some(Credential {
+                    credential_id: old.credential_id,
+                    credential_hash: old.credential_hash,
+                    holder: old.holder,
+                    issuer: old.issuer,
+                    issued_by: old.issued_by,
+                    signed_with: old.signed_with,
+                    credential_type: old.credential_type,
+                    metadata: old.metadata,
+                    issued_at: old.issued_at,
+                    expires_at: old.expires_at,
+                    valid_from: None,
+                    valid_until: None,
+                    status: old.status,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((v3::Credentials::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let credentials: u64 = decode_state(state)?;
+            ensure!(
+                Credentials::<T>::iter_values().count() as u64 == credentials,
+                "v5: credentials lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 4 to version 5
+    pub type MigrateV4ToV5<T> = VersionedMigration<
+        4,
+        5,
+        InnerMigrateV4ToV5<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -1,4 +1,6 @@
 use crate as credential;
+use frame::deps::sp_core::{ed25519, Pair};
+use frame::deps::sp_runtime::traits::Identity;
 use frame::prelude::*;
 use frame::testing_prelude::*;
 
@@ -29,24 +31,74 @@ mod runtime {
 
     #[runtime::pallet_index(2)]
     pub type Credential = credential;
+
+    #[runtime::pallet_index(3)]
+    pub type Balances = pallet_balances;
+
+    #[runtime::pallet_index(4)]
+    pub type Timestamp = pallet_timestamp;
 }
 
 // System pallet configuration
 #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
 impl frame_system::Config for Test {
     type Block = Block;
+    type AccountData = pallet_balances::AccountData<u64>;
 }
 
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
+}
+
+#[derive_impl(pallet_timestamp::config_preludes::TestDefaultConfig)]
+impl pallet_timestamp::Config for Test {}
+
 // DID pallet configuration (required by Credential pallet)
 parameter_types! {
     pub const MaxDocumentSize: u32 = 1024;
     pub const MaxPublicKeys: u32 = 10;
+    pub const MaxKeyHistory: u32 = 12;
+    pub const MaxCompromisedKeys: u32 = 4;
+    pub const MaxServiceEndpoints: u32 = 8;
+    pub const MaxUrlLength: u32 = 256;
+    pub const DidDeposit: u64 = 100;
+    pub const ByteDeposit: u64 = 1;
+    pub const MaxGuardians: u32 = 10;
+    pub const MaxLinkedAccounts: u32 = 4;
+    pub const RecoveryDelay: u64 = 10;
+    pub const VerificationPeriod: u64 = 1_000;
+    pub const MaxLapsesPerBlock: u32 = 2;
+}
+
+ord_parameter_types! {
+    pub const AccreditationCouncil: u64 = 99;
 }
 
 impl did::Config for Test {
     type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type DidIdentifier = u64;
+    type DidIdentifierOf = Identity;
+    type MaxLinkedAccounts = MaxLinkedAccounts;
     type MaxDocumentSize = MaxDocumentSize;
     type MaxPublicKeys = MaxPublicKeys;
+    type MaxKeyHistory = MaxKeyHistory;
+    type MaxCompromisedKeys = MaxCompromisedKeys;
+    type MaxServiceEndpoints = MaxServiceEndpoints;
+    type MaxUrlLength = MaxUrlLength;
+    type Currency = Balances;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type DidDeposit = DidDeposit;
+    type ByteDeposit = ByteDeposit;
+    type IssuedCredentials = Credential;
+    type MaxGuardians = MaxGuardians;
+    type RecoveryDelay = RecoveryDelay;
+    type VerifyOrigin = EnsureSignedBy<AccreditationCouncil, u64>;
+    type RevokeOrigin = EnsureSignedBy<AccreditationCouncil, u64>;
+    type VerificationPeriod = VerificationPeriod;
+    type MaxLapsesPerBlock = MaxLapsesPerBlock;
     type WeightInfo = did::weights::SubstrateWeight;
 }
 
@@ -54,30 +106,53 @@ impl did::Config for Test {
 parameter_types! {
     pub const MaxMetadataSize: u32 = 512;
     pub const MaxCredentialsPerHolder: u32 = 100;
+    pub const CredentialDeposit: u64 = 50;
+    pub const DelegateDeposit: u64 = 20;
+    pub const MaxExpiriesPerBlock: u32 = 2;
+    pub const MaxDelegates: u32 = 3;
+    pub const ExpectedBlockTime: u64 = 100;
 }
 
 impl credential::Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type MaxMetadataSize = MaxMetadataSize;
     type MaxCredentialsPerHolder = MaxCredentialsPerHolder;
+    type CredentialDeposit = CredentialDeposit;
+    type DelegateDeposit = DelegateDeposit;
+    type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+    type MaxDelegates = MaxDelegates;
+    type TimeProvider = Timestamp;
+    type ExpectedBlockTime = ExpectedBlockTime;
     type WeightInfo = credential::weights::SubstrateWeight;
 }
 
+/// Balance given to each test account at genesis
+pub const INITIAL_BALANCE: u64 = 1_000_000;
+
 // Test externalities initialization
 pub fn new_test_ext() -> TestExternalities {
-    frame_system::GenesisConfig::<Test>::default()
+    let mut t = frame_system::GenesisConfig::<Test>::default()
         .build_storage()
-        .unwrap()
-        .into()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: (1..=20).map(|account| (account, INITIAL_BALANCE)).collect(),
+        ..Default::default()
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    t.into()
 }
 
 // Helper functions for tests
 pub fn create_did_for_account(account: u64) {
-    let public_key = [account as u8; 32];
+    let pair = ed25519::Pair::from_seed(&[account as u8; 32]);
+    let proof = pair.sign(&Did::key_possession_payload(&account));
     assert_ok!(Did::create_did(
         RuntimeOrigin::signed(account),
-        public_key,
-        did::KeyType::Ed25519
+        did::PublicKey::Ed25519(pair.public().0),
+        proof.into()
     ));
 }
 
@@ -95,7 +170,7 @@ pub fn register_institution(account: u64, name: &str) {
 
 pub fn verify_institution(account: u64) {
     assert_ok!(Did::verify_institution(
-        RuntimeOrigin::root(),
+        RuntimeOrigin::signed(AccreditationCouncil::get()),
         account
     ));
 }
@@ -103,4 +178,30 @@ pub fn verify_institution(account: u64) {
 pub fn setup_verified_institution(account: u64, name: &str) {
     register_institution(account, name);
     verify_institution(account);
-}
\ No newline at end of file
+}
+/// Link `account` to the DID of `did`, consented to by the DID's key
+pub fn link_account(account: u64, did: u64) {
+    let pair = ed25519::Pair::from_seed(&[did as u8; 32]);
+    let proof = pair.sign(&Did::account_link_payload(&did, &account));
+    assert_ok!(Did::link_account(
+        RuntimeOrigin::signed(account),
+        did,
+        did::PublicKey::Ed25519(pair.public().0).key_id(),
+        proof.into()
+    ));
+}
+
+/// Submit `call` from `submitter` on behalf of `did`, signed with the DID's key
+pub fn submit_did_call(submitter: u64, did: u64, call: RuntimeCall) {
+    let pair = ed25519::Pair::from_seed(&[did as u8; 32]);
+    let nonce = Did::did_call_nonces(did);
+    let signature = pair.sign(&Did::did_call_payload(&did, &call, nonce, &submitter));
+    assert_ok!(Did::submit_did_call(
+        RuntimeOrigin::signed(submitter),
+        did,
+        Box::new(call),
+        nonce,
+        did::PublicKey::Ed25519(pair.public().0).key_id(),
+        signature.into()
+    ));
+}
@@ -1,5 +1,9 @@
-use crate::{mock::*, Error, Event, CredentialType, CredentialStatus};
+use crate::{
+    mock::*, CredentialStatus, CredentialType, DelegatePermissions, Error, Event, HoldReason,
+    SuspensionReason, ValidityWindow,
+};
 use frame::testing_prelude::*;
+use frame::traits::fungible::InspectHold;
 
 // ================== Test Constants ==================
 
@@ -30,7 +34,8 @@ fn issue_credential_works() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         // Verify event was emitted
@@ -40,8 +45,10 @@ fn issue_credential_works() {
                 credential_hash,
                 holder: STUDENT,
                 issuer: UNIVERSITY,
+                issued_by: None,
                 credential_type: CredentialType::Degree,
-            }.into()
+            }
+            .into(),
         );
     });
 }
@@ -67,13 +74,52 @@ fn issue_credential_fails_if_issuer_not_verified() {
                 credential_hash,
                 CredentialType::Degree,
                 metadata,
-                None
+                None,
+                ValidityWindow::default()
             ),
             Error::<Test>::IssuerNotVerified
         );
     });
 }
 
+#[test]
+fn issue_credential_fails_if_issuer_accreditation_lapsed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"Degree".to_vec().try_into().unwrap();
+        let issue = |credential_hash| {
+            Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                credential_hash,
+                CredentialType::Degree,
+                metadata.clone(),
+                None,
+                ValidityWindow::default(),
+            )
+        };
+
+        // Rejected from the lapse block, even before the lapse is processed
+        let lapse = 1 + VerificationPeriod::get();
+        System::set_block_number(lapse);
+        assert_noop!(issue([1u8; 32]), Error::<Test>::IssuerAccreditationLapsed);
+
+        Did::on_initialize(lapse);
+        assert_noop!(issue([1u8; 32]), Error::<Test>::IssuerAccreditationLapsed);
+
+        // Renewal restores issuing
+        assert_ok!(Did::renew_institution_verification(
+            RuntimeOrigin::signed(AccreditationCouncil::get()),
+            UNIVERSITY
+        ));
+        assert_ok!(issue([1u8; 32]));
+    });
+}
+
 #[test]
 fn issue_credential_fails_if_issuer_has_no_did() {
     new_test_ext().execute_with(|| {
@@ -93,9 +139,10 @@ fn issue_credential_fails_if_issuer_has_no_did() {
                 credential_hash,
                 CredentialType::Degree,
                 metadata,
-                None
+                None,
+                ValidityWindow::default()
             ),
-            Error::<Test>::IssuerNotVerified
+            Error::<Test>::IssuerHasNoDid
         );
     });
 }
@@ -120,7 +167,8 @@ fn issue_credential_fails_if_holder_has_no_did() {
                 credential_hash,
                 CredentialType::Degree,
                 metadata,
-                None
+                None,
+                ValidityWindow::default()
             ),
             Error::<Test>::HolderHasNoDid
         );
@@ -131,16 +179,15 @@ fn issue_credential_fails_if_holder_has_no_did() {
 fn issue_credential_fails_if_holder_did_inactive() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
-        
+
         setup_verified_institution(UNIVERSITY, "MIT");
         create_did_for_account(STUDENT);
-        
+
         // Deactivate student's DID
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(STUDENT)));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(STUDENT), STUDENT));
 
         let credential_hash = [1u8; 32];
-        let metadata: BoundedVec<u8, MaxMetadataSize> = 
-            b"Degree".to_vec().try_into().unwrap();
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"Degree".to_vec().try_into().unwrap();
 
         // Should fail because holder's DID is inactive
         assert_noop!(
@@ -150,7 +197,8 @@ fn issue_credential_fails_if_holder_did_inactive() {
                 credential_hash,
                 CredentialType::Degree,
                 metadata,
-                None
+                None,
+                ValidityWindow::default()
             ),
             Error::<Test>::DidNotActive
         );
@@ -176,7 +224,8 @@ fn issue_credential_fails_for_duplicate_hash() {
             credential_hash,
             CredentialType::Degree,
             metadata.clone(),
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         // Try to issue another credential with same hash
@@ -187,7 +236,8 @@ fn issue_credential_fails_for_duplicate_hash() {
                 credential_hash,
                 CredentialType::Certificate,
                 metadata,
-                None
+                None,
+                ValidityWindow::default()
             ),
             Error::<Test>::CredentialAlreadyExists
         );
@@ -212,7 +262,8 @@ fn issue_credential_stores_correct_data() {
             credential_hash,
             CredentialType::Degree,
             metadata.clone(),
-            Some(1000)
+            Some(1000),
+            ValidityWindow::default()
         ));
 
         // Get credential ID
@@ -250,7 +301,8 @@ fn revoke_credential_works() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -275,6 +327,61 @@ fn revoke_credential_works() {
     });
 }
 
+#[test]
+fn remove_credential_releases_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            b"Degree".to_vec().try_into().unwrap(),
+            None,
+            ValidityWindow::default()
+        ));
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+
+        // Credentials in force stay on chain
+        assert_noop!(
+            Credential::remove_credential(RuntimeOrigin::signed(UNIVERSITY), credential_id),
+            Error::<Test>::CredentialNotRemovable
+        );
+
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert_noop!(
+            Credential::remove_credential(RuntimeOrigin::signed(STUDENT), credential_id),
+            Error::<Test>::NotAuthorized
+        );
+        assert_ok!(Credential::remove_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+
+        assert!(Credential::credentials(credential_id).is_none());
+        assert_eq!(Credential::credential_by_hash(credential_hash), None);
+        assert_eq!(Credential::credential_deposits(credential_id), None);
+        assert!(Credential::credentials_by_holder(STUDENT).is_empty());
+        assert!(Credential::credentials_by_issuer(UNIVERSITY).is_empty());
+        assert_eq!(credential_deposit_held(UNIVERSITY), 0);
+        System::assert_last_event(
+            Event::CredentialRemoved {
+                credential_id,
+                removed_by: UNIVERSITY,
+            }
+            .into(),
+        );
+    });
+}
+
 #[test]
 fn revoke_credential_fails_if_not_issuer() {
     new_test_ext().execute_with(|| {
@@ -295,7 +402,8 @@ fn revoke_credential_fails_if_not_issuer() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -329,7 +437,8 @@ fn revoke_credential_fails_if_already_revoked() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -392,7 +501,8 @@ fn verify_credential_works() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -408,7 +518,9 @@ fn verify_credential_works() {
             Event::CredentialVerified {
                 credential_id,
                 verified_by: VERIFIER,
-            }.into()
+                issuer_closed_at: None,
+            }
+            .into(),
         );
     });
 }
@@ -432,7 +544,8 @@ fn verify_credential_fails_if_revoked() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -474,7 +587,8 @@ fn verify_credential_fails_if_expired() {
             credential_hash,
             CredentialType::Certificate,
             metadata,
-            Some(10)
+            Some(10),
+            ValidityWindow::default()
         ));
 
         // Move to block 11 (after expiration)
@@ -491,6 +605,61 @@ fn verify_credential_fails_if_expired() {
     });
 }
 
+#[test]
+fn closed_issuer_cannot_issue_but_credentials_stay_verifiable() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        create_did_for_account(VERIFIER);
+
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"Degree".to_vec().try_into().unwrap();
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            [1u8; 32],
+            CredentialType::Degree,
+            metadata.clone(),
+            None,
+            ValidityWindow::default()
+        ));
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+
+        System::set_block_number(5);
+        assert_ok!(Did::deregister_institution(RuntimeOrigin::signed(
+            UNIVERSITY
+        )));
+
+        assert_noop!(
+            Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                [2u8; 32],
+                CredentialType::Degree,
+                metadata,
+                None,
+                ValidityWindow::default()
+            ),
+            Error::<Test>::IssuerClosed
+        );
+
+        assert_ok!(Credential::verify_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            [1u8; 32]
+        ));
+        System::assert_last_event(
+            Event::CredentialVerified {
+                credential_id,
+                verified_by: VERIFIER,
+                issuer_closed_at: Some(5),
+            }
+            .into(),
+        );
+    });
+}
+
 #[test]
 fn verify_credential_fails_if_not_found() {
     new_test_ext().execute_with(|| {
@@ -530,7 +699,8 @@ fn update_metadata_works() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -553,7 +723,9 @@ fn update_metadata_works() {
         System::assert_last_event(
             Event::CredentialMetadataUpdated {
                 credential_id,
-            }.into()
+                updated_by: UNIVERSITY,
+            }
+            .into(),
         );
     });
 }
@@ -576,7 +748,8 @@ fn update_metadata_fails_if_not_issuer() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -613,7 +786,8 @@ fn update_metadata_fails_if_revoked() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -639,6 +813,1612 @@ fn update_metadata_fails_if_revoked() {
     });
 }
 
+// ================== Deposit Tests ==================
+
+fn credential_deposit_held(account: u64) -> u64 {
+    Balances::balance_on_hold(&HoldReason::CredentialDeposit.into(), &account)
+}
+
+#[test]
+fn issue_credential_holds_deposit_from_issuer() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        let metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"Computer Science Degree".to_vec().try_into().unwrap();
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            metadata,
+            None,
+            ValidityWindow::default()
+        ));
+
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+        let credential = Credential::credentials(credential_id).unwrap();
+        let expected = CredentialDeposit::get() + credential.encoded_size() as u64;
+
+        let deposit = Credential::credential_deposits(credential_id).unwrap();
+        assert_eq!(deposit.owner, UNIVERSITY);
+        assert_eq!(deposit.amount, expected);
+        assert_eq!(credential_deposit_held(UNIVERSITY), expected);
+        assert_eq!(credential_deposit_held(STUDENT), 0);
+    });
+}
+
+#[test]
+fn update_metadata_resizes_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"BSc".to_vec().try_into().unwrap();
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            metadata,
+            None,
+            ValidityWindow::default()
+        ));
+
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+        let initial = credential_deposit_held(UNIVERSITY);
+
+        let new_metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"BSc Computer Science".to_vec().try_into().unwrap();
+        assert_ok!(Credential::update_credential_metadata(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            new_metadata
+        ));
+
+        assert_eq!(credential_deposit_held(UNIVERSITY), initial + 17);
+    });
+}
+
+// ================== Succession Tests ==================
+
+#[test]
+fn successor_can_revoke_and_update_merged_issuer_credentials() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "Old College");
+        setup_verified_institution(VERIFIER, "New University");
+        create_did_for_account(STUDENT);
+
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"BSc".to_vec().try_into().unwrap();
+
+        for hash in [[1u8; 32], [2u8; 32]] {
+            assert_ok!(Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                hash,
+                CredentialType::Degree,
+                metadata.clone(),
+                None,
+                ValidityWindow::default()
+            ));
+        }
+        let first = Credential::credential_by_hash([1u8; 32]).unwrap();
+        let second = Credential::credential_by_hash([2u8; 32]).unwrap();
+
+        assert_noop!(
+            Credential::revoke_credential(RuntimeOrigin::signed(VERIFIER), first),
+            Error::<Test>::NotAuthorized
+        );
+
+        assert_ok!(Did::set_successor(
+            RuntimeOrigin::signed(AccreditationCouncil::get()),
+            UNIVERSITY,
+            VERIFIER
+        ));
+
+        let new_metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"BSc Computer Science".to_vec().try_into().unwrap();
+        assert_ok!(Credential::update_credential_metadata(
+            RuntimeOrigin::signed(VERIFIER),
+            first,
+            new_metadata.clone()
+        ));
+        assert_eq!(
+            Credential::credentials(first).unwrap().metadata,
+            new_metadata
+        );
+        System::assert_last_event(
+            Event::CredentialMetadataUpdated {
+                credential_id: first,
+                updated_by: VERIFIER,
+            }
+            .into(),
+        );
+
+        // The successor takes over the deposit of the credential it updated
+        let deposit = Credential::credential_deposits(first).unwrap();
+        assert_eq!(deposit.owner, VERIFIER);
+        assert_eq!(credential_deposit_held(VERIFIER), deposit.amount);
+
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            second
+        ));
+        System::assert_last_event(
+            Event::CredentialRevoked {
+                credential_id: second,
+                revoked_by: VERIFIER,
+            }
+            .into(),
+        );
+
+        // Succession does not work in reverse
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            STUDENT,
+            [3u8; 32],
+            CredentialType::Degree,
+            metadata,
+            None,
+            ValidityWindow::default()
+        ));
+        assert_noop!(
+            Credential::revoke_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                Credential::credential_by_hash([3u8; 32]).unwrap()
+            ),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+// ================== Delegation Tests ==================
+
+const REGISTRAR: u64 = 4;
+
+fn delegate_permissions(
+    can_issue: bool,
+    can_revoke: bool,
+    can_update_metadata: bool,
+    credential_types: Vec<CredentialType>,
+) -> DelegatePermissions {
+    DelegatePermissions {
+        can_issue,
+        can_revoke,
+        can_update_metadata,
+        credential_types: credential_types.try_into().unwrap(),
+    }
+}
+
+fn issue_on_behalf(hash: [u8; 32], credential_type: CredentialType) -> DispatchResult {
+    Credential::issue_credential_on_behalf(
+        RuntimeOrigin::signed(REGISTRAR),
+        UNIVERSITY,
+        STUDENT,
+        hash,
+        credential_type,
+        b"Degree".to_vec().try_into().unwrap(),
+        None,
+        ValidityWindow::default(),
+    )
+}
+
+#[test]
+fn set_delegate_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(REGISTRAR);
+
+        let permissions = delegate_permissions(true, false, false, vec![]);
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            permissions.clone()
+        ));
+
+        assert_eq!(
+            Credential::delegates(UNIVERSITY, REGISTRAR),
+            Some(permissions.clone())
+        );
+        let expected = DelegateDeposit::get() + permissions.encoded_size() as u64;
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::DelegateDeposit.into(), &UNIVERSITY),
+            expected
+        );
+        System::assert_last_event(
+            Event::DelegateSet {
+                institution: UNIVERSITY,
+                delegate: REGISTRAR,
+                permissions,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn set_delegate_fails_for_invalid_input() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let permissions = delegate_permissions(true, true, true, vec![]);
+
+        create_did_for_account(STUDENT);
+        assert_noop!(
+            Credential::set_delegate(
+                RuntimeOrigin::signed(STUDENT),
+                REGISTRAR,
+                permissions.clone()
+            ),
+            Error::<Test>::NotAnInstitution
+        );
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        assert_noop!(
+            Credential::set_delegate(
+                RuntimeOrigin::signed(UNIVERSITY),
+                UNIVERSITY,
+                permissions.clone()
+            ),
+            Error::<Test>::InvalidDelegate
+        );
+        // Delegates are DIDs, so the registrar needs one
+        assert_noop!(
+            Credential::set_delegate(
+                RuntimeOrigin::signed(UNIVERSITY),
+                REGISTRAR,
+                permissions.clone()
+            ),
+            Error::<Test>::InvalidDelegate
+        );
+
+        create_did_for_account(REGISTRAR);
+        assert_noop!(
+            Credential::set_delegate(
+                RuntimeOrigin::signed(UNIVERSITY),
+                REGISTRAR,
+                delegate_permissions(false, false, false, vec![CredentialType::Degree])
+            ),
+            Error::<Test>::InvalidDelegatePermissions
+        );
+
+        assert_ok!(Did::deregister_institution(RuntimeOrigin::signed(
+            UNIVERSITY
+        )));
+        assert_noop!(
+            Credential::set_delegate(RuntimeOrigin::signed(UNIVERSITY), REGISTRAR, permissions),
+            Error::<Test>::IssuerClosed
+        );
+    });
+}
+
+#[test]
+fn remove_delegate_releases_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(REGISTRAR);
+
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(true, true, true, vec![])
+        ));
+        assert_ok!(Credential::remove_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR
+        ));
+
+        assert_eq!(Credential::delegates(UNIVERSITY, REGISTRAR), None);
+        assert_eq!(Credential::delegate_deposits(UNIVERSITY, REGISTRAR), None);
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::DelegateDeposit.into(), &UNIVERSITY),
+            0
+        );
+        System::assert_last_event(
+            Event::DelegateRemoved {
+                institution: UNIVERSITY,
+                delegate: REGISTRAR,
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            Credential::remove_delegate(RuntimeOrigin::signed(UNIVERSITY), REGISTRAR),
+            Error::<Test>::DelegateNotFound
+        );
+    });
+}
+
+#[test]
+fn set_delegate_fails_past_max_delegates() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        let permissions = delegate_permissions(true, false, false, vec![]);
+        for delegate in 10..10 + MaxDelegates::get() as u64 {
+            create_did_for_account(delegate);
+            assert_ok!(Credential::set_delegate(
+                RuntimeOrigin::signed(UNIVERSITY),
+                delegate,
+                permissions.clone()
+            ));
+        }
+
+        create_did_for_account(REGISTRAR);
+        assert_noop!(
+            Credential::set_delegate(
+                RuntimeOrigin::signed(UNIVERSITY),
+                REGISTRAR,
+                permissions.clone()
+            ),
+            Error::<Test>::TooManyDelegates
+        );
+        // Existing delegates can still be updated
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            10,
+            delegate_permissions(true, true, false, vec![])
+        ));
+    });
+}
+
+#[test]
+fn controller_transfer_moves_credential_and_delegate_deposits() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let new_controller = 5;
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        create_did_for_account(REGISTRAR);
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            [1u8; 32],
+            CredentialType::Degree,
+            b"Degree".to_vec().try_into().unwrap(),
+            None,
+            ValidityWindow::default()
+        ));
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(true, true, true, vec![])
+        ));
+        let credential_held = credential_deposit_held(UNIVERSITY);
+        let delegate_held =
+            Balances::balance_on_hold(&HoldReason::DelegateDeposit.into(), &UNIVERSITY);
+
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY,
+            new_controller
+        ));
+        assert_ok!(Did::accept_controller(
+            RuntimeOrigin::signed(new_controller),
+            UNIVERSITY
+        ));
+
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+        assert_eq!(
+            Credential::credential_deposits(credential_id)
+                .unwrap()
+                .owner,
+            new_controller
+        );
+        assert_eq!(
+            Credential::delegate_deposits(UNIVERSITY, REGISTRAR)
+                .unwrap()
+                .owner,
+            new_controller
+        );
+        assert_eq!(credential_deposit_held(UNIVERSITY), 0);
+        assert_eq!(credential_deposit_held(new_controller), credential_held);
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::DelegateDeposit.into(), &UNIVERSITY),
+            0
+        );
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::DelegateDeposit.into(), &new_controller),
+            delegate_held
+        );
+    });
+}
+
+#[test]
+fn delegate_issues_credential_on_behalf_of_institution() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(REGISTRAR);
+        create_did_for_account(STUDENT);
+
+        assert_noop!(
+            issue_on_behalf([1u8; 32], CredentialType::Degree),
+            Error::<Test>::NotAuthorized
+        );
+
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(true, false, false, vec![CredentialType::Transcript])
+        ));
+
+        assert_noop!(
+            issue_on_behalf([1u8; 32], CredentialType::Degree),
+            Error::<Test>::NotAuthorized
+        );
+        assert_ok!(issue_on_behalf([1u8; 32], CredentialType::Transcript));
+
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+        let credential = Credential::credentials(credential_id).unwrap();
+        assert_eq!(credential.issuer, UNIVERSITY);
+        assert_eq!(credential.issued_by, Some(REGISTRAR));
+        assert_eq!(
+            Credential::credential_deposits(credential_id)
+                .unwrap()
+                .owner,
+            UNIVERSITY
+        );
+        assert_eq!(credential_deposit_held(REGISTRAR), 0);
+        assert!(Credential::credentials_by_issuer(UNIVERSITY).contains(&credential_id));
+        System::assert_last_event(
+            Event::CredentialIssued {
+                credential_id,
+                credential_hash: [1u8; 32],
+                holder: STUDENT,
+                issuer: UNIVERSITY,
+                issued_by: Some(REGISTRAR),
+                credential_type: CredentialType::Transcript,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn delegate_revoke_and_update_follow_permissions() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(REGISTRAR);
+        create_did_for_account(STUDENT);
+
+        let metadata: BoundedVec<u8, MaxMetadataSize> = b"BSc".to_vec().try_into().unwrap();
+        for (hash, credential_type) in [
+            ([1u8; 32], CredentialType::Degree),
+            ([2u8; 32], CredentialType::Transcript),
+        ] {
+            assert_ok!(Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                hash,
+                credential_type,
+                metadata.clone(),
+                None,
+                ValidityWindow::default()
+            ));
+        }
+        let degree = Credential::credential_by_hash([1u8; 32]).unwrap();
+        let transcript = Credential::credential_by_hash([2u8; 32]).unwrap();
+
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(false, false, true, vec![CredentialType::Degree])
+        ));
+
+        assert_noop!(
+            Credential::revoke_credential(RuntimeOrigin::signed(REGISTRAR), degree),
+            Error::<Test>::NotAuthorized
+        );
+        assert_noop!(
+            Credential::update_credential_metadata(
+                RuntimeOrigin::signed(REGISTRAR),
+                transcript,
+                metadata.clone()
+            ),
+            Error::<Test>::NotAuthorized
+        );
+
+        let new_metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"BSc Computer Science".to_vec().try_into().unwrap();
+        assert_ok!(Credential::update_credential_metadata(
+            RuntimeOrigin::signed(REGISTRAR),
+            degree,
+            new_metadata
+        ));
+        // The institution keeps paying for credentials its delegates amend
+        assert_eq!(
+            Credential::credential_deposits(degree).unwrap().owner,
+            UNIVERSITY
+        );
+        System::assert_last_event(
+            Event::CredentialMetadataUpdated {
+                credential_id: degree,
+                updated_by: REGISTRAR,
+            }
+            .into(),
+        );
+
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(false, true, false, vec![])
+        ));
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(REGISTRAR),
+            transcript
+        ));
+        System::assert_last_event(
+            Event::CredentialRevoked {
+                credential_id: transcript,
+                revoked_by: REGISTRAR,
+            }
+            .into(),
+        );
+    });
+}
+
+// ================== Expiry Tests ==================
+
+fn issue_expiring(credential_hash: [u8; 32], expires_at: u64) -> [u8; 32] {
+    assert_ok!(Credential::issue_credential(
+        RuntimeOrigin::signed(UNIVERSITY),
+        STUDENT,
+        credential_hash,
+        CredentialType::Certificate,
+        b"Certificate".to_vec().try_into().unwrap(),
+        Some(expires_at),
+        ValidityWindow::default()
+    ));
+    Credential::credential_by_hash(credential_hash).unwrap()
+}
+
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        let next = System::block_number() + 1;
+        System::set_block_number(next);
+        Credential::on_initialize(next);
+    }
+}
+
+#[test]
+fn credential_expires_after_its_expiration_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_id = issue_expiring([1u8; 32], 10);
+        assert!(Credential::credential_expiries(11, credential_id).is_some());
+
+        run_to_block(10);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Active
+        );
+
+        run_to_block(11);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Expired
+        );
+        System::assert_last_event(
+            Event::CredentialExpired {
+                credential_id,
+                holder: STUDENT,
+            }
+            .into(),
+        );
+        assert!(Credential::credential_expiries(11, credential_id).is_none());
+        assert_eq!(Credential::expiry_cursor(), 12);
+
+        assert_noop!(
+            Credential::verify_credential(RuntimeOrigin::signed(VERIFIER), [1u8; 32]),
+            Error::<Test>::CredentialExpired
+        );
+    });
+}
+
+#[test]
+fn expiry_backlog_is_bounded_and_worked_off_on_idle() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_ids = [1u8, 2, 3].map(|i| issue_expiring([i; 32], 5));
+        let expired = || {
+            credential_ids
+                .iter()
+                .filter(|id| {
+                    Credential::credentials(*id).unwrap().status == CredentialStatus::Expired
+                })
+                .count() as u32
+        };
+
+        run_to_block(6);
+        assert_eq!(expired(), MaxExpiriesPerBlock::get());
+
+        Credential::on_idle(6, Weight::MAX);
+        assert_eq!(expired(), 3);
+
+        // The idle hook stays within the weight it is given
+        issue_expiring([4u8; 32], 6);
+        System::set_block_number(7);
+        assert_eq!(Credential::on_idle(7, Weight::zero()), Weight::zero());
+        assert_eq!(Credential::expiry_cursor(), 7);
+    });
+}
+
+#[test]
+fn revoked_credential_does_not_expire() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_id = issue_expiring([1u8; 32], 5);
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+
+        run_to_block(6);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Revoked
+        );
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Credential(Event::CredentialExpired { .. })
+        )));
+    });
+}
+
+fn issue_with_window(credential_hash: [u8; 32], valid_from: Option<u64>, valid_until: Option<u64>) {
+    assert_ok!(Credential::issue_credential(
+        RuntimeOrigin::signed(UNIVERSITY),
+        STUDENT,
+        credential_hash,
+        CredentialType::Certificate,
+        b"Certificate".to_vec().try_into().unwrap(),
+        None,
+        ValidityWindow {
+            valid_from,
+            valid_until,
+        }
+    ));
+}
+
+#[test]
+fn verify_credential_checks_validity_window() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(500);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        issue_with_window([1u8; 32], Some(1_000), Some(2_000));
+
+        assert_noop!(
+            Credential::verify_credential(RuntimeOrigin::signed(VERIFIER), [1u8; 32]),
+            Error::<Test>::CredentialNotYetValid
+        );
+
+        Timestamp::set_timestamp(1_000);
+        assert_ok!(Credential::verify_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            [1u8; 32]
+        ));
+
+        // The window end is checked by time alone, whatever the block number
+        Timestamp::set_timestamp(2_000);
+        assert_noop!(
+            Credential::verify_credential(RuntimeOrigin::signed(VERIFIER), [1u8; 32]),
+            Error::<Test>::CredentialExpired
+        );
+    });
+}
+
+#[test]
+fn credential_expires_at_end_of_validity_window() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(500);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        issue_with_window([1u8; 32], None, Some(800));
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+
+        // Queued at the block expected to follow the end of the window
+        let expected = 1 + (800 - 500) / ExpectedBlockTime::get();
+        assert!(Credential::credential_expiries(expected, credential_id).is_some());
+
+        // Blocks were slower than expected, so it is queued again
+        Timestamp::set_timestamp(750);
+        run_to_block(expected);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Active
+        );
+        assert!(Credential::credential_expiries(expected + 1, credential_id).is_some());
+
+        Timestamp::set_timestamp(800);
+        run_to_block(expected + 1);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Expired
+        );
+        System::assert_last_event(
+            Event::CredentialExpired {
+                credential_id,
+                holder: STUDENT,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn issue_credential_rejects_empty_validity_window() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        assert_noop!(
+            Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                [1u8; 32],
+                CredentialType::Certificate,
+                b"Certificate".to_vec().try_into().unwrap(),
+                None,
+                ValidityWindow {
+                    valid_from: Some(2_000),
+                    valid_until: Some(2_000),
+                }
+            ),
+            Error::<Test>::InvalidValidityWindow
+        );
+
+        // Open-ended windows need no ordering
+        issue_with_window([2u8; 32], Some(2_000), None);
+        issue_with_window([3u8; 32], None, Some(2_000));
+    });
+}
+
+// ================== Suspension Tests ==================
+
+#[test]
+fn suspend_and_reinstate_credential_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        let credential_id = issue_expiring([1u8; 32], 100);
+
+        System::set_block_number(5);
+        assert_ok!(Credential::suspend_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            SuspensionReason::MisconductInvestigation
+        ));
+        System::assert_last_event(
+            Event::CredentialSuspended {
+                credential_id,
+                suspended_by: UNIVERSITY,
+                reason: SuspensionReason::MisconductInvestigation,
+            }
+            .into(),
+        );
+
+        let suspension = Credential::suspensions(credential_id).unwrap();
+        assert_eq!(suspension.reason, SuspensionReason::MisconductInvestigation);
+        assert_eq!(suspension.suspended_by, UNIVERSITY);
+        assert_eq!(suspension.suspended_at, 5);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Suspended
+        );
+
+        // Suspension is reported distinctly from revocation and expiry
+        assert_noop!(
+            Credential::verify_credential(RuntimeOrigin::signed(VERIFIER), [1u8; 32]),
+            Error::<Test>::CredentialSuspended
+        );
+        assert_noop!(
+            Credential::suspend_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                credential_id,
+                SuspensionReason::Other
+            ),
+            Error::<Test>::CredentialNotActive
+        );
+
+        assert_ok!(Credential::reinstate_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        System::assert_last_event(
+            Event::CredentialReinstated {
+                credential_id,
+                reinstated_by: UNIVERSITY,
+            }
+            .into(),
+        );
+        assert!(Credential::suspensions(credential_id).is_none());
+        assert_ok!(Credential::verify_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            [1u8; 32]
+        ));
+        assert_noop!(
+            Credential::reinstate_credential(RuntimeOrigin::signed(UNIVERSITY), credential_id),
+            Error::<Test>::CredentialNotSuspended
+        );
+    });
+}
+
+#[test]
+fn only_issuer_can_suspend_or_reinstate() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(REGISTRAR);
+        create_did_for_account(STUDENT);
+        let credential_id = issue_expiring([1u8; 32], 100);
+
+        // Not even a delegate with every permission
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(true, true, true, vec![])
+        ));
+        for who in [REGISTRAR, STUDENT] {
+            assert_noop!(
+                Credential::suspend_credential(
+                    RuntimeOrigin::signed(who),
+                    credential_id,
+                    SuspensionReason::Dispute
+                ),
+                Error::<Test>::NotAuthorized
+            );
+        }
+
+        assert_ok!(Credential::suspend_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            SuspensionReason::Dispute
+        ));
+        assert_noop!(
+            Credential::reinstate_credential(RuntimeOrigin::signed(REGISTRAR), credential_id),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn suspended_credential_can_be_revoked() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        let credential_id = issue_expiring([1u8; 32], 100);
+
+        assert_ok!(Credential::suspend_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            SuspensionReason::MisconductInvestigation
+        ));
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+
+        assert!(Credential::suspensions(credential_id).is_none());
+        assert_noop!(
+            Credential::reinstate_credential(RuntimeOrigin::signed(UNIVERSITY), credential_id),
+            Error::<Test>::CredentialNotSuspended
+        );
+    });
+}
+
+#[test]
+fn credential_reinstated_after_its_expiration_block_expires() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        let credential_id = issue_expiring([1u8; 32], 5);
+
+        assert_ok!(Credential::suspend_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            SuspensionReason::Administrative
+        ));
+
+        // The expiry queue leaves suspended credentials alone
+        run_to_block(6);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Suspended
+        );
+
+        assert_ok!(Credential::reinstate_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Expired
+        );
+        System::assert_last_event(
+            Event::CredentialExpired {
+                credential_id,
+                holder: STUDENT,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn credential_reinstated_before_its_window_ends_still_expires() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(500);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        issue_with_window([1u8; 32], None, Some(800));
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+        let expected = 1 + (800 - 500) / ExpectedBlockTime::get();
+
+        assert_ok!(Credential::suspend_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id,
+            SuspensionReason::Administrative
+        ));
+
+        // The queue entry fires early while suspended and is kept
+        Timestamp::set_timestamp(750);
+        run_to_block(expected);
+        assert!(Credential::credential_expiries(expected + 1, credential_id).is_some());
+
+        assert_ok!(Credential::reinstate_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Active
+        );
+
+        Timestamp::set_timestamp(800);
+        run_to_block(expected + 1);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Expired
+        );
+    });
+}
+
+// ================== Migration Tests ==================
+
+#[test]
+fn migration_v1_records_no_delegate() {
+    use crate::{
+        migrations::v1::{self, OldCredential},
+        Credentials,
+    };
+    use frame::deps::frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+    new_test_ext().execute_with(|| {
+        let credential_id = [7u8; 32];
+        let old_credential = OldCredential::<Test> {
+            credential_id,
+            credential_hash: [1u8; 32],
+            holder: STUDENT,
+            issuer: UNIVERSITY,
+            credential_type: CredentialType::Degree,
+            metadata: b"BSc".to_vec().try_into().unwrap(),
+            issued_at: 5,
+            expires_at: None,
+            status: CredentialStatus::Active,
+        };
+        unhashed::put(
+            &Credentials::<Test>::hashed_key_for(credential_id),
+            &old_credential,
+        );
+        StorageVersion::new(0).put::<Credential>();
+
+        v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+        let credential = v1::Credentials::<Test>::get(credential_id).unwrap();
+        assert_eq!(credential.issuer, UNIVERSITY);
+        assert_eq!(credential.issued_by, None);
+        assert_eq!(credential.issued_at, 5);
+        assert_eq!(StorageVersion::get::<Credential>(), 1);
+    });
+}
+
+#[test]
+fn migration_v2_keys_credentials_by_did() {
+    use crate::migrations::{
+        v1::{self, CredentialV1},
+        v2,
+    };
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let credential_id = [7u8; 32];
+        v1::Credentials::<Test>::insert(
+            credential_id,
+            CredentialV1::<Test> {
+                credential_id,
+                credential_hash: [1u8; 32],
+                holder: STUDENT,
+                issuer: UNIVERSITY,
+                issued_by: Some(REGISTRAR),
+                credential_type: CredentialType::Degree,
+                metadata: b"BSc".to_vec().try_into().unwrap(),
+                issued_at: 5,
+                expires_at: None,
+                status: CredentialStatus::Active,
+            },
+        );
+        v2::CredentialsByIssuer::<Test>::insert(
+            UNIVERSITY,
+            BoundedVec::truncate_from(vec![credential_id]),
+        );
+        v2::Delegates::<Test>::insert(
+            UNIVERSITY,
+            REGISTRAR,
+            delegate_permissions(true, false, false, vec![]),
+        );
+        StorageVersion::new(1).put::<Credential>();
+
+        v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+        let credential = v2::Credentials::<Test>::get(credential_id).unwrap();
+        assert_eq!(credential.holder, STUDENT);
+        assert_eq!(credential.issuer, UNIVERSITY);
+        assert_eq!(credential.issued_by, Some(REGISTRAR));
+        assert_eq!(
+            Credential::credentials_by_issuer(UNIVERSITY).into_inner(),
+            vec![credential_id]
+        );
+        assert!(
+            Credential::delegates(UNIVERSITY, REGISTRAR)
+                .unwrap()
+                .can_issue
+        );
+        assert_eq!(StorageVersion::get::<Credential>(), 2);
+    });
+}
+
+#[test]
+fn migration_v3_records_no_signing_key() {
+    use crate::migrations::v2::{self, CredentialV2};
+    use crate::migrations::v3;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let credential_id = [7u8; 32];
+        v2::Credentials::<Test>::insert(
+            credential_id,
+            CredentialV2::<Test> {
+                credential_id,
+                credential_hash: [1u8; 32],
+                holder: STUDENT,
+                issuer: UNIVERSITY,
+                issued_by: Some(REGISTRAR),
+                credential_type: CredentialType::Degree,
+                metadata: b"BSc".to_vec().try_into().unwrap(),
+                issued_at: 5,
+                expires_at: None,
+                status: CredentialStatus::Active,
+            },
+        );
+        StorageVersion::new(2).put::<Credential>();
+
+        v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+        let credential = v3::Credentials::<Test>::get(credential_id).unwrap();
+        assert_eq!(credential.issued_by, Some(REGISTRAR));
+        assert_eq!(credential.signed_with, None);
+        assert_eq!(credential.issued_at, 5);
+        assert_eq!(StorageVersion::get::<Credential>(), 3);
+    });
+}
+
+fn credential_v3(
+    credential_id: [u8; 32],
+    expires_at: Option<u64>,
+) -> crate::migrations::v3::CredentialV3<Test> {
+    crate::migrations::v3::CredentialV3 {
+        credential_id,
+        credential_hash: credential_id,
+        holder: STUDENT,
+        issuer: UNIVERSITY,
+        issued_by: None,
+        signed_with: None,
+        credential_type: CredentialType::Certificate,
+        metadata: b"Certificate".to_vec().try_into().unwrap(),
+        issued_at: 1,
+        expires_at,
+        status: CredentialStatus::Active,
+    }
+}
+
+#[test]
+fn migration_v4_queues_expiries() {
+    use crate::migrations::{v3, v4};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let expired = [1u8; 32];
+        let expiring = [2u8; 32];
+        let revoked = [3u8; 32];
+        v3::Credentials::<Test>::insert(expired, credential_v3(expired, Some(5)));
+        v3::Credentials::<Test>::insert(expiring, credential_v3(expiring, Some(50)));
+        v3::Credentials::<Test>::insert(
+            revoked,
+            v3::CredentialV3 {
+                status: CredentialStatus::Revoked,
+                ..credential_v3(revoked, Some(5))
+            },
+        );
+        StorageVersion::new(3).put::<Credential>();
+
+        System::set_block_number(20);
+        v4::MigrateV3ToV4::<Test>::on_runtime_upgrade();
+
+        assert!(Credential::credential_expiries(20, expired).is_some());
+        assert!(Credential::credential_expiries(51, expiring).is_some());
+        assert_eq!(crate::CredentialExpiries::<Test>::iter().count(), 2);
+        assert_eq!(Credential::expiry_cursor(), 20);
+        assert_eq!(StorageVersion::get::<Credential>(), 4);
+    });
+}
+
+#[test]
+fn migration_v5_keeps_expiration_block() {
+    use crate::migrations::{v3, v5};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let credential_id = [1u8; 32];
+        v3::Credentials::<Test>::insert(credential_id, credential_v3(credential_id, Some(30)));
+        StorageVersion::new(4).put::<Credential>();
+
+        v5::MigrateV4ToV5::<Test>::on_runtime_upgrade();
+
+        let credential = Credential::credentials(credential_id).unwrap();
+        assert_eq!(credential.expires_at, Some(30));
+        assert_eq!(credential.valid_from, None);
+        assert_eq!(credential.valid_until, None);
+        assert_eq!(credential.status, CredentialStatus::Active);
+        assert_eq!(StorageVersion::get::<Credential>(), 5);
+    });
+}
+
+// ================== DID Deletion Tests ==================
+
+#[test]
+fn institution_did_cannot_be_deleted_with_active_credentials() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        let metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"Computer Science Degree".to_vec().try_into().unwrap();
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            metadata,
+            None,
+            ValidityWindow::default()
+        ));
+
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(UNIVERSITY), UNIVERSITY),
+            did::Error::<Test>::InstitutionHasActiveCredentials
+        );
+
+        // Once its credentials are no longer active the institution can leave
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+
+        assert_ok!(Did::delete_did(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY
+        ));
+        assert!(Did::institutions(UNIVERSITY).is_none());
+    });
+}
+
+#[test]
+fn deleting_institution_did_releases_every_hold() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        create_did_for_account(REGISTRAR);
+
+        let credential_hash = [1u8; 32];
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            b"Degree".to_vec().try_into().unwrap(),
+            None,
+            ValidityWindow::default()
+        ));
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+        assert_ok!(Credential::set_delegate(
+            RuntimeOrigin::signed(UNIVERSITY),
+            REGISTRAR,
+            delegate_permissions(true, true, false, vec![])
+        ));
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert!(credential_deposit_held(UNIVERSITY) > 0);
+
+        assert_ok!(Did::delete_did(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY
+        ));
+
+        // Nothing the institution paid for is left behind
+        assert_eq!(Balances::total_balance_on_hold(&UNIVERSITY), 0);
+        assert!(Credential::credentials(credential_id).is_none());
+        assert_eq!(Credential::credential_by_hash(credential_hash), None);
+        assert!(Credential::credentials_by_holder(STUDENT).is_empty());
+        assert!(Credential::credentials_by_issuer(UNIVERSITY).is_empty());
+        assert_eq!(Credential::delegates(UNIVERSITY, REGISTRAR), None);
+        assert_eq!(Credential::delegate_deposits(UNIVERSITY, REGISTRAR), None);
+    });
+}
+
+#[test]
+fn credentials_outlive_their_holder_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            b"Degree".to_vec().try_into().unwrap(),
+            None,
+            ValidityWindow::default()
+        ));
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(STUDENT), STUDENT));
+
+        // The issuer still owns the credential and its deposit
+        assert_eq!(Credential::credentials_by_holder(STUDENT).len(), 1);
+        assert!(credential_deposit_held(UNIVERSITY) > 0);
+
+        // Nothing more can be issued to the deleted DID
+        assert_noop!(
+            Credential::issue_credential(
+                RuntimeOrigin::signed(UNIVERSITY),
+                STUDENT,
+                [2u8; 32],
+                CredentialType::Degree,
+                b"Degree".to_vec().try_into().unwrap(),
+                None,
+                ValidityWindow::default()
+            ),
+            Error::<Test>::HolderHasNoDid
+        );
+
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert_ok!(Credential::remove_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            credential_id
+        ));
+        assert!(Credential::credentials_by_holder(STUDENT).is_empty());
+        assert_eq!(credential_deposit_held(UNIVERSITY), 0);
+    });
+}
+
+// ================== DID Recovery Tests ==================
+
+#[test]
+fn recovered_holder_keeps_credentials() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let new_account = 4u64;
+        let guardian = VERIFIER;
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        let metadata: BoundedVec<u8, MaxMetadataSize> =
+            b"Computer Science Degree".to_vec().try_into().unwrap();
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(UNIVERSITY),
+            STUDENT,
+            credential_hash,
+            CredentialType::Degree,
+            metadata,
+            None,
+            ValidityWindow::default()
+        ));
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+
+        assert_ok!(Did::set_recovery_config(
+            RuntimeOrigin::signed(STUDENT),
+            STUDENT,
+            vec![guardian].try_into().unwrap(),
+            1
+        ));
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(guardian),
+            STUDENT,
+            new_account
+        ));
+
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_ok!(Did::claim_recovery(
+            RuntimeOrigin::signed(new_account),
+            STUDENT
+        ));
+
+        // Credentials reference the DID, which survives the change of account
+        let references = Credential::credentials_by_holder(STUDENT);
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].credential_id, credential_id);
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().holder,
+            STUDENT
+        );
+        assert!(Credential::credentials_by_holder(new_account).is_empty());
+        assert_eq!(Did::account_dids(new_account), Some(STUDENT));
+    });
+}
+
+// ================== Linked Account Tests ==================
+
+#[test]
+fn linked_account_issues_for_institution() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let staff = 5u64;
+
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+        link_account(staff, UNIVERSITY);
+
+        assert_ok!(Credential::issue_credential(
+            RuntimeOrigin::signed(staff),
+            STUDENT,
+            [1u8; 32],
+            CredentialType::Degree,
+            b"BSc".to_vec().try_into().unwrap(),
+            None,
+            ValidityWindow::default()
+        ));
+
+        // The credential is issued by the DID and paid for by its controller
+        let credential_id = Credential::credential_by_hash([1u8; 32]).unwrap();
+        let credential = Credential::credentials(credential_id).unwrap();
+        assert_eq!(credential.issuer, UNIVERSITY);
+        assert_eq!(credential.issued_by, None);
+        assert_eq!(
+            Credential::credential_deposits(credential_id)
+                .unwrap()
+                .owner,
+            UNIVERSITY
+        );
+        assert_eq!(credential_deposit_held(staff), 0);
+
+        assert_ok!(Credential::revoke_credential(
+            RuntimeOrigin::signed(staff),
+            credential_id
+        ));
+        System::assert_last_event(
+            Event::CredentialRevoked {
+                credential_id,
+                revoked_by: UNIVERSITY,
+            }
+            .into(),
+        );
+
+        // Once unlinked, the account no longer acts for the institution
+        assert_ok!(Did::unlink_account(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY,
+            staff
+        ));
+        assert_noop!(
+            Credential::issue_credential(
+                RuntimeOrigin::signed(staff),
+                STUDENT,
+                [2u8; 32],
+                CredentialType::Degree,
+                b"BSc".to_vec().try_into().unwrap(),
+                None,
+                ValidityWindow::default()
+            ),
+            Error::<Test>::IssuerHasNoDid
+        );
+    });
+}
+
+// ================== DID Call Tests ==================
+
+#[test]
+fn institution_issues_with_did_key_from_separate_fee_payer() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let relayer = 9u64;
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let credential_hash = [1u8; 32];
+        let call = RuntimeCall::Credential(crate::Call::issue_credential {
+            holder: STUDENT,
+            credential_hash,
+            credential_type: CredentialType::Degree,
+            metadata: b"BSc".to_vec().try_into().unwrap(),
+            expires_at: None,
+            validity: ValidityWindow::default(),
+        });
+        submit_did_call(relayer, UNIVERSITY, call);
+
+        let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
+        let credential = Credential::credentials(credential_id).unwrap();
+        assert_eq!(credential.issuer, UNIVERSITY);
+        assert_eq!(credential.issued_by, None);
+
+        // The relayer is not linked to the DID and cannot act for it directly
+        assert_noop!(
+            Credential::revoke_credential(RuntimeOrigin::signed(relayer), credential_id),
+            Error::<Test>::NotAuthorized
+        );
+
+        submit_did_call(
+            relayer,
+            UNIVERSITY,
+            RuntimeCall::Credential(crate::Call::revoke_credential { credential_id }),
+        );
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().status,
+            CredentialStatus::Revoked
+        );
+    });
+}
+
+#[test]
+fn credential_signed_with_compromised_key_is_untrusted() {
+    use frame::deps::sp_core::{ed25519, Pair};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let relayer = 9u64;
+        setup_verified_institution(UNIVERSITY, "MIT");
+        create_did_for_account(STUDENT);
+
+        let issue = |credential_hash: [u8; 32]| {
+            RuntimeCall::Credential(crate::Call::issue_credential {
+                holder: STUDENT,
+                credential_hash,
+                credential_type: CredentialType::Degree,
+                metadata: b"BSc".to_vec().try_into().unwrap(),
+                expires_at: None,
+                validity: ValidityWindow::default(),
+            })
+        };
+        let key_id =
+            did::PublicKey::Ed25519(ed25519::Pair::from_seed(&[UNIVERSITY as u8; 32]).public().0)
+                .key_id();
+
+        submit_did_call(relayer, UNIVERSITY, issue([1u8; 32]));
+        System::set_block_number(10);
+        submit_did_call(relayer, UNIVERSITY, issue([2u8; 32]));
+        let credential_id = Credential::credential_by_hash([2u8; 32]).unwrap();
+        assert_eq!(
+            Credential::credentials(credential_id).unwrap().signed_with,
+            Some(key_id)
+        );
+
+        // A new key takes over before the old one is reported
+        let new_key = ed25519::Pair::from_seed(&[42u8; 32]);
+        let proof = new_key.sign(&Did::key_possession_payload(&UNIVERSITY));
+        assert_ok!(Did::add_public_key(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY,
+            did::PublicKey::Ed25519(new_key.public().0),
+            proof.into()
+        ));
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(UNIVERSITY),
+            UNIVERSITY,
+            did::PublicKey::Ed25519(new_key.public().0).key_id(),
+            did::VerificationRelationship::Authentication
+        ));
+
+        System::set_block_number(20);
+        assert_ok!(Did::report_key_compromised(
+            RuntimeOrigin::signed(UNIVERSITY),
+            key_id,
+            5
+        ));
+
+        // Issued before the compromise
+        assert_ok!(Credential::verify_credential(
+            RuntimeOrigin::signed(VERIFIER),
+            [1u8; 32]
+        ));
+        assert_noop!(
+            Credential::verify_credential(RuntimeOrigin::signed(VERIFIER), [2u8; 32]),
+            Error::<Test>::SigningKeyCompromised
+        );
+    });
+}
+
 // ================== Integration Tests ==================
 
 #[test]
@@ -662,7 +2442,8 @@ fn complete_credential_lifecycle() {
             credential_hash,
             CredentialType::Degree,
             metadata,
-            Some(1000)
+            Some(1000),
+            ValidityWindow::default()
         ));
 
         let credential_id = Credential::credential_by_hash(credential_hash).unwrap();
@@ -723,7 +2504,8 @@ fn multiple_credentials_per_student() {
             hash1,
             CredentialType::Degree,
             metadata1,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         // Issue master's degree
@@ -736,7 +2518,8 @@ fn multiple_credentials_per_student() {
             hash2,
             CredentialType::MastersDegree,
             metadata2,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         // Issue certificate
@@ -749,7 +2532,8 @@ fn multiple_credentials_per_student() {
             hash3,
             CredentialType::Certificate,
             metadata3,
-            None
+            None,
+            ValidityWindow::default()
         ));
 
         // Verify all credentials exist
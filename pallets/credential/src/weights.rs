@@ -5,6 +5,14 @@ pub trait WeightInfo {
     fn revoke_credential() -> Weight;
     fn verify_credential() -> Weight;
     fn update_credential_metadata() -> Weight;
+    fn set_delegate() -> Weight;
+    fn remove_delegate() -> Weight;
+    fn issue_credential_on_behalf() -> Weight;
+    fn expire_credential() -> Weight;
+    fn suspend_credential() -> Weight;
+    fn reinstate_credential() -> Weight;
+    fn remove_credential() -> Weight;
+    fn transfer_deposit() -> Weight;
 }
 
 pub struct SubstrateWeight;
@@ -22,4 +30,28 @@ impl WeightInfo for SubstrateWeight {
     fn update_credential_metadata() -> Weight {
         Weight::from_parts(25_000_000, 0)
     }
+    fn set_delegate() -> Weight {
+        Weight::from_parts(20_000_000, 0)
+    }
+    fn remove_delegate() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn issue_credential_on_behalf() -> Weight {
+        Weight::from_parts(55_000_000, 0)
+    }
+    fn expire_credential() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn suspend_credential() -> Weight {
+        Weight::from_parts(25_000_000, 0)
+    }
+    fn reinstate_credential() -> Weight {
+        Weight::from_parts(25_000_000, 0)
+    }
+    fn remove_credential() -> Weight {
+        Weight::from_parts(30_000_000, 0)
+    }
+    fn transfer_deposit() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
 }
@@ -5,7 +5,21 @@
 //! ## Overview
 //! This pallet implements Decentralized Identifiers (DIDs) for academic verification.
 
-use frame::hashing;
+extern crate alloc;
+
+use alloc::boxed::Box;
+use frame::{
+    deps::{
+        sp_core::{ecdsa, ed25519, sr25519},
+        sp_io,
+    },
+    hashing,
+    prelude::{DispatchResult, Weight},
+    traits::{
+        fungible::{Inspect, MutateHold},
+        tokens::Precision,
+    },
+};
 pub use pallet::*;
 
 #[cfg(test)]
@@ -17,15 +31,73 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+pub mod runtime_api;
 pub mod weights;
 pub use weights::*;
 
+/// Lets the DID pallet ask whether an issuer still has credentials in force,
+/// move the deposits held for them when its controller changes and release
+/// them when it is deleted
+pub trait IssuedCredentials<DidIdentifier> {
+    /// Whether `issuer` has issued any credential that is still active
+    fn has_active_credentials(issuer: &DidIdentifier) -> bool;
+
+    /// Hold the deposits of everything `issuer` owns from its current
+    /// controller, releasing them from the previous one
+    fn transfer_deposits(issuer: &DidIdentifier) -> DispatchResult;
+
+    /// Upper bound on the weight of `transfer_deposits`
+    fn transfer_deposits_weight() -> Weight;
+
+    /// Remove everything `issuer` still owns and release its deposits
+    fn release_deposits(issuer: &DidIdentifier) -> DispatchResult;
+
+    /// Upper bound on the weight of `release_deposits`
+    fn release_deposits_weight() -> Weight;
+}
+
+impl<DidIdentifier> IssuedCredentials<DidIdentifier> for () {
+    fn has_active_credentials(_issuer: &DidIdentifier) -> bool {
+        false
+    }
+
+    fn transfer_deposits(_issuer: &DidIdentifier) -> DispatchResult {
+        Ok(())
+    }
+
+    fn transfer_deposits_weight() -> Weight {
+        Weight::zero()
+    }
+
+    fn release_deposits(_issuer: &DidIdentifier) -> DispatchResult {
+        Ok(())
+    }
+
+    fn release_deposits_weight() -> Weight {
+        Weight::zero()
+    }
+}
+
 #[frame::pallet]
 pub mod pallet {
     use super::*;
     use frame::prelude::*;
 
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(12);
+
+    /// Domain separator for key proof-of-possession payloads
+    pub const KEY_POSSESSION_CONTEXT: &[u8] = b"academic-did/key-possession";
+
+    /// Domain separator for a DID's consent to linking an account
+    pub const ACCOUNT_LINK_CONTEXT: &[u8] = b"academic-did/account-link";
+
+    /// Domain separator for calls signed with a DID key
+    pub const DID_CALL_CONTEXT: &[u8] = b"academic-did/did-call";
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     /// The pallet's configuration trait
@@ -34,6 +106,28 @@ pub mod pallet {
         /// The overarching event type
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
+        /// Identifier of a DID, independent of the accounts linked to it
+        type DidIdentifier: Parameter + Member + MaxEncodedLen;
+
+        /// Derives the identifier of a new DID from the account creating it
+        type DidIdentifierOf: Convert<Self::AccountId, Self::DidIdentifier>;
+
+        /// Maximum number of accounts linked to a DID
+        #[pallet::constant]
+        type MaxLinkedAccounts: Get<u32>;
+
+        /// The overarching origin type, able to represent a DID origin
+        type RuntimeOrigin: From<Origin<Self>>
+            + Into<Result<Origin<Self>, <Self as Config>::RuntimeOrigin>>
+            + IsType<<Self as frame_system::Config>::RuntimeOrigin>;
+
+        /// The overarching call type, dispatched by `submit_did_call`
+        type RuntimeCall: Parameter
+            + Dispatchable<
+                RuntimeOrigin = <Self as Config>::RuntimeOrigin,
+                PostInfo = PostDispatchInfo,
+            > + GetDispatchInfo;
+
         /// Maximum size of institution name or document metadata
         #[pallet::constant]
         type MaxDocumentSize: Get<u32>;
@@ -42,41 +136,262 @@ pub mod pallet {
         #[pallet::constant]
         type MaxPublicKeys: Get<u32>;
 
+        /// Maximum number of key history records kept per DID, which must exceed
+        /// `MaxPublicKeys`
+        #[pallet::constant]
+        type MaxKeyHistory: Get<u32>;
+
+        /// Maximum number of compromised keys kept on record per DID
+        #[pallet::constant]
+        type MaxCompromisedKeys: Get<u32>;
+
+        /// Maximum number of service endpoints per DID
+        #[pallet::constant]
+        type MaxServiceEndpoints: Get<u32>;
+
+        /// Maximum length of a service endpoint URL
+        #[pallet::constant]
+        type MaxUrlLength: Get<u32>;
+
+        /// Currency used for storage deposits
+        type Currency: MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>;
+
+        /// The overarching hold reason
+        type RuntimeHoldReason: From<HoldReason>;
+
+        /// Base deposit held for each DID document or institution registration
+        #[pallet::constant]
+        type DidDeposit: Get<BalanceOf<Self>>;
+
+        /// Additional deposit held per encoded byte of stored data
+        #[pallet::constant]
+        type ByteDeposit: Get<BalanceOf<Self>>;
+
+        /// Credentials issued by institutions, consulted before deleting a DID
+        /// and told to move their deposits when a controller changes
+        type IssuedCredentials: IssuedCredentials<Self::DidIdentifier>;
+
+        /// Maximum number of recovery guardians per DID
+        #[pallet::constant]
+        type MaxGuardians: Get<u32>;
+
+        /// Blocks between guardians approving a recovery and it being claimable,
+        /// during which the current controller can cancel it
+        #[pallet::constant]
+        type RecoveryDelay: Get<BlockNumberFor<Self>>;
+
+        /// Origin allowed to verify institutions
+        type VerifyOrigin: EnsureOrigin<<Self as frame_system::Config>::RuntimeOrigin>;
+
+        /// Origin allowed to revoke institution verification
+        type RevokeOrigin: EnsureOrigin<<Self as frame_system::Config>::RuntimeOrigin>;
+
+        /// Blocks an institution verification lasts before it must be renewed
+        #[pallet::constant]
+        type VerificationPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of institution verifications lapsed in
+        /// `on_initialize`; any remainder is lapsed in `on_idle` or the
+        /// following blocks
+        #[pallet::constant]
+        type MaxLapsesPerBlock: Get<u32>;
+
         /// Weight information for extrinsics
         type WeightInfo: WeightInfo;
     }
 
+    /// Reasons for this pallet placing a hold on funds
+    #[pallet::composite_enum]
+    pub enum HoldReason {
+        /// Deposit for a DID document and its service endpoints
+        DidDeposit,
+        /// Deposit for an institution registration
+        InstitutionDeposit,
+    }
+
     //Events
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         DidCreated {
-            owner: T::AccountId,
+            owner: T::DidIdentifier,
         },
         DidUpdated {
-            owner: T::AccountId,
+            owner: T::DidIdentifier,
         },
         DidDeactivated {
-            owner: T::AccountId,
+            owner: T::DidIdentifier,
         },
         PublicKeyAdded {
-            owner: T::AccountId,
+            owner: T::DidIdentifier,
             key_id: [u8; 32],
         },
         PublicKeyRemoved {
-            owner: T::AccountId,
+            owner: T::DidIdentifier,
+            key_id: [u8; 32],
+        },
+        KeyCompromised {
+            owner: T::DidIdentifier,
             key_id: [u8; 32],
+            compromised_since: BlockNumberFor<T>,
         },
         InstitutionRegistered {
-            did: T::AccountId,
+            did: T::DidIdentifier,
             name: BoundedVec<u8, T::MaxDocumentSize>,
         },
         InstitutionVerified {
-            did: T::AccountId,
+            did: T::DidIdentifier,
+            accreditor: Option<T::DidIdentifier>,
         },
         InstitutionRevoked {
-            did: T::AccountId,
+            did: T::DidIdentifier,
+        },
+        ControllerTransferInitiated {
+            did: T::DidIdentifier,
+            new_controller: T::AccountId,
+        },
+        ControllerTransferCancelled {
+            did: T::DidIdentifier,
+        },
+        ControllerTransferred {
+            did: T::DidIdentifier,
+            old_controller: T::AccountId,
+            new_controller: T::AccountId,
+        },
+        VerificationRelationshipAdded {
+            owner: T::DidIdentifier,
+            key_id: [u8; 32],
+            relationship: VerificationRelationship,
+        },
+        VerificationRelationshipRemoved {
+            owner: T::DidIdentifier,
+            key_id: [u8; 32],
+            relationship: VerificationRelationship,
+        },
+        ServiceEndpointAdded {
+            owner: T::DidIdentifier,
+            service_id: ServiceId,
         },
+        ServiceEndpointRemoved {
+            owner: T::DidIdentifier,
+            service_id: ServiceId,
+        },
+        DidDeleted {
+            owner: T::DidIdentifier,
+        },
+        RecoveryConfigured {
+            did: T::DidIdentifier,
+            threshold: u32,
+        },
+        RecoveryConfigRemoved {
+            did: T::DidIdentifier,
+        },
+        RecoveryVouched {
+            did: T::DidIdentifier,
+            guardian: T::AccountId,
+            new_account: T::AccountId,
+        },
+        RecoveryCancelled {
+            did: T::DidIdentifier,
+        },
+        DidRecovered {
+            did: T::DidIdentifier,
+            controller: T::AccountId,
+        },
+        AccreditationBodyRegistered {
+            body: T::DidIdentifier,
+            scope: AccreditationScope,
+        },
+        AccreditationBodyRemoved {
+            body: T::DidIdentifier,
+        },
+        InstitutionVerificationRenewed {
+            did: T::DidIdentifier,
+            verified_until: BlockNumberFor<T>,
+        },
+        InstitutionVerificationLapsed {
+            did: T::DidIdentifier,
+        },
+        InstitutionProfileUpdated {
+            did: T::DidIdentifier,
+        },
+        InstitutionDeregistered {
+            did: T::DidIdentifier,
+        },
+        InstitutionClosed {
+            did: T::DidIdentifier,
+        },
+        SuccessorSet {
+            institution: T::DidIdentifier,
+            successor: T::DidIdentifier,
+        },
+        AccountLinked {
+            did: T::DidIdentifier,
+            account: T::AccountId,
+        },
+        AccountUnlinked {
+            did: T::DidIdentifier,
+            account: T::AccountId,
+        },
+        DidCallDispatched {
+            did: T::DidIdentifier,
+            submitter: T::AccountId,
+            result: DispatchResult,
+        },
+    }
+
+    /// Origin of a call authorised by one of a DID's keys
+    #[derive(
+        Clone,
+        PartialEq,
+        Eq,
+        RuntimeDebug,
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        TypeInfo,
+        MaxEncodedLen,
+    )]
+    pub struct DidRawOrigin<DidIdentifier, AccountId> {
+        /// DID whose key signed the call
+        pub did: DidIdentifier,
+        /// Key that signed the call
+        pub key_id: [u8; 32],
+        /// Account that submitted the call and paid its fees
+        pub submitter: AccountId,
+    }
+
+    #[pallet::origin]
+    pub type Origin<T> =
+        DidRawOrigin<<T as Config>::DidIdentifier, <T as frame_system::Config>::AccountId>;
+
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// Storage deposit and the account it is held from
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct Deposit<AccountId, Balance> {
+        pub owner: AccountId,
+        pub amount: Balance,
+    }
+
+    /// Guardians able to recover a DID, and how many of them must agree
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct RecoveryConfig<T: Config> {
+        pub guardians: BoundedVec<T::AccountId, T::MaxGuardians>,
+        pub threshold: u32,
+    }
+
+    /// Recovery in progress, with the account each guardian vouched for
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct ActiveRecovery<T: Config> {
+        /// Guardians paired with the account they vouched for
+        pub vouches: BoundedVec<(T::AccountId, T::AccountId), T::MaxGuardians>,
+        /// Account `threshold` guardians agreed on, and the block they did,
+        /// starting the delay
+        pub approved: Option<(T::AccountId, BlockNumberFor<T>)>,
     }
 
     //Storage
@@ -84,8 +399,135 @@ pub mod pallet {
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     pub struct PublicKeyEntry {
         pub key_id: [u8; 32],
-        pub key_type: KeyType,
-        pub public_key: [u8; 32],
+        pub public_key: PublicKey,
+        pub relationships: BoundedVec<VerificationRelationship, MaxRelationships>,
+    }
+
+    /// Public key material, sized according to its key type
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+    )]
+    pub enum PublicKey {
+        Ed25519([u8; 32]),
+        Sr25519([u8; 32]),
+        /// Compressed secp256k1 point
+        Ecdsa([u8; 33]),
+        /// Curve25519 key-agreement key, which cannot sign
+        X25519([u8; 32]),
+        /// Compressed NIST P-256 point
+        P256([u8; 33]),
+    }
+
+    impl PublicKey {
+        pub fn key_type(&self) -> KeyType {
+            match self {
+                PublicKey::Ed25519(_) => KeyType::Ed25519,
+                PublicKey::Sr25519(_) => KeyType::Sr25519,
+                PublicKey::Ecdsa(_) => KeyType::Ecdsa,
+                PublicKey::X25519(_) => KeyType::X25519,
+                PublicKey::P256(_) => KeyType::P256,
+            }
+        }
+
+        /// Raw key bytes
+        pub fn as_bytes(&self) -> &[u8] {
+            match self {
+                PublicKey::Ed25519(key) | PublicKey::Sr25519(key) | PublicKey::X25519(key) => key,
+                PublicKey::Ecdsa(key) | PublicKey::P256(key) => key,
+            }
+        }
+
+        /// Identifier of this key within a DID document
+        pub fn key_id(&self) -> [u8; 32] {
+            hashing::blake2_256(self.as_bytes())
+        }
+
+        /// Whether the key can produce signatures (and so authenticate or assert)
+        pub fn can_sign(&self) -> bool {
+            !matches!(self, PublicKey::X25519(_))
+        }
+
+        /// Structural validity of the encoded point
+        fn is_well_formed(&self) -> bool {
+            match self {
+                PublicKey::Ecdsa(key) | PublicKey::P256(key) => matches!(key[0], 0x02 | 0x03),
+                _ => true,
+            }
+        }
+    }
+
+    /// Proof that the caller holds the private part of a key being added
+    #[derive(
+        Clone, Encode, Decode, DecodeWithMemTracking, Eq, PartialEq, RuntimeDebug, TypeInfo,
+    )]
+    pub enum KeyProof {
+        Ed25519(ed25519::Signature),
+        Sr25519(sr25519::Signature),
+        Ecdsa(ecdsa::Signature),
+        /// `r || s` signature over the SHA-256 digest of the payload
+        P256([u8; 64]),
+        /// Key-agreement keys cannot sign, so carry no proof
+        None,
+    }
+
+    impl From<ed25519::Signature> for KeyProof {
+        fn from(signature: ed25519::Signature) -> Self {
+            KeyProof::Ed25519(signature)
+        }
+    }
+
+    impl From<sr25519::Signature> for KeyProof {
+        fn from(signature: sr25519::Signature) -> Self {
+            KeyProof::Sr25519(signature)
+        }
+    }
+
+    impl From<ecdsa::Signature> for KeyProof {
+        fn from(signature: ecdsa::Signature) -> Self {
+            KeyProof::Ecdsa(signature)
+        }
+    }
+
+    /// Upper bound on relationships per key (one of each kind)
+    pub type MaxRelationships = ConstU32<4>;
+
+    /// W3C verification relationships a key can be assigned to
+    #[derive(
+        Clone,
+        Copy,
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+    )]
+    pub enum VerificationRelationship {
+        /// Key may authenticate as the DID subject
+        Authentication,
+        /// Key may sign credentials and other assertions
+        AssertionMethod,
+        /// Key may be used for encryption / key agreement
+        KeyAgreement,
+        /// Key may delegate capabilities of the DID subject
+        CapabilityDelegation,
+    }
+
+    impl PublicKeyEntry {
+        /// Whether this key is assigned the given verification relationship
+        pub fn has_relationship(&self, relationship: VerificationRelationship) -> bool {
+            self.relationships.contains(&relationship)
+        }
     }
 
     #[derive(
@@ -104,6 +546,8 @@ pub mod pallet {
         Ed25519,
         Sr25519,
         Ecdsa,
+        X25519,
+        P256,
     }
 
     /// DID Document containing identity information
@@ -117,72 +561,498 @@ pub mod pallet {
         pub active: bool,
     }
 
+    /// Period during which a key was part of a DID document with the given
+    /// relationships, open-ended while it still is
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct KeyRecord<BlockNumber> {
+        pub key: PublicKeyEntry,
+        pub valid_from: BlockNumber,
+        pub valid_until: Option<BlockNumber>,
+    }
+
+    /// Bounded log of the keys a DID has had
+    ///
+    /// The oldest expired records are dropped when the log is full, after which
+    /// the key set is only known from `complete_from` onwards.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct KeyHistory<T: Config> {
+        pub records: BoundedVec<KeyRecord<BlockNumberFor<T>>, T::MaxKeyHistory>,
+        pub complete_from: BlockNumberFor<T>,
+    }
+
+    /// Key taken out of a DID document because it was compromised
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct CompromisedKey<BlockNumber> {
+        pub key: PublicKeyEntry,
+        /// First block from which signatures by the key are untrusted
+        pub compromised_since: BlockNumber,
+        pub reported_at: BlockNumber,
+    }
+
+    /// Upper bound on service ids and service type names
+    pub type MaxServiceIdLength = ConstU32<64>;
+
+    /// Service id within a DID document, e.g. `credential-status`
+    pub type ServiceId = BoundedVec<u8, MaxServiceIdLength>;
+
+    /// Service advertised by a DID, such as a credential-status list or DIDComm inbox
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct ServiceEndpoint<T: Config> {
+        pub id: ServiceId,
+        /// Service type name, e.g. `DIDCommMessaging` or `LinkedDomains`
+        pub service_type: BoundedVec<u8, MaxServiceIdLength>,
+        pub url: BoundedVec<u8, T::MaxUrlLength>,
+    }
+
+    /// Upper bound on accreditation sector names
+    pub type MaxSectorLength = ConstU32<64>;
+
+    /// Field of education, e.g. `medicine`
+    pub type SectorName = BoundedVec<u8, MaxSectorLength>;
+
+    /// Jurisdiction of an accreditation body
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+    )]
+    pub enum AccreditationScope {
+        /// ISO 3166-1 alpha-2 country code, e.g. `GB`
+        Country([u8; 2]),
+        /// Field of education, e.g. `medicine`
+        Sector(SectorName),
+    }
+
+    impl AccreditationScope {
+        fn is_valid(&self) -> bool {
+            match self {
+                Self::Country(code) => code.iter().all(u8::is_ascii_uppercase),
+                Self::Sector(name) => !name.is_empty(),
+            }
+        }
+
+        /// Whether an institution with `profile` falls within this scope
+        pub fn covers(&self, profile: &InstitutionProfile) -> bool {
+            match self {
+                Self::Country(code) => profile.country == Some(*code),
+                Self::Sector(name) => profile
+                    .sectors
+                    .iter()
+                    .any(|sector| sector.eq_ignore_ascii_case(name)),
+            }
+        }
+    }
+
+    /// Body trusted to verify institutions within its scope
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct AccreditationBody<T: Config> {
+        pub name: BoundedVec<u8, T::MaxDocumentSize>,
+        pub scope: AccreditationScope,
+        pub registered_at: BlockNumberFor<T>,
+    }
+
+    /// Upper bound on each free-text institution profile field
+    pub type MaxProfileFieldLength = ConstU32<128>;
+
+    /// Upper bound on accreditation references in an institution profile
+    pub type MaxAccreditationReferences = ConstU32<8>;
+
+    /// Upper bound on sectors in an institution profile
+    pub type MaxInstitutionSectors = ConstU32<8>;
+
+    /// Free-text institution profile field
+    pub type ProfileField = BoundedVec<u8, MaxProfileFieldLength>;
+
+    /// Structured public information about an institution
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+        Default,
+    )]
+    pub struct InstitutionProfile {
+        /// ISO 3166-1 alpha-2 country code, e.g. `GB`
+        pub country: Option<[u8; 2]>,
+        /// Identifier in the national registry of legal entities
+        pub legal_identifier: Option<ProfileField>,
+        /// Website domain without scheme, e.g. `ox.ac.uk`
+        pub website: Option<ProfileField>,
+        /// Blake2-256 hash of the logo image
+        pub logo_hash: Option<[u8; 32]>,
+        /// References to accreditations held, e.g. `QAA-2023-0042`
+        pub accreditations: BoundedVec<ProfileField, MaxAccreditationReferences>,
+        /// Fields of education offered, matched against the sector of
+        /// accreditation bodies
+        pub sectors: BoundedVec<SectorName, MaxInstitutionSectors>,
+    }
+
     /// Institution registration information
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
     #[scale_info(skip_type_params(T))]
     pub struct Institution<T: Config> {
         pub name: BoundedVec<u8, T::MaxDocumentSize>,
-        pub did: T::AccountId,
+        pub did: T::DidIdentifier,
         pub verified: bool,
         pub registered_at: BlockNumberFor<T>,
+        /// Accreditation body that verified the institution, `None` if verified
+        /// directly by `VerifyOrigin`
+        pub accreditor: Option<T::DidIdentifier>,
+        /// Block at which the verification lapses; kept after lapsing so the
+        /// lapse can be told apart from a revocation
+        pub verified_until: Option<BlockNumberFor<T>>,
     }
 
-    ///Storage of all DID documents, indexed by account ID
+    ///Storage of all DID documents, indexed by DID identifier
     #[pallet::storage]
     #[pallet::getter(fn did_documents)]
     pub type DidDocuments<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::AccountId, DidDocument<T>>;
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, DidDocument<T>>;
 
     /// Storage of institution registrations
     #[pallet::storage]
     #[pallet::getter(fn institutions)]
     pub type Institutions<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::AccountId, Institution<T>>;
-    //Errors
-    #[pallet::error]
-    pub enum Error<T> {
-        DidAlreadyExists,
-        DidNotFound,
-        NotAuthorized,
-        TooManyPublicKeys,
-        PublicKeyAlreadyExists,
-        PublicKeyNotFound,
-        InstitutionAlreadyRegistered,
-        InstitutionNotFound,
-        DidInactive,
-        InvalidInstitutionName,
-    }
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, Institution<T>>;
 
-    #[pallet::call]
-    impl<T: Config> Pallet<T> {
-        /// Create a new DID with an initial public key
-        ///
-        /// # Arguments
-        /// * `origin` - The account creating the DID
-        /// * `public_key` - The initial public key to associate
-        /// * `key_type` - The type of the public key
-        ///
-        /// # Errors
-        /// * `DidAlreadyExists` - If a DID already exists for this account
-        /// * `TooManyPublicKeys` - Should not occur on creation with single key
-        #[pallet::call_index(0)]
-        #[pallet::weight(T::WeightInfo::create_did())]
-        pub fn create_did(
-            origin: OriginFor<T>,
-            public_key: [u8; 32],
-            key_type: KeyType,
-        ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+    /// Service endpoints advertised by each DID
+    #[pallet::storage]
+    #[pallet::getter(fn service_endpoints)]
+    pub type ServiceEndpoints<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        BoundedVec<ServiceEndpoint<T>, T::MaxServiceEndpoints>,
+        ValueQuery,
+    >;
+
+    /// Deposits held for DID documents, indexed by DID subject
+    #[pallet::storage]
+    #[pallet::getter(fn did_deposits)]
+    pub type DidDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, Deposit<T::AccountId, BalanceOf<T>>>;
 
-            ensure!(
-                !DidDocuments::<T>::contains_key(&who),
+    /// Deposits held for institution registrations
+    #[pallet::storage]
+    #[pallet::getter(fn institution_deposits)]
+    pub type InstitutionDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, Deposit<T::AccountId, BalanceOf<T>>>;
+
+    /// Recovery guardians registered by each DID
+    #[pallet::storage]
+    #[pallet::getter(fn recovery_configs)]
+    pub type RecoveryConfigs<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, RecoveryConfig<T>>;
+
+    /// Recoveries in progress, indexed by the DID being recovered
+    #[pallet::storage]
+    #[pallet::getter(fn active_recoveries)]
+    pub type ActiveRecoveries<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, ActiveRecovery<T>>;
+
+    /// Registered accreditation bodies
+    #[pallet::storage]
+    #[pallet::getter(fn accreditation_bodies)]
+    pub type AccreditationBodies<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, AccreditationBody<T>>;
+
+    /// Institutions accredited by each accreditation body, including lapsed ones
+    #[pallet::storage]
+    #[pallet::getter(fn accredited_institutions)]
+    pub type AccreditedInstitutions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        (),
+        OptionQuery,
+    >;
+
+    /// Structured profiles of registered institutions
+    #[pallet::storage]
+    #[pallet::getter(fn institution_profiles)]
+    pub type InstitutionProfiles<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, InstitutionProfile>;
+
+    /// Institution verifications lapsing at each block
+    #[pallet::storage]
+    #[pallet::getter(fn verification_expiries)]
+    pub type VerificationExpiries<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        (),
+        OptionQuery,
+    >;
+
+    /// First block of the verification expiry queue not yet fully processed
+    #[pallet::storage]
+    #[pallet::getter(fn verification_expiry_cursor)]
+    pub type VerificationExpiryCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Verified institutions indexed by the hash of their normalized name
+    #[pallet::storage]
+    #[pallet::getter(fn institution_names)]
+    pub type InstitutionNames<T: Config> =
+        StorageMap<_, Blake2_128Concat, [u8; 32], T::DidIdentifier>;
+
+    /// Reverse index from key id to the DID holding that key
+    ///
+    /// Only signing keys, whose possession is proven when they are added, are
+    /// indexed and unique across DIDs. Anyone can add a copy of a published
+    /// X25519 key-agreement key, so those would let them block its owner.
+    #[pallet::storage]
+    #[pallet::getter(fn did_by_key_id)]
+    pub type DidByKeyId<T: Config> = StorageMap<_, Blake2_128Concat, [u8; 32], T::DidIdentifier>;
+
+    /// Block at which each closed or deregistered institution stopped issuing
+    #[pallet::storage]
+    #[pallet::getter(fn closed_institutions)]
+    pub type ClosedInstitutions<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, BlockNumberFor<T>>;
+
+    /// Institution that took over each merged institution's credentials
+    #[pallet::storage]
+    #[pallet::getter(fn successors)]
+    pub type Successors<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, T::DidIdentifier>;
+
+    /// Merged institutions each institution directly succeeds, the reverse of
+    /// `Successors`
+    #[pallet::storage]
+    #[pallet::getter(fn predecessors)]
+    pub type Predecessors<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        (),
+        OptionQuery,
+    >;
+
+    /// Identifiers of deleted DIDs, which can never be created again
+    #[pallet::storage]
+    #[pallet::getter(fn tombstones)]
+    pub type Tombstones<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, BlockNumberFor<T>>;
+
+    /// Number of keys added and accounts linked to each DID so far, used to make
+    /// key proofs and link consents single-use
+    #[pallet::storage]
+    #[pallet::getter(fn key_nonces)]
+    pub type KeyNonces<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, u64, ValueQuery>;
+
+    /// Nonce the next call signed with a DID key must carry, per DID
+    #[pallet::storage]
+    #[pallet::getter(fn did_call_nonces)]
+    pub type DidCallNonces<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, u64, ValueQuery>;
+
+    /// History of the keys of each DID, to check which were valid at past blocks
+    #[pallet::storage]
+    #[pallet::getter(fn key_histories)]
+    pub type KeyHistories<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, KeyHistory<T>>;
+
+    /// Keys reported compromised, kept so that signatures made before the
+    /// compromise can still be attributed and trusted
+    #[pallet::storage]
+    #[pallet::getter(fn compromised_keys)]
+    pub type CompromisedKeys<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        BoundedVec<CompromisedKey<BlockNumberFor<T>>, T::MaxCompromisedKeys>,
+        ValueQuery,
+    >;
+
+    /// Controller transfers awaiting acceptance, indexed by DID subject
+    #[pallet::storage]
+    #[pallet::getter(fn pending_controllers)]
+    pub type PendingControllers<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, T::AccountId>;
+
+    /// Accounts able to act for each DID
+    #[pallet::storage]
+    #[pallet::getter(fn linked_accounts)]
+    pub type LinkedAccounts<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::DidIdentifier,
+        BoundedVec<T::AccountId, T::MaxLinkedAccounts>,
+        ValueQuery,
+    >;
+
+    /// DID each linked account acts for
+    #[pallet::storage]
+    #[pallet::getter(fn account_dids)]
+    pub type AccountDids<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, T::DidIdentifier>;
+    //Errors
+    #[pallet::error]
+    pub enum Error<T> {
+        DidAlreadyExists,
+        DidNotFound,
+        NotAuthorized,
+        TooManyPublicKeys,
+        PublicKeyAlreadyExists,
+        PublicKeyNotFound,
+        InstitutionAlreadyRegistered,
+        InstitutionNotFound,
+        DidInactive,
+        InvalidInstitutionName,
+        NoPendingControllerTransfer,
+        AlreadyController,
+        RelationshipAlreadyAssigned,
+        RelationshipNotAssigned,
+        LastAuthenticationKey,
+        InvalidKeyProof,
+        InvalidPublicKey,
+        RelationshipNotSupported,
+        TooManyServiceEndpoints,
+        ServiceEndpointAlreadyExists,
+        ServiceEndpointNotFound,
+        InvalidServiceEndpoint,
+        DidTombstoned,
+        InstitutionHasActiveCredentials,
+        InvalidRecoveryConfig,
+        TooManyGuardians,
+        NoRecoveryConfig,
+        NotGuardian,
+        AlreadyVouched,
+        RecoveryTargetMismatch,
+        NoActiveRecovery,
+        RecoveryNotApproved,
+        RecoveryDelayNotElapsed,
+        KeyAlreadyInUse,
+        AccreditationBodyAlreadyRegistered,
+        AccreditationBodyNotFound,
+        InvalidAccreditationScope,
+        NotAccreditor,
+        InstitutionNotVerified,
+        InvalidCountryCode,
+        InvalidLegalIdentifier,
+        InvalidWebsiteDomain,
+        InvalidAccreditationReference,
+        InstitutionNameTaken,
+        InstitutionClosed,
+        InvalidSuccessor,
+        AccountAlreadyLinked,
+        AccountNotLinked,
+        TooManyLinkedAccounts,
+        InvalidDidCallNonce,
+        InvalidDidCallSignature,
+        InvalidCompromiseBlock,
+        TooManyCompromisedKeys,
+        OutOfAccreditationScope,
+        InvalidSector,
+        DidStillReferenced,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            let limit = T::WeightInfo::lapse_institution_verification()
+                .saturating_mul(T::MaxLapsesPerBlock::get().into());
+            Self::process_verification_expiries(now, limit)
+        }
+
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::process_verification_expiries(now, remaining_weight)
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::MaxKeyHistory::get() > T::MaxPublicKeys::get(),
+                "MaxKeyHistory must exceed MaxPublicKeys to keep every current key"
+            );
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Create a new DID with an initial public key
+        ///
+        /// The identifier is derived from the creating account through
+        /// `DidIdentifierOf`, and the account becomes the DID's controller and its
+        /// first linked account.
+        ///
+        /// # Arguments
+        /// * `origin` - The account creating the DID
+        /// * `public_key` - The initial public key to associate
+        /// * `proof` - Signature by `public_key` over `key_possession_payload`
+        ///
+        /// # Errors
+        /// * `DidAlreadyExists` - If the account is linked to a DID, or the
+        ///   identifier derived from it is in use
+        /// * `DidTombstoned` - If the DID this account would create was deleted
+        /// * `RelationshipNotSupported` - If `public_key` cannot sign, as the
+        ///   initial key must authenticate the DID
+        /// * `InvalidKeyProof` - If `proof` was not made with `public_key`
+        /// * `KeyAlreadyInUse` - If `public_key` belongs to another DID
+        /// * `TooManyPublicKeys` - Should not occur on creation with single key
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::create_did())]
+        pub fn create_did(
+            origin: OriginFor<T>,
+            public_key: PublicKey,
+            proof: KeyProof,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                !AccountDids::<T>::contains_key(&who),
+                Error::<T>::DidAlreadyExists
+            );
+
+            let did = T::DidIdentifierOf::convert(who.clone());
+            ensure!(
+                !DidDocuments::<T>::contains_key(&did),
                 Error::<T>::DidAlreadyExists
             );
+            ensure!(
+                !Tombstones::<T>::contains_key(&did),
+                Error::<T>::DidTombstoned
+            );
+
+            ensure!(public_key.can_sign(), Error::<T>::RelationshipNotSupported);
+            Self::verify_key_possession(&did, &public_key, &proof)?;
+
+            let key_id = public_key.key_id();
+            ensure!(
+                !DidByKeyId::<T>::contains_key(key_id),
+                Error::<T>::KeyAlreadyInUse
+            );
+
+            // The initial key authenticates the DID and signs its assertions
+            let relationships = BoundedVec::truncate_from(vec![
+                VerificationRelationship::Authentication,
+                VerificationRelationship::AssertionMethod,
+            ]);
 
             let key_entry = PublicKeyEntry {
-                key_id: hashing::blake2_256(&public_key),
-                key_type,
+                key_id,
                 public_key,
+                relationships,
             };
 
             let mut public_keys = BoundedVec::new();
@@ -198,93 +1068,144 @@ pub mod pallet {
                 active: true,
             };
 
-            DidDocuments::<T>::insert(&who, did_doc);
-            Self::deposit_event(Event::DidCreated { owner: who });
+            let now = frame_system::Pallet::<T>::block_number();
+            KeyHistories::<T>::insert(
+                &did,
+                KeyHistory {
+                    records: BoundedVec::new(),
+                    complete_from: now,
+                },
+            );
+            Self::record_key(&did, key_id, Some(&did_doc.public_keys[0]))?;
+
+            DidDocuments::<T>::insert(&did, did_doc);
+            DidByKeyId::<T>::insert(key_id, &did);
+            KeyNonces::<T>::mutate(&did, |nonce| nonce.saturating_inc());
+            Self::link(&did, &who)?;
+            Self::update_did_deposit(&did)?;
+
+            Self::deposit_event(Event::DidCreated { owner: did });
 
             Ok(())
         }
 
+        /// Add a public key to a DID (controller only)
+        ///
+        /// `proof` must be a signature by `public_key` over `key_possession_payload(did)`,
+        /// or `KeyProof::None` for X25519 key-agreement keys, which cannot sign.
+        /// Signing keys must not belong to another DID; key-agreement keys are
+        /// not checked, as their possession cannot be proven.
         #[pallet::call_index(1)]
         #[pallet::weight(T::WeightInfo::add_public_key())]
         pub fn add_public_key(
             origin: OriginFor<T>,
-            public_key: [u8; 32],
-            key_type: KeyType,
+            did: T::DidIdentifier,
+            public_key: PublicKey,
+            proof: KeyProof,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            DidDocuments::<T>::try_mutate(&who, |did_doc_opt| -> DispatchResult {
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
                 let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
 
                 ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
                 ensure!(did_doc.active, Error::<T>::DidInactive);
 
-                let key_id = hashing::blake2_256(&public_key);
+                let key_id = public_key.key_id();
 
                 ensure!(
                     !did_doc.public_keys.iter().any(|k| k.key_id == key_id),
                     Error::<T>::PublicKeyAlreadyExists
                 );
+                let indexed = public_key.can_sign();
+                ensure!(
+                    !indexed || !DidByKeyId::<T>::contains_key(key_id),
+                    Error::<T>::KeyAlreadyInUse
+                );
+
+                Self::verify_key_possession(&did, &public_key, &proof)?;
 
                 let key_entry = PublicKeyEntry {
                     key_id,
-                    key_type,
                     public_key,
+                    relationships: BoundedVec::new(),
                 };
 
+                Self::record_key(&did, key_id, Some(&key_entry))?;
                 did_doc
                     .public_keys
                     .try_push(key_entry)
                     .map_err(|_| Error::<T>::TooManyPublicKeys)?;
 
                 did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+                if indexed {
+                    DidByKeyId::<T>::insert(key_id, &did);
+                }
+                KeyNonces::<T>::mutate(&did, |nonce| nonce.saturating_inc());
 
                 Self::deposit_event(Event::PublicKeyAdded {
-                    owner: who.clone(),
+                    owner: did.clone(),
                     key_id,
                 });
 
                 Ok(())
-            })
+            })?;
+
+            Self::update_did_deposit(&did)
         }
 
-        //Remove a public key from a DID
+        /// Remove a public key from a DID (controller only)
         #[pallet::call_index(2)]
         #[pallet::weight(T::WeightInfo::remove_public_key())]
-        pub fn remove_public_key(origin: OriginFor<T>, key_id: [u8; 32]) -> DispatchResult {
+        pub fn remove_public_key(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            key_id: [u8; 32],
+        ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            DidDocuments::<T>::try_mutate(&who, |did_doc_opt| -> DispatchResult {
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
                 let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
 
                 ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
 
-                let initial_len = did_doc.public_keys.len();
-                did_doc.public_keys.retain(|k| k.key_id != key_id);
+                let index = did_doc
+                    .public_keys
+                    .iter()
+                    .position(|k| k.key_id == key_id)
+                    .ok_or(Error::<T>::PublicKeyNotFound)?;
 
                 ensure!(
-                    did_doc.public_keys.len() < initial_len,
-                    Error::<T>::PublicKeyNotFound
+                    !Self::is_last_authentication_key(&did_doc.public_keys, &key_id),
+                    Error::<T>::LastAuthenticationKey
                 );
 
+                let key = did_doc.public_keys.remove(index);
+                if key.public_key.can_sign() {
+                    DidByKeyId::<T>::remove(key_id);
+                }
+                Self::record_key(&did, key_id, None)?;
+
                 did_doc.updated_at = frame_system::Pallet::<T>::block_number();
 
                 Self::deposit_event(Event::PublicKeyRemoved {
-                    owner: who.clone(),
+                    owner: did.clone(),
                     key_id,
                 });
 
                 Ok(())
-            })
+            })?;
+
+            Self::update_did_deposit(&did)
         }
 
-        /// Deactivate a DID
+        /// Deactivate a DID (controller only)
         #[pallet::call_index(3)]
         #[pallet::weight(T::WeightInfo::deactivate_did())]
-        pub fn deactivate_did(origin: OriginFor<T>) -> DispatchResult {
+        pub fn deactivate_did(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            DidDocuments::<T>::try_mutate(&who, |did_doc_opt| -> DispatchResult {
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
                 let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
 
                 ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
@@ -292,19 +1213,19 @@ pub mod pallet {
                 did_doc.active = false;
                 did_doc.updated_at = frame_system::Pallet::<T>::block_number();
 
-                Self::deposit_event(Event::DidDeactivated { owner: who.clone() });
+                Self::deposit_event(Event::DidDeactivated { owner: did.clone() });
 
                 Ok(())
             })
         }
 
-        /// Reactivate a deactivated DID
+        /// Reactivate a deactivated DID (controller only)
         #[pallet::call_index(4)]
         #[pallet::weight(T::WeightInfo::reactivate_did())]
-        pub fn reactivate_did(origin: OriginFor<T>) -> DispatchResult {
+        pub fn reactivate_did(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            DidDocuments::<T>::try_mutate(&who, |did_doc_opt| -> DispatchResult {
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
                 let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
 
                 ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
@@ -312,86 +1233,152 @@ pub mod pallet {
                 did_doc.active = true;
                 did_doc.updated_at = frame_system::Pallet::<T>::block_number();
 
-                Self::deposit_event(Event::DidUpdated { owner: who.clone() });
+                Self::deposit_event(Event::DidUpdated { owner: did.clone() });
 
                 Ok(())
             })
         }
 
-        /// Register as an academic institution
+        /// Register the caller's DID as an academic institution
         #[pallet::call_index(5)]
         #[pallet::weight(T::WeightInfo::register_institution())]
         pub fn register_institution(
             origin: OriginFor<T>,
             name: BoundedVec<u8, T::MaxDocumentSize>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+            let did = Self::acting_did(origin)?.ok_or(Error::<T>::DidNotFound)?;
 
             ensure!(
-                !Institutions::<T>::contains_key(&who),
+                !Institutions::<T>::contains_key(&did),
                 Error::<T>::InstitutionAlreadyRegistered
             );
 
+            ensure!(!name.is_empty(), Error::<T>::InvalidInstitutionName);
             ensure!(
-                DidDocuments::<T>::contains_key(&who),
-                Error::<T>::DidNotFound
+                !InstitutionNames::<T>::contains_key(Self::institution_name_hash(&name)),
+                Error::<T>::InstitutionNameTaken
             );
 
-            ensure!(!name.is_empty(), Error::<T>::InvalidInstitutionName);
-
             let institution = Institution {
                 name: name.clone(),
-                did: who.clone(),
+                did: did.clone(),
                 verified: false,
                 registered_at: frame_system::Pallet::<T>::block_number(),
+                accreditor: None,
+                verified_until: None,
             };
 
-            Institutions::<T>::insert(&who, institution);
+            Institutions::<T>::insert(&did, institution);
+            Self::update_institution_deposit(&did)?;
 
-            Self::deposit_event(Event::InstitutionRegistered { did: who, name });
+            Self::deposit_event(Event::InstitutionRegistered { did, name });
 
             Ok(())
         }
 
-        /// Verify an institution (governance/root only)
+        /// Verify an institution (`VerifyOrigin` or an accreditation body)
+        ///
+        /// The verification lapses after `VerificationPeriod` unless renewed, and
+        /// the verifying accreditation body is recorded on the institution.
+        /// Governance may override any verification, while an accreditation body
+        /// cannot take over an institution verified by someone else.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If `institution_did` is not registered
+        /// * `NotAccreditor` - If another party already verified the institution
+        /// * `OutOfAccreditationScope` - If the institution's profile is outside
+        ///   the accreditation body's country or sector
+        /// * `InstitutionNameTaken` - If another verified institution has the same
+        ///   normalized name
+        /// * `InstitutionClosed` - If the institution has closed
         #[pallet::call_index(6)]
         #[pallet::weight(T::WeightInfo::verify_institution())]
         pub fn verify_institution(
             origin: OriginFor<T>,
-            institution_did: T::AccountId,
+            institution_did: T::DidIdentifier,
         ) -> DispatchResult {
-            ensure_root(origin)?;
+            let accreditor = Self::ensure_accreditor::<T::VerifyOrigin>(origin)?;
+
+            ensure!(
+                !ClosedInstitutions::<T>::contains_key(&institution_did),
+                Error::<T>::InstitutionClosed
+            );
 
             Institutions::<T>::try_mutate(&institution_did, |institution_opt| -> DispatchResult {
                 let institution = institution_opt
                     .as_mut()
                     .ok_or(Error::<T>::InstitutionNotFound)?;
 
+                if accreditor.is_some() && institution.verified {
+                    ensure!(
+                        institution.accreditor == accreditor,
+                        Error::<T>::NotAccreditor
+                    );
+                }
+                if let Some(body) = &accreditor {
+                    Self::ensure_in_scope(body, &institution_did)?;
+                }
+
+                Self::claim_institution_name(&institution_did, &institution.name)?;
+
+                if let Some(previous) = institution.accreditor.take() {
+                    AccreditedInstitutions::<T>::remove(&previous, &institution_did);
+                }
+                if let Some(body) = &accreditor {
+                    AccreditedInstitutions::<T>::insert(body, &institution_did, ());
+                }
+
                 institution.verified = true;
+                institution.accreditor = accreditor.clone();
+                Self::schedule_verification_lapse(&institution_did, institution);
 
                 Self::deposit_event(Event::InstitutionVerified {
                     did: institution_did.clone(),
+                    accreditor,
                 });
 
                 Ok(())
             })
         }
 
-        /// Revoke institution verification (governance/root only)
+        /// Revoke institution verification (`RevokeOrigin` or the verifying
+        /// accreditation body)
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If `institution_did` is not registered
+        /// * `NotAccreditor` - If an accreditation body revokes an institution it
+        ///   did not verify
+        /// * `OutOfAccreditationScope` - If the institution's profile is outside
+        ///   the accreditation body's country or sector
         #[pallet::call_index(7)]
         #[pallet::weight(T::WeightInfo::revoke_institution())]
         pub fn revoke_institution(
             origin: OriginFor<T>,
-            institution_did: T::AccountId,
+            institution_did: T::DidIdentifier,
         ) -> DispatchResult {
-            ensure_root(origin)?;
+            let accreditor = Self::ensure_accreditor::<T::RevokeOrigin>(origin)?;
 
             Institutions::<T>::try_mutate(&institution_did, |institution_opt| -> DispatchResult {
                 let institution = institution_opt
                     .as_mut()
                     .ok_or(Error::<T>::InstitutionNotFound)?;
 
+                if let Some(body) = &accreditor {
+                    ensure!(
+                        institution.accreditor == accreditor,
+                        Error::<T>::NotAccreditor
+                    );
+                    Self::ensure_in_scope(body, &institution_did)?;
+                }
+
+                if let Some(previous) = institution.accreditor.take() {
+                    AccreditedInstitutions::<T>::remove(&previous, &institution_did);
+                }
                 institution.verified = false;
+                if let Some(until) = institution.verified_until.take() {
+                    VerificationExpiries::<T>::remove(until, &institution_did);
+                }
+                Self::release_institution_name(&institution_did, &institution.name);
 
                 Self::deposit_event(Event::InstitutionRevoked {
                     did: institution_did.clone(),
@@ -400,5 +1387,1836 @@ pub mod pallet {
                 Ok(())
             })
         }
+
+        /// Propose a new controller for a DID
+        ///
+        /// The transfer only takes effect once the proposed controller calls
+        /// `accept_controller`. Proposing again replaces any pending transfer.
+        ///
+        /// # Arguments
+        /// * `origin` - The current controller of the DID
+        /// * `did` - The DID subject whose controller changes
+        /// * `new_controller` - The account that will manage the DID
+        ///
+        /// # Errors
+        /// * `DidNotFound` - If no DID exists for `did`
+        /// * `NotAuthorized` - If the caller is not the current controller
+        /// * `AlreadyController` - If `new_controller` already controls the DID
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::transfer_controller())]
+        pub fn transfer_controller(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            new_controller: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+            ensure!(
+                did_doc.controller != new_controller,
+                Error::<T>::AlreadyController
+            );
+
+            PendingControllers::<T>::insert(&did, &new_controller);
+
+            Self::deposit_event(Event::ControllerTransferInitiated {
+                did,
+                new_controller,
+            });
+
+            Ok(())
+        }
+
+        /// Accept a pending controller transfer (proposed controller only)
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::accept_controller()
+            .saturating_add(T::IssuedCredentials::transfer_deposits_weight()))]
+        pub fn accept_controller(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let pending = PendingControllers::<T>::get(&did)
+                .ok_or(Error::<T>::NoPendingControllerTransfer)?;
+            ensure!(pending == who, Error::<T>::NotAuthorized);
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                let old_controller = core::mem::replace(&mut did_doc.controller, who.clone());
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+
+                PendingControllers::<T>::remove(&did);
+
+                Self::deposit_event(Event::ControllerTransferred {
+                    did: did.clone(),
+                    old_controller,
+                    new_controller: who.clone(),
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_controller_deposits(&did)
+        }
+
+        /// Cancel a pending controller transfer (current controller only)
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::cancel_controller_transfer())]
+        pub fn cancel_controller_transfer(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            ensure!(
+                PendingControllers::<T>::take(&did).is_some(),
+                Error::<T>::NoPendingControllerTransfer
+            );
+
+            Self::deposit_event(Event::ControllerTransferCancelled { did });
+
+            Ok(())
+        }
+
+        /// Assign a verification relationship to one of the DID's keys (controller only)
+        ///
+        /// # Errors
+        /// * `PublicKeyNotFound` - If the key is not part of the DID document
+        /// * `RelationshipAlreadyAssigned` - If the key already has this relationship
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::add_verification_relationship())]
+        pub fn add_verification_relationship(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            key_id: [u8; 32],
+            relationship: VerificationRelationship,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+                ensure!(did_doc.active, Error::<T>::DidInactive);
+
+                let key = did_doc
+                    .public_keys
+                    .iter_mut()
+                    .find(|k| k.key_id == key_id)
+                    .ok_or(Error::<T>::PublicKeyNotFound)?;
+
+                ensure!(
+                    !key.has_relationship(relationship),
+                    Error::<T>::RelationshipAlreadyAssigned
+                );
+
+                // Only the key-agreement relationship works without signing
+                ensure!(
+                    relationship == VerificationRelationship::KeyAgreement
+                        || key.public_key.can_sign(),
+                    Error::<T>::RelationshipNotSupported
+                );
+
+                key.relationships
+                    .try_push(relationship)
+                    .map_err(|_| Error::<T>::RelationshipAlreadyAssigned)?;
+                Self::record_key(&did, key_id, Some(&*key))?;
+
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+
+                Self::deposit_event(Event::VerificationRelationshipAdded {
+                    owner: did.clone(),
+                    key_id,
+                    relationship,
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Unassign a verification relationship from one of the DID's keys (controller only)
+        ///
+        /// # Errors
+        /// * `PublicKeyNotFound` - If the key is not part of the DID document
+        /// * `RelationshipNotAssigned` - If the key does not have this relationship
+        /// * `LastAuthenticationKey` - If this would leave the DID without an authentication key
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::remove_verification_relationship())]
+        pub fn remove_verification_relationship(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            key_id: [u8; 32],
+            relationship: VerificationRelationship,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+                if relationship == VerificationRelationship::Authentication {
+                    ensure!(
+                        !Self::is_last_authentication_key(&did_doc.public_keys, &key_id),
+                        Error::<T>::LastAuthenticationKey
+                    );
+                }
+
+                let key = did_doc
+                    .public_keys
+                    .iter_mut()
+                    .find(|k| k.key_id == key_id)
+                    .ok_or(Error::<T>::PublicKeyNotFound)?;
+
+                ensure!(
+                    key.has_relationship(relationship),
+                    Error::<T>::RelationshipNotAssigned
+                );
+
+                key.relationships.retain(|r| *r != relationship);
+                Self::record_key(&did, key_id, Some(&*key))?;
+
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+
+                Self::deposit_event(Event::VerificationRelationshipRemoved {
+                    owner: did.clone(),
+                    key_id,
+                    relationship,
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Advertise a service endpoint on a DID (controller only)
+        ///
+        /// # Errors
+        /// * `InvalidServiceEndpoint` - If the id, type or URL is empty
+        /// * `ServiceEndpointAlreadyExists` - If the DID already has a service with this id
+        /// * `TooManyServiceEndpoints` - If the DID already has `MaxServiceEndpoints` services
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::add_service_endpoint())]
+        pub fn add_service_endpoint(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            service_id: ServiceId,
+            service_type: BoundedVec<u8, MaxServiceIdLength>,
+            url: BoundedVec<u8, T::MaxUrlLength>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                !service_id.is_empty() && !service_type.is_empty() && !url.is_empty(),
+                Error::<T>::InvalidServiceEndpoint
+            );
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+                ensure!(did_doc.active, Error::<T>::DidInactive);
+
+                ServiceEndpoints::<T>::try_mutate(&did, |services| -> DispatchResult {
+                    ensure!(
+                        !services.iter().any(|s| s.id == service_id),
+                        Error::<T>::ServiceEndpointAlreadyExists
+                    );
+
+                    services
+                        .try_push(ServiceEndpoint {
+                            id: service_id.clone(),
+                            service_type,
+                            url,
+                        })
+                        .map_err(|_| Error::<T>::TooManyServiceEndpoints)?;
+
+                    Ok(())
+                })?;
+
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+
+                Self::deposit_event(Event::ServiceEndpointAdded {
+                    owner: did.clone(),
+                    service_id,
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Remove a service endpoint from a DID (controller only)
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::remove_service_endpoint())]
+        pub fn remove_service_endpoint(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            service_id: ServiceId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+                ServiceEndpoints::<T>::try_mutate_exists(&did, |services_opt| -> DispatchResult {
+                    let services = services_opt
+                        .as_mut()
+                        .ok_or(Error::<T>::ServiceEndpointNotFound)?;
+
+                    let index = services
+                        .iter()
+                        .position(|s| s.id == service_id)
+                        .ok_or(Error::<T>::ServiceEndpointNotFound)?;
+
+                    services.remove(index);
+
+                    if services.is_empty() {
+                        *services_opt = None;
+                    }
+
+                    Ok(())
+                })?;
+
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+
+                Self::deposit_event(Event::ServiceEndpointRemoved {
+                    owner: did.clone(),
+                    service_id,
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Permanently delete a DID (controller only)
+        ///
+        /// Removes the document, its services, linked accounts, any institution
+        /// registration and profile, and the revoked or expired credentials and
+        /// delegates it issued, releases their deposits and tombstones the
+        /// identifier so the DID can never be created again. Compromised keys
+        /// stay on record and indexed to the deleted DID, so they cannot be
+        /// added to another one.
+        ///
+        /// Credentials issued to the DID stay with their issuers, who paid for
+        /// them and can still revoke and remove them, and its reputation is kept
+        /// as history. Neither can grow once the DID is gone, as holders and
+        /// endorsees must have a DID document.
+        ///
+        /// # Errors
+        /// * `DidNotFound` - If no DID exists for `did`
+        /// * `NotAuthorized` - If the caller is not the controller
+        /// * `InstitutionHasActiveCredentials` - If `did` is an institution whose
+        ///   issued credentials are still active
+        /// * `DidStillReferenced` - If `did` is an accreditation body, is recorded
+        ///   as the accreditor of an institution, or succeeds a merged institution
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::delete_did()
+            .saturating_add(T::IssuedCredentials::release_deposits_weight()))]
+        pub fn delete_did(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            // Other records would be left pointing at a deleted DID
+            ensure!(
+                !AccreditationBodies::<T>::contains_key(&did)
+                    && AccreditedInstitutions::<T>::iter_key_prefix(&did)
+                        .next()
+                        .is_none()
+                    && Predecessors::<T>::iter_key_prefix(&did).next().is_none(),
+                Error::<T>::DidStillReferenced
+            );
+
+            if let Some(institution) = Institutions::<T>::take(&did) {
+                ensure!(
+                    !T::IssuedCredentials::has_active_credentials(&did),
+                    Error::<T>::InstitutionHasActiveCredentials
+                );
+
+                if let Some(accreditor) = institution.accreditor {
+                    AccreditedInstitutions::<T>::remove(&accreditor, &did);
+                }
+                if let Some(until) = institution.verified_until {
+                    VerificationExpiries::<T>::remove(until, &did);
+                }
+                InstitutionProfiles::<T>::remove(&did);
+                ClosedInstitutions::<T>::remove(&did);
+                if let Some(successor) = Successors::<T>::take(&did) {
+                    Predecessors::<T>::remove(successor, &did);
+                }
+                Self::release_institution_name(&did, &institution.name);
+                T::IssuedCredentials::release_deposits(&did)?;
+
+                if let Some(deposit) = InstitutionDeposits::<T>::take(&did) {
+                    Self::release_deposit(&HoldReason::InstitutionDeposit.into(), deposit)?;
+                }
+            }
+
+            if let Some(deposit) = DidDeposits::<T>::take(&did) {
+                Self::release_deposit(&HoldReason::DidDeposit.into(), deposit)?;
+            }
+
+            for key in did_doc
+                .public_keys
+                .iter()
+                .filter(|k| k.public_key.can_sign())
+            {
+                DidByKeyId::<T>::remove(key.key_id);
+            }
+
+            for account in LinkedAccounts::<T>::take(&did) {
+                AccountDids::<T>::remove(account);
+            }
+
+            DidDocuments::<T>::remove(&did);
+            ServiceEndpoints::<T>::remove(&did);
+            PendingControllers::<T>::remove(&did);
+            KeyNonces::<T>::remove(&did);
+            KeyHistories::<T>::remove(&did);
+            DidCallNonces::<T>::remove(&did);
+            RecoveryConfigs::<T>::remove(&did);
+            ActiveRecoveries::<T>::remove(&did);
+            Tombstones::<T>::insert(&did, frame_system::Pallet::<T>::block_number());
+
+            Self::deposit_event(Event::DidDeleted { owner: did });
+
+            Ok(())
+        }
+
+        /// Register guardians who can together recover the DID (controller only)
+        ///
+        /// Replaces any existing configuration and cancels a recovery in progress.
+        ///
+        /// # Errors
+        /// * `InvalidRecoveryConfig` - If guardians repeat, or `threshold` is zero or
+        ///   exceeds the number of guardians
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::set_recovery_config())]
+        pub fn set_recovery_config(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            guardians: BoundedVec<T::AccountId, T::MaxGuardians>,
+            threshold: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            ensure!(
+                threshold > 0 && threshold as usize <= guardians.len(),
+                Error::<T>::InvalidRecoveryConfig
+            );
+            ensure!(
+                guardians
+                    .iter()
+                    .enumerate()
+                    .all(|(i, g)| !guardians[..i].contains(g)),
+                Error::<T>::InvalidRecoveryConfig
+            );
+
+            RecoveryConfigs::<T>::insert(
+                &did,
+                RecoveryConfig {
+                    guardians,
+                    threshold,
+                },
+            );
+            ActiveRecoveries::<T>::remove(&did);
+
+            Self::deposit_event(Event::RecoveryConfigured {
+                did: did.clone(),
+                threshold,
+            });
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Remove the DID's recovery guardians (controller only)
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::remove_recovery_config())]
+        pub fn remove_recovery_config(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            ensure!(
+                RecoveryConfigs::<T>::take(&did).is_some(),
+                Error::<T>::NoRecoveryConfig
+            );
+            ActiveRecoveries::<T>::remove(&did);
+
+            Self::deposit_event(Event::RecoveryConfigRemoved { did: did.clone() });
+
+            Self::update_did_deposit(&did)
+        }
+
+        /// Vouch, as a guardian, for moving a DID to `new_account`
+        ///
+        /// Vouches are counted per account, and a guardian vouching again for
+        /// another account moves their vouch, so a single guardian cannot hold
+        /// the recovery to an account of their choosing. Once `threshold`
+        /// guardians vouch for the same account, it can claim the DID after
+        /// `RecoveryDelay` blocks.
+        ///
+        /// # Errors
+        /// * `NoRecoveryConfig` - If the DID has no guardians
+        /// * `NotGuardian` - If the caller is not one of the DID's guardians
+        /// * `RecoveryTargetMismatch` - If a recovery to another account was
+        ///   already approved
+        /// * `AlreadyVouched` - If the caller already vouched for `new_account`
+        #[pallet::call_index(18)]
+        #[pallet::weight(T::WeightInfo::vouch_recovery())]
+        pub fn vouch_recovery(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            new_account: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let config = RecoveryConfigs::<T>::get(&did).ok_or(Error::<T>::NoRecoveryConfig)?;
+            ensure!(config.guardians.contains(&who), Error::<T>::NotGuardian);
+
+            ActiveRecoveries::<T>::try_mutate(&did, |recovery_opt| -> DispatchResult {
+                let recovery = recovery_opt.get_or_insert_with(|| ActiveRecovery {
+                    vouches: BoundedVec::new(),
+                    approved: None,
+                });
+
+                if let Some((approved, _)) = &recovery.approved {
+                    ensure!(*approved == new_account, Error::<T>::RecoveryTargetMismatch);
+                }
+
+                match recovery
+                    .vouches
+                    .iter_mut()
+                    .find(|(guardian, _)| *guardian == who)
+                {
+                    Some((_, target)) => {
+                        ensure!(*target != new_account, Error::<T>::AlreadyVouched);
+                        *target = new_account.clone();
+                    }
+                    None => recovery
+                        .vouches
+                        .try_push((who.clone(), new_account.clone()))
+                        .map_err(|_| Error::<T>::TooManyGuardians)?,
+                }
+
+                let vouches = recovery
+                    .vouches
+                    .iter()
+                    .filter(|(_, target)| *target == new_account)
+                    .count() as u32;
+                if recovery.approved.is_none() && vouches >= config.threshold {
+                    recovery.approved = Some((
+                        new_account.clone(),
+                        frame_system::Pallet::<T>::block_number(),
+                    ));
+                }
+
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::RecoveryVouched {
+                did,
+                guardian: who,
+                new_account,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel a recovery in progress (controller only)
+        #[pallet::call_index(19)]
+        #[pallet::weight(T::WeightInfo::cancel_recovery())]
+        pub fn cancel_recovery(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            ensure!(
+                ActiveRecoveries::<T>::take(&did).is_some(),
+                Error::<T>::NoActiveRecovery
+            );
+
+            Self::deposit_event(Event::RecoveryCancelled { did });
+
+            Ok(())
+        }
+
+        /// Complete an approved recovery, making the caller the DID's controller
+        ///
+        /// The DID keeps its identifier, so credentials and other records that
+        /// reference it are unaffected. Every account linked to the DID is
+        /// unlinked, as the lost account may be in other hands, and the caller is
+        /// linked in their place.
+        ///
+        /// # Errors
+        /// * `NoActiveRecovery` - If no recovery is open for `did`
+        /// * `RecoveryNotApproved` - If no account has `threshold` vouches
+        /// * `NotAuthorized` - If the caller is not the approved account
+        /// * `RecoveryDelayNotElapsed` - If `RecoveryDelay` has not passed since approval
+        /// * `DidAlreadyExists` - If the caller is linked to another DID
+        #[pallet::call_index(20)]
+        #[pallet::weight(T::WeightInfo::claim_recovery()
+            .saturating_add(T::IssuedCredentials::transfer_deposits_weight()))]
+        pub fn claim_recovery(origin: OriginFor<T>, did: T::DidIdentifier) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let recovery = ActiveRecoveries::<T>::get(&did).ok_or(Error::<T>::NoActiveRecovery)?;
+            let (new_account, approved_at) =
+                recovery.approved.ok_or(Error::<T>::RecoveryNotApproved)?;
+            ensure!(new_account == who, Error::<T>::NotAuthorized);
+            ensure!(
+                frame_system::Pallet::<T>::block_number()
+                    >= approved_at.saturating_add(T::RecoveryDelay::get()),
+                Error::<T>::RecoveryDelayNotElapsed
+            );
+
+            if let Some(linked) = AccountDids::<T>::get(&who) {
+                ensure!(linked == did, Error::<T>::DidAlreadyExists);
+            }
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+                did_doc.controller = who.clone();
+                did_doc.updated_at = frame_system::Pallet::<T>::block_number();
+                Ok(())
+            })?;
+
+            for account in LinkedAccounts::<T>::take(&did) {
+                AccountDids::<T>::remove(account);
+            }
+            Self::link(&did, &who)?;
+
+            ActiveRecoveries::<T>::remove(&did);
+            PendingControllers::<T>::remove(&did);
+
+            Self::deposit_event(Event::DidRecovered {
+                did: did.clone(),
+                controller: who,
+            });
+
+            // Moves the deposits from the lost account to the new controller
+            Self::update_controller_deposits(&did)
+        }
+
+        /// Register an accreditation body able to verify institutions (`VerifyOrigin` only)
+        ///
+        /// # Errors
+        /// * `DidNotFound` - If `body` has no DID
+        /// * `AccreditationBodyAlreadyRegistered` - If `body` is already registered
+        /// * `InvalidInstitutionName` - If `name` is empty
+        /// * `InvalidAccreditationScope` - If the country code is not two uppercase
+        ///   letters or the sector is empty
+        #[pallet::call_index(21)]
+        #[pallet::weight(T::WeightInfo::register_accreditation_body())]
+        pub fn register_accreditation_body(
+            origin: OriginFor<T>,
+            body: T::DidIdentifier,
+            name: BoundedVec<u8, T::MaxDocumentSize>,
+            scope: AccreditationScope,
+        ) -> DispatchResult {
+            T::VerifyOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                DidDocuments::<T>::contains_key(&body),
+                Error::<T>::DidNotFound
+            );
+            ensure!(
+                !AccreditationBodies::<T>::contains_key(&body),
+                Error::<T>::AccreditationBodyAlreadyRegistered
+            );
+            ensure!(!name.is_empty(), Error::<T>::InvalidInstitutionName);
+            ensure!(scope.is_valid(), Error::<T>::InvalidAccreditationScope);
+
+            AccreditationBodies::<T>::insert(
+                &body,
+                AccreditationBody {
+                    name,
+                    scope: scope.clone(),
+                    registered_at: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+
+            Self::deposit_event(Event::AccreditationBodyRegistered { body, scope });
+
+            Ok(())
+        }
+
+        /// Remove an accreditation body (`RevokeOrigin` only)
+        ///
+        /// Institutions it verified stay verified and keep it as their accreditor,
+        /// so governance can review them through `AccreditedInstitutions`.
+        ///
+        /// # Errors
+        /// * `AccreditationBodyNotFound` - If `body` is not registered
+        #[pallet::call_index(22)]
+        #[pallet::weight(T::WeightInfo::remove_accreditation_body())]
+        pub fn remove_accreditation_body(
+            origin: OriginFor<T>,
+            body: T::DidIdentifier,
+        ) -> DispatchResult {
+            T::RevokeOrigin::ensure_origin(origin)?;
+
+            AccreditationBodies::<T>::take(&body).ok_or(Error::<T>::AccreditationBodyNotFound)?;
+
+            Self::deposit_event(Event::AccreditationBodyRemoved { body });
+
+            Ok(())
+        }
+
+        /// Extend an institution's verification by `VerificationPeriod` from now
+        /// (`VerifyOrigin` or the verifying accreditation body)
+        ///
+        /// Also restores a verification that has already lapsed.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If `institution_did` is not registered
+        /// * `InstitutionNotVerified` - If the institution was never verified or
+        ///   its verification was revoked
+        /// * `NotAccreditor` - If an accreditation body renews an institution it
+        ///   did not verify
+        /// * `OutOfAccreditationScope` - If the institution's profile is outside
+        ///   the accreditation body's country or sector
+        /// * `InstitutionClosed` - If the institution has closed
+        #[pallet::call_index(23)]
+        #[pallet::weight(T::WeightInfo::renew_institution_verification())]
+        pub fn renew_institution_verification(
+            origin: OriginFor<T>,
+            institution_did: T::DidIdentifier,
+        ) -> DispatchResult {
+            let accreditor = Self::ensure_accreditor::<T::VerifyOrigin>(origin)?;
+
+            ensure!(
+                !ClosedInstitutions::<T>::contains_key(&institution_did),
+                Error::<T>::InstitutionClosed
+            );
+
+            Institutions::<T>::try_mutate(&institution_did, |institution_opt| -> DispatchResult {
+                let institution = institution_opt
+                    .as_mut()
+                    .ok_or(Error::<T>::InstitutionNotFound)?;
+
+                ensure!(
+                    institution.verified_until.is_some(),
+                    Error::<T>::InstitutionNotVerified
+                );
+                if let Some(body) = &accreditor {
+                    ensure!(
+                        institution.accreditor == accreditor,
+                        Error::<T>::NotAccreditor
+                    );
+                    Self::ensure_in_scope(body, &institution_did)?;
+                }
+
+                institution.verified = true;
+                let verified_until =
+                    Self::schedule_verification_lapse(&institution_did, institution);
+
+                Self::deposit_event(Event::InstitutionVerificationRenewed {
+                    did: institution_did.clone(),
+                    verified_until,
+                });
+
+                Ok(())
+            })
+        }
+
+        /// Replace the public profile of an institution (controller only)
+        ///
+        /// The institution deposit is resized to cover the profile.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If `institution_did` is not registered
+        /// * `NotAuthorized` - If the caller is not the DID controller
+        /// * `InvalidCountryCode` - If the country is not two uppercase letters
+        /// * `InvalidLegalIdentifier` - If the identifier is empty or has characters
+        ///   other than letters, digits, spaces, `-`, `/` and `.`
+        /// * `InvalidWebsiteDomain` - If the website is not a lowercase domain name
+        /// * `InvalidAccreditationReference` - If a reference is empty, repeated or
+        ///   not printable ASCII
+        /// * `InvalidSector` - If a sector is empty or repeated
+        /// * `OutOfAccreditationScope` - If the profile would leave the scope of
+        ///   the accreditation body that verified the institution
+        #[pallet::call_index(24)]
+        #[pallet::weight(T::WeightInfo::update_institution_profile())]
+        pub fn update_institution_profile(
+            origin: OriginFor<T>,
+            institution_did: T::DidIdentifier,
+            profile: InstitutionProfile,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let institution =
+                Institutions::<T>::get(&institution_did).ok_or(Error::<T>::InstitutionNotFound)?;
+            let did_doc =
+                DidDocuments::<T>::get(&institution_did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+            Self::validate_profile(&profile)?;
+
+            // An institution cannot move out of the scope of its accreditor
+            if let Some(body) = institution
+                .accreditor
+                .and_then(AccreditationBodies::<T>::get)
+            {
+                ensure!(
+                    body.scope.covers(&profile),
+                    Error::<T>::OutOfAccreditationScope
+                );
+            }
+
+            InstitutionProfiles::<T>::insert(&institution_did, profile);
+            Self::update_institution_deposit(&institution_did)?;
+
+            Self::deposit_event(Event::InstitutionProfileUpdated {
+                did: institution_did,
+            });
+
+            Ok(())
+        }
+
+        /// Stop operating as an institution
+        ///
+        /// The registration is kept, marked closed at the current block, so that
+        /// credentials issued before then remain verifiable. The institution can
+        /// no longer issue credentials or be verified, and its name is freed.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If the caller's DID is not a registered institution
+        /// * `InstitutionClosed` - If the institution has already closed
+        #[pallet::call_index(25)]
+        #[pallet::weight(T::WeightInfo::deregister_institution())]
+        pub fn deregister_institution(origin: OriginFor<T>) -> DispatchResult {
+            let did = Self::acting_did(origin)?.ok_or(Error::<T>::InstitutionNotFound)?;
+
+            Self::close(&did)?;
+
+            Self::deposit_event(Event::InstitutionDeregistered { did });
+
+            Ok(())
+        }
+
+        /// Close an institution that has ceased operating (`RevokeOrigin` only)
+        ///
+        /// Has the same effect as the institution deregistering itself.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If `institution_did` is not registered
+        /// * `InstitutionClosed` - If the institution has already closed
+        #[pallet::call_index(26)]
+        #[pallet::weight(T::WeightInfo::close_institution())]
+        pub fn close_institution(
+            origin: OriginFor<T>,
+            institution_did: T::DidIdentifier,
+        ) -> DispatchResult {
+            T::RevokeOrigin::ensure_origin(origin)?;
+
+            Self::close(&institution_did)?;
+
+            Self::deposit_event(Event::InstitutionClosed {
+                did: institution_did,
+            });
+
+            Ok(())
+        }
+
+        /// Make `successor` responsible for the credentials of a merged
+        /// institution (`VerifyOrigin` only)
+        ///
+        /// The successor, and in turn its own successors, may revoke and amend
+        /// credentials issued by `institution_did`. Setting a new successor
+        /// replaces the previous one.
+        ///
+        /// # Errors
+        /// * `InstitutionNotFound` - If either institution is not registered
+        /// * `InstitutionClosed` - If `successor` has closed
+        /// * `InvalidSuccessor` - If the succession would lead back to
+        ///   `institution_did`
+        #[pallet::call_index(27)]
+        #[pallet::weight(T::WeightInfo::set_successor())]
+        pub fn set_successor(
+            origin: OriginFor<T>,
+            institution_did: T::DidIdentifier,
+            successor: T::DidIdentifier,
+        ) -> DispatchResult {
+            T::VerifyOrigin::ensure_origin(origin)?;
+
+            ensure!(
+                Institutions::<T>::contains_key(&institution_did)
+                    && Institutions::<T>::contains_key(&successor),
+                Error::<T>::InstitutionNotFound
+            );
+            ensure!(
+                !ClosedInstitutions::<T>::contains_key(&successor),
+                Error::<T>::InstitutionClosed
+            );
+            ensure!(
+                successor != institution_did && !Self::is_successor(&institution_did, &successor),
+                Error::<T>::InvalidSuccessor
+            );
+
+            if let Some(previous) = Successors::<T>::get(&institution_did) {
+                Predecessors::<T>::remove(previous, &institution_did);
+            }
+            Successors::<T>::insert(&institution_did, &successor);
+            Predecessors::<T>::insert(&successor, &institution_did, ());
+
+            Self::deposit_event(Event::SuccessorSet {
+                institution: institution_did,
+                successor,
+            });
+
+            Ok(())
+        }
+
+        /// Link the calling account to `did`, so that it acts for the DID
+        ///
+        /// The account consents by signing the extrinsic and the DID by `proof`, a
+        /// signature by one of its authentication keys over
+        /// `account_link_payload(did, account)`. The DID deposit grows to cover
+        /// the link.
+        ///
+        /// # Errors
+        /// * `AccountAlreadyLinked` - If the caller is linked to a DID
+        /// * `DidNotFound` - If no DID exists for `did`
+        /// * `DidInactive` - If the DID is deactivated
+        /// * `PublicKeyNotFound` - If `key_id` is not part of the DID document
+        /// * `RelationshipNotAssigned` - If the key cannot authenticate the DID
+        /// * `InvalidKeyProof` - If `proof` was not made with the key
+        /// * `TooManyLinkedAccounts` - If the DID has `MaxLinkedAccounts` accounts
+        #[pallet::call_index(28)]
+        #[pallet::weight(T::WeightInfo::link_account())]
+        pub fn link_account(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            key_id: [u8; 32],
+            proof: KeyProof,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                !AccountDids::<T>::contains_key(&who),
+                Error::<T>::AccountAlreadyLinked
+            );
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.active, Error::<T>::DidInactive);
+
+            let key = did_doc
+                .public_keys
+                .iter()
+                .find(|k| k.key_id == key_id)
+                .ok_or(Error::<T>::PublicKeyNotFound)?;
+            ensure!(
+                key.has_relationship(VerificationRelationship::Authentication),
+                Error::<T>::RelationshipNotAssigned
+            );
+
+            let payload = Self::account_link_payload(&did, &who);
+            ensure!(
+                Self::verify_signature(&key.public_key, &proof, &payload),
+                Error::<T>::InvalidKeyProof
+            );
+
+            Self::link(&did, &who)?;
+            KeyNonces::<T>::mutate(&did, |nonce| nonce.saturating_inc());
+            Self::update_did_deposit(&did)?;
+
+            Self::deposit_event(Event::AccountLinked { did, account: who });
+
+            Ok(())
+        }
+
+        /// Unlink `account` from `did` (the account itself or the DID controller)
+        ///
+        /// # Errors
+        /// * `AccountNotLinked` - If `account` is not linked to `did`
+        /// * `NotAuthorized` - If the caller is neither `account` nor the controller
+        #[pallet::call_index(29)]
+        #[pallet::weight(T::WeightInfo::unlink_account())]
+        pub fn unlink_account(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            account: T::AccountId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                AccountDids::<T>::get(&account).as_ref() == Some(&did),
+                Error::<T>::AccountNotLinked
+            );
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(
+                who == account || did_doc.controller == who,
+                Error::<T>::NotAuthorized
+            );
+
+            Self::unlink(&did, &account);
+            Self::update_did_deposit(&did)?;
+
+            Self::deposit_event(Event::AccountUnlinked { did, account });
+
+            Ok(())
+        }
+
+        /// Dispatch `call` on behalf of `did`, authorised by one of its keys
+        ///
+        /// `signature` is made by an authentication or assertion key of the DID
+        /// over `did_call_payload(did, call, nonce, submitter)`, so the key
+        /// authorising the call can differ from the account paying its fees. The
+        /// call is dispatched with a DID origin, which pallets accept wherever the
+        /// DID's linked accounts may act. The nonce is consumed even if `call`
+        /// fails, whose outcome is reported in the `DidCallDispatched` event.
+        ///
+        /// # Errors
+        /// * `DidNotFound` - If no DID exists for `did`
+        /// * `DidInactive` - If the DID is deactivated
+        /// * `InvalidDidCallNonce` - If `nonce` is not the DID's next call nonce
+        /// * `PublicKeyNotFound` - If `key_id` is not part of the DID document
+        /// * `RelationshipNotAssigned` - If the key is neither an authentication
+        ///   nor an assertion key
+        /// * `InvalidDidCallSignature` - If `signature` was not made with the key
+        #[pallet::call_index(30)]
+        #[pallet::weight({
+            let dispatch_info = call.get_dispatch_info();
+            T::WeightInfo::submit_did_call().saturating_add(dispatch_info.call_weight)
+        })]
+        #[allow(clippy::useless_conversion)]
+        pub fn submit_did_call(
+            origin: OriginFor<T>,
+            did: T::DidIdentifier,
+            call: Box<<T as Config>::RuntimeCall>,
+            nonce: u64,
+            key_id: [u8; 32],
+            signature: KeyProof,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let did_doc = DidDocuments::<T>::get(&did).ok_or(Error::<T>::DidNotFound)?;
+            ensure!(did_doc.active, Error::<T>::DidInactive);
+            ensure!(
+                DidCallNonces::<T>::get(&did) == nonce,
+                Error::<T>::InvalidDidCallNonce
+            );
+
+            let key = did_doc
+                .public_keys
+                .iter()
+                .find(|k| k.key_id == key_id)
+                .ok_or(Error::<T>::PublicKeyNotFound)?;
+            ensure!(
+                key.has_relationship(VerificationRelationship::Authentication)
+                    || key.has_relationship(VerificationRelationship::AssertionMethod),
+                Error::<T>::RelationshipNotAssigned
+            );
+
+            let payload = Self::did_call_payload(&did, &call, nonce, &who);
+            ensure!(
+                Self::verify_signature(&key.public_key, &signature, &payload),
+                Error::<T>::InvalidDidCallSignature
+            );
+
+            DidCallNonces::<T>::insert(&did, nonce.saturating_add(1));
+
+            let did_origin = Origin::<T> {
+                did: did.clone(),
+                key_id,
+                submitter: who.clone(),
+            };
+            let result = call.dispatch(did_origin.into());
+            let call_weight = match result {
+                Ok(info) => info.actual_weight,
+                Err(e) => e.post_info.actual_weight,
+            };
+
+            Self::deposit_event(Event::DidCallDispatched {
+                did,
+                submitter: who,
+                result: result.map(|_| ()).map_err(|e| e.error),
+            });
+
+            Ok(call_weight
+                .map(|w| T::WeightInfo::submit_did_call().saturating_add(w))
+                .into())
+        }
+
+        /// Report that a key of `did` has been compromised since `compromised_since`
+        /// (controller only)
+        ///
+        /// Unlike `remove_public_key`, the key is kept on record so that
+        /// signatures it made before `compromised_since` remain valid, while
+        /// later ones are reported as untrusted. The key is taken out of the
+        /// DID document and cannot be added to any DID again. Key-agreement keys
+        /// sign nothing and are not indexed; remove them with `remove_public_key`.
+        ///
+        /// # Errors
+        /// * `PublicKeyNotFound` - If the key is not part of a DID document
+        /// * `NotAuthorized` - If the caller is not the controller
+        /// * `InvalidCompromiseBlock` - If `compromised_since` is in the future
+        /// * `LastAuthenticationKey` - If the DID would be left without an
+        ///   authentication key; add a new one first
+        /// * `TooManyCompromisedKeys` - If the DID has `MaxCompromisedKeys`
+        ///   compromised keys on record
+        #[pallet::call_index(31)]
+        #[pallet::weight(T::WeightInfo::report_key_compromised())]
+        pub fn report_key_compromised(
+            origin: OriginFor<T>,
+            key_id: [u8; 32],
+            compromised_since: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let did = DidByKeyId::<T>::get(key_id).ok_or(Error::<T>::PublicKeyNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(compromised_since <= now, Error::<T>::InvalidCompromiseBlock);
+
+            DidDocuments::<T>::try_mutate(&did, |did_doc_opt| -> DispatchResult {
+                let did_doc = did_doc_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                ensure!(did_doc.controller == who, Error::<T>::NotAuthorized);
+
+                let index = did_doc
+                    .public_keys
+                    .iter()
+                    .position(|k| k.key_id == key_id)
+                    .ok_or(Error::<T>::PublicKeyNotFound)?;
+
+                ensure!(
+                    !Self::is_last_authentication_key(&did_doc.public_keys, &key_id),
+                    Error::<T>::LastAuthenticationKey
+                );
+
+                let key = did_doc.public_keys.remove(index);
+                Self::record_key(&did, key_id, None)?;
+                CompromisedKeys::<T>::try_mutate(&did, |compromised| {
+                    compromised.try_push(CompromisedKey {
+                        key,
+                        compromised_since,
+                        reported_at: now,
+                    })
+                })
+                .map_err(|_| Error::<T>::TooManyCompromisedKeys)?;
+
+                did_doc.updated_at = now;
+
+                Self::deposit_event(Event::KeyCompromised {
+                    owner: did.clone(),
+                    key_id,
+                    compromised_since,
+                });
+
+                Ok(())
+            })?;
+
+            Self::update_did_deposit(&did)
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Collect a DID's document and services for the `DidApi` runtime API
+        pub fn resolve(
+            did: T::DidIdentifier,
+        ) -> Option<runtime_api::DidResolution<T::DidIdentifier, T::AccountId, BlockNumberFor<T>>>
+        {
+            let did_doc = DidDocuments::<T>::get(&did)?;
+
+            let services = ServiceEndpoints::<T>::get(&did)
+                .into_iter()
+                .map(|s| runtime_api::ResolvedService {
+                    id: s.id.into_inner(),
+                    service_type: s.service_type.into_inner(),
+                    url: s.url.into_inner(),
+                })
+                .collect();
+
+            Some(runtime_api::DidResolution {
+                did,
+                controller: did_doc.controller,
+                public_keys: did_doc.public_keys.into_inner(),
+                services,
+                created_at: did_doc.created_at,
+                updated_at: did_doc.updated_at,
+                active: did_doc.active,
+            })
+        }
+
+        /// Keys `did` had at the end of `block`, for the `DidApi` runtime API
+        ///
+        /// Keys reported compromised are left out from their compromise block on.
+        /// `None` if the DID does not exist or its history no longer reaches back
+        /// to `block`.
+        pub fn did_document_at(
+            did: &T::DidIdentifier,
+            block: BlockNumberFor<T>,
+        ) -> Option<Vec<PublicKeyEntry>> {
+            let history = KeyHistories::<T>::get(did)?;
+            if block < history.complete_from {
+                return None;
+            }
+
+            let keys = history
+                .records
+                .into_iter()
+                .filter(|r| r.valid_from <= block && r.valid_until.is_none_or(|u| block < u))
+                .filter(|r| {
+                    Self::compromised_since(did, &r.key.key_id).is_none_or(|since| block < since)
+                })
+                .map(|r| r.key)
+                .collect();
+            Some(keys)
+        }
+
+        /// Block from which signatures by the key `key_id` of `did` are
+        /// untrusted, if it was reported compromised
+        pub fn compromised_since(
+            did: &T::DidIdentifier,
+            key_id: &[u8; 32],
+        ) -> Option<BlockNumberFor<T>> {
+            CompromisedKeys::<T>::get(did)
+                .into_iter()
+                .find(|c| c.key.key_id == *key_id)
+                .map(|c| c.compromised_since)
+        }
+
+        /// Whether a signature made by the key `key_id` of `did` at `block` can be
+        /// trusted, for the `DidApi` runtime API
+        pub fn key_status_at(
+            did: &T::DidIdentifier,
+            key_id: &[u8; 32],
+            block: BlockNumberFor<T>,
+        ) -> runtime_api::KeyStatus<BlockNumberFor<T>> {
+            if let Some(since) = Self::compromised_since(did, key_id) {
+                if since <= block {
+                    return runtime_api::KeyStatus::Compromised { since };
+                }
+            }
+
+            match Self::did_document_at(did, block) {
+                None => runtime_api::KeyStatus::Unknown,
+                Some(keys) if keys.iter().any(|k| k.key_id == *key_id) => {
+                    runtime_api::KeyStatus::Valid
+                }
+                Some(_) => runtime_api::KeyStatus::NotInDocument,
+            }
+        }
+
+        /// DID that `account` is linked to, for the `DidApi` runtime API
+        pub fn did_by_account(account: &T::AccountId) -> Option<T::DidIdentifier> {
+            AccountDids::<T>::get(account)
+        }
+
+        /// DID holding the signing key `public_key`, for the `DidApi` runtime API
+        pub fn did_by_key(public_key: &PublicKey) -> Option<T::DidIdentifier> {
+            DidByKeyId::<T>::get(public_key.key_id())
+        }
+
+        /// Whether `candidate` directly or transitively succeeds `institution`
+        pub fn is_successor(candidate: &T::DidIdentifier, institution: &T::DidIdentifier) -> bool {
+            // `set_successor` rejects cycles, so the chain always ends
+            let mut current = Successors::<T>::get(institution);
+            while let Some(successor) = current {
+                if successor == *candidate {
+                    return true;
+                }
+                current = Successors::<T>::get(&successor);
+            }
+
+            false
+        }
+
+        /// Verified institution registered under `name`, for the `DidApi` runtime API
+        pub fn institution_by_name(name: &[u8]) -> Option<T::DidIdentifier> {
+            InstitutionNames::<T>::get(Self::institution_name_hash(name))
+        }
+
+        /// Hash of `name` ignoring ASCII case and surrounding or repeated whitespace
+        pub fn institution_name_hash(name: &[u8]) -> [u8; 32] {
+            let normalized = name
+                .split(u8::is_ascii_whitespace)
+                .filter(|word| !word.is_empty())
+                .map(<[u8]>::to_ascii_lowercase)
+                .collect::<Vec<_>>()
+                .join(&b' ');
+
+            hashing::blake2_256(&normalized)
+        }
+
+        /// Let `account` act for `did`
+        fn link(did: &T::DidIdentifier, account: &T::AccountId) -> DispatchResult {
+            LinkedAccounts::<T>::try_mutate(did, |accounts| accounts.try_push(account.clone()))
+                .map_err(|_| Error::<T>::TooManyLinkedAccounts)?;
+            AccountDids::<T>::insert(account, did);
+
+            Ok(())
+        }
+
+        fn unlink(did: &T::DidIdentifier, account: &T::AccountId) {
+            LinkedAccounts::<T>::mutate_exists(did, |accounts_opt| {
+                if let Some(accounts) = accounts_opt {
+                    accounts.retain(|a| a != account);
+                    if accounts.is_empty() {
+                        *accounts_opt = None;
+                    }
+                }
+            });
+            AccountDids::<T>::remove(account);
+        }
+
+        /// Reserve `name` for `did`, unless another verified institution holds it
+        fn claim_institution_name(did: &T::DidIdentifier, name: &[u8]) -> DispatchResult {
+            let name_hash = Self::institution_name_hash(name);
+            if let Some(holder) = InstitutionNames::<T>::get(name_hash) {
+                ensure!(holder == *did, Error::<T>::InstitutionNameTaken);
+            }
+
+            InstitutionNames::<T>::insert(name_hash, did);
+
+            Ok(())
+        }
+
+        fn release_institution_name(did: &T::DidIdentifier, name: &[u8]) {
+            let name_hash = Self::institution_name_hash(name);
+            if InstitutionNames::<T>::get(name_hash).as_ref() == Some(did) {
+                InstitutionNames::<T>::remove(name_hash);
+            }
+        }
+
+        /// Record the closure of an institution and withdraw its verification
+        fn close(did: &T::DidIdentifier) -> DispatchResult {
+            ensure!(
+                !ClosedInstitutions::<T>::contains_key(did),
+                Error::<T>::InstitutionClosed
+            );
+
+            Institutions::<T>::try_mutate(did, |institution_opt| -> DispatchResult {
+                let institution = institution_opt
+                    .as_mut()
+                    .ok_or(Error::<T>::InstitutionNotFound)?;
+
+                institution.verified = false;
+                if let Some(until) = institution.verified_until.take() {
+                    VerificationExpiries::<T>::remove(until, did);
+                }
+                Self::release_institution_name(did, &institution.name);
+
+                Ok(())
+            })?;
+
+            ClosedInstitutions::<T>::insert(did, frame_system::Pallet::<T>::block_number());
+
+            Ok(())
+        }
+
+        /// Move the institution's lapse to `VerificationPeriod` from now
+        fn schedule_verification_lapse(
+            did: &T::DidIdentifier,
+            institution: &mut Institution<T>,
+        ) -> BlockNumberFor<T> {
+            if let Some(until) = institution.verified_until {
+                VerificationExpiries::<T>::remove(until, did);
+            }
+
+            let until = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::VerificationPeriod::get());
+            VerificationExpiries::<T>::insert(until, did, ());
+            institution.verified_until = Some(until);
+
+            until
+        }
+
+        /// `None` if `origin` passes `O`, otherwise the accreditation body the
+        /// signer is linked to
+        fn ensure_accreditor<O: EnsureOrigin<OriginFor<T>>>(
+            origin: OriginFor<T>,
+        ) -> Result<Option<T::DidIdentifier>, DispatchError> {
+            match O::try_origin(origin) {
+                Ok(_) => Ok(None),
+                Err(origin) => {
+                    let body = Self::acting_did(origin)?
+                        .filter(|did| AccreditationBodies::<T>::contains_key(did))
+                        .ok_or(DispatchError::BadOrigin)?;
+                    Ok(Some(body))
+                }
+            }
+        }
+
+        /// Lapse the verifications queued up to block `now`, using at most `limit`
+        ///
+        /// Resumes from `VerificationExpiryCursor`, so a backlog left by a busy
+        /// block is worked off in `on_idle` or later blocks.
+        fn process_verification_expiries(now: BlockNumberFor<T>, limit: Weight) -> Weight {
+            let db_weight = T::DbWeight::get();
+            let mut weight = db_weight.reads_writes(1, 1);
+            if weight.any_gt(limit) {
+                return Weight::zero();
+            }
+
+            let start = VerificationExpiryCursor::<T>::get();
+            let mut cursor = start;
+            let lapse_weight = T::WeightInfo::lapse_institution_verification();
+
+            while cursor <= now && weight.saturating_add(lapse_weight).all_lte(limit) {
+                match VerificationExpiries::<T>::iter_key_prefix(cursor).next() {
+                    Some(did) => {
+                        VerificationExpiries::<T>::remove(cursor, &did);
+                        Self::lapse_verification(&did, cursor);
+                        weight.saturating_accrue(lapse_weight);
+                    }
+                    None => {
+                        cursor.saturating_inc();
+                        weight.saturating_accrue(db_weight.reads(1));
+                    }
+                }
+            }
+
+            if cursor != start {
+                VerificationExpiryCursor::<T>::put(cursor);
+            }
+
+            weight
+        }
+
+        /// Mark an institution unverified if its verification lapsed at `until`
+        ///
+        /// Its name is released; renewing the verification claims it again if no
+        /// other institution has been verified under it meanwhile.
+        fn lapse_verification(did: &T::DidIdentifier, until: BlockNumberFor<T>) {
+            Institutions::<T>::mutate(did, |institution_opt| {
+                let Some(institution) = institution_opt else {
+                    return;
+                };
+                if institution.verified && institution.verified_until == Some(until) {
+                    institution.verified = false;
+                    Self::release_institution_name(did, &institution.name);
+                    Self::deposit_event(Event::InstitutionVerificationLapsed { did: did.clone() });
+                }
+            });
+        }
+
+        /// Ensure `institution` falls within the scope of accreditation `body`
+        fn ensure_in_scope(
+            body: &T::DidIdentifier,
+            institution: &T::DidIdentifier,
+        ) -> DispatchResult {
+            let scope = AccreditationBodies::<T>::get(body)
+                .ok_or(Error::<T>::AccreditationBodyNotFound)?
+                .scope;
+            let profile = InstitutionProfiles::<T>::get(institution).unwrap_or_default();
+            ensure!(scope.covers(&profile), Error::<T>::OutOfAccreditationScope);
+            Ok(())
+        }
+
+        /// Keys of a DID assigned the given verification relationship
+        pub fn keys_with_relationship(
+            did: &T::DidIdentifier,
+            relationship: VerificationRelationship,
+        ) -> Vec<PublicKeyEntry> {
+            DidDocuments::<T>::get(did)
+                .map(|did_doc| {
+                    did_doc
+                        .public_keys
+                        .into_iter()
+                        .filter(|k| k.has_relationship(relationship))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        /// Payload a new key must sign to prove possession when added to `did`
+        ///
+        /// Binds the proof to this chain, the DID and the DID's current key nonce,
+        /// so a proof cannot be replayed elsewhere or reused for another DID.
+        pub fn key_possession_payload(did: &T::DidIdentifier) -> Vec<u8> {
+            let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+            (
+                KEY_POSSESSION_CONTEXT,
+                genesis_hash,
+                did,
+                KeyNonces::<T>::get(did),
+            )
+                .encode()
+        }
+
+        /// Payload a DID authentication key signs to consent to linking `account`
+        ///
+        /// Binds the consent to this chain, the DID, the account and the DID's
+        /// current key nonce, so it cannot be replayed.
+        pub fn account_link_payload(did: &T::DidIdentifier, account: &T::AccountId) -> Vec<u8> {
+            let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+            (
+                ACCOUNT_LINK_CONTEXT,
+                genesis_hash,
+                did,
+                account,
+                KeyNonces::<T>::get(did),
+            )
+                .encode()
+        }
+
+        /// Payload a DID key signs to authorise `call` through `submit_did_call`
+        ///
+        /// Binds the authorisation to this chain, the DID, the call nonce and the
+        /// submitting account, so it can be neither replayed nor front-run by
+        /// another submitter.
+        pub fn did_call_payload(
+            did: &T::DidIdentifier,
+            call: &<T as Config>::RuntimeCall,
+            nonce: u64,
+            submitter: &T::AccountId,
+        ) -> Vec<u8> {
+            let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+            (DID_CALL_CONTEXT, genesis_hash, did, call, nonce, submitter).encode()
+        }
+
+        /// DID acting through `origin`
+        ///
+        /// A DID origin acts for its DID and a signed origin for the DID its
+        /// account is linked to, if any. Other origins are rejected.
+        pub fn acting_did(origin: OriginFor<T>) -> Result<Option<T::DidIdentifier>, DispatchError> {
+            match <T as Config>::RuntimeOrigin::from(origin).into() {
+                Ok(DidRawOrigin { did, .. }) => Ok(Some(did)),
+                Err(origin) => {
+                    let origin: OriginFor<T> = origin.into();
+                    let who = ensure_signed(origin)?;
+                    Ok(AccountDids::<T>::get(&who))
+                }
+            }
+        }
+
+        /// Key that authorised the call, if `origin` is a DID origin
+        pub fn signing_key(origin: &OriginFor<T>) -> Option<[u8; 32]> {
+            let origin: Result<Origin<T>, _> =
+                <T as Config>::RuntimeOrigin::from(origin.clone()).into();
+            origin.ok().map(|o| o.key_id)
+        }
+
+        /// Check that `proof` is a signature by `public_key` over the DID's possession payload
+        ///
+        /// X25519 keys cannot sign and are accepted with `KeyProof::None`, so
+        /// nothing shows they belong to the DID. They are kept out of
+        /// `DidByKeyId` and never block another DID from adding the same key.
+        fn verify_key_possession(
+            did: &T::DidIdentifier,
+            public_key: &PublicKey,
+            proof: &KeyProof,
+        ) -> DispatchResult {
+            ensure!(public_key.is_well_formed(), Error::<T>::InvalidPublicKey);
+
+            let payload = Self::key_possession_payload(did);
+
+            let valid = match (public_key, proof) {
+                (PublicKey::X25519(_), KeyProof::None) => true,
+                _ => Self::verify_signature(public_key, proof, &payload),
+            };
+
+            ensure!(valid, Error::<T>::InvalidKeyProof);
+            Ok(())
+        }
+
+        /// Whether `proof` is a signature by `public_key` over `payload`
+        fn verify_signature(public_key: &PublicKey, proof: &KeyProof, payload: &[u8]) -> bool {
+            match (public_key, proof) {
+                (PublicKey::Ed25519(key), KeyProof::Ed25519(signature)) => {
+                    sp_io::crypto::ed25519_verify(
+                        signature,
+                        payload,
+                        &ed25519::Public::from_raw(*key),
+                    )
+                }
+                (PublicKey::Sr25519(key), KeyProof::Sr25519(signature)) => {
+                    sp_io::crypto::sr25519_verify(
+                        signature,
+                        payload,
+                        &sr25519::Public::from_raw(*key),
+                    )
+                }
+                (PublicKey::Ecdsa(key), KeyProof::Ecdsa(signature)) => {
+                    sp_io::crypto::ecdsa_verify(signature, payload, &ecdsa::Public::from_raw(*key))
+                }
+                (PublicKey::P256(key), KeyProof::P256(signature)) => {
+                    Self::verify_p256(key, signature, payload)
+                }
+                _ => false,
+            }
+        }
+
+        /// Verify a P-256 ECDSA signature, for which there is no host function
+        fn verify_p256(public_key: &[u8; 33], signature: &[u8; 64], message: &[u8]) -> bool {
+            use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+            let Ok(verifying_key) = VerifyingKey::from_sec1_bytes(public_key) else {
+                return false;
+            };
+            let Ok(signature) = Signature::from_slice(signature) else {
+                return false;
+            };
+
+            verifying_key.verify(message, &signature).is_ok()
+        }
+
+        /// Deposit for an item of `bytes` encoded bytes with the given base deposit
+        pub fn storage_deposit(base: BalanceOf<T>, bytes: usize) -> BalanceOf<T> {
+            let bytes = BalanceOf::<T>::from(bytes.try_into().unwrap_or(u32::MAX));
+            base.saturating_add(T::ByteDeposit::get().saturating_mul(bytes))
+        }
+
+        /// Hold `amount` from `owner` for an item, replacing its `current` deposit
+        ///
+        /// Only the difference is held or released when the owner is unchanged;
+        /// otherwise the previous owner's deposit is released in full.
+        pub fn update_deposit(
+            reason: &T::RuntimeHoldReason,
+            current: Option<Deposit<T::AccountId, BalanceOf<T>>>,
+            owner: &T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> Result<Deposit<T::AccountId, BalanceOf<T>>, DispatchError> {
+            match current {
+                Some(current) if current.owner == *owner => {
+                    if amount > current.amount {
+                        T::Currency::hold(reason, owner, amount - current.amount)?;
+                    } else if amount < current.amount {
+                        T::Currency::release(
+                            reason,
+                            owner,
+                            current.amount - amount,
+                            Precision::BestEffort,
+                        )?;
+                    }
+                }
+                current => {
+                    if let Some(current) = current {
+                        Self::release_deposit(reason, current)?;
+                    }
+                    T::Currency::hold(reason, owner, amount)?;
+                }
+            }
+
+            Ok(Deposit {
+                owner: owner.clone(),
+                amount,
+            })
+        }
+
+        /// Return a deposit to its owner
+        pub fn release_deposit(
+            reason: &T::RuntimeHoldReason,
+            deposit: Deposit<T::AccountId, BalanceOf<T>>,
+        ) -> DispatchResult {
+            T::Currency::release(
+                reason,
+                &deposit.owner,
+                deposit.amount,
+                Precision::BestEffort,
+            )?;
+            Ok(())
+        }
+
+        /// Resize the deposit of `did` to its current document, services, recovery
+        /// guardians and linked accounts, held from its current controller
+        fn update_did_deposit(did: &T::DidIdentifier) -> DispatchResult {
+            let did_doc = DidDocuments::<T>::get(did).ok_or(Error::<T>::DidNotFound)?;
+            let bytes = did_doc
+                .encoded_size()
+                .saturating_add(ServiceEndpoints::<T>::get(did).encoded_size())
+                .saturating_add(RecoveryConfigs::<T>::get(did).map_or(0, |c| c.encoded_size()))
+                .saturating_add(LinkedAccounts::<T>::get(did).encoded_size())
+                .saturating_add(CompromisedKeys::<T>::get(did).encoded_size())
+                .saturating_add(KeyHistories::<T>::get(did).map_or(0, |h| h.encoded_size()));
+
+            let deposit = Self::update_deposit(
+                &HoldReason::DidDeposit.into(),
+                DidDeposits::<T>::get(did),
+                &did_doc.controller,
+                Self::storage_deposit(T::DidDeposit::get(), bytes),
+            )?;
+            DidDeposits::<T>::insert(did, deposit);
+
+            Ok(())
+        }
+
+        /// Record in the key history of `did` that the key `key_id` now reads
+        /// `entry`, or was removed if `None`
+        ///
+        /// The key's current record is closed and a new one opened from this
+        /// block. When the history is full the record that expired first is
+        /// dropped, and the history no longer covers blocks before its expiry.
+        fn record_key(
+            did: &T::DidIdentifier,
+            key_id: [u8; 32],
+            entry: Option<&PublicKeyEntry>,
+        ) -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+
+            KeyHistories::<T>::try_mutate(did, |history_opt| -> DispatchResult {
+                let history = history_opt.as_mut().ok_or(Error::<T>::DidNotFound)?;
+
+                // A record opened in this block was never in force at a block's end
+                history.records.retain(|r| {
+                    r.key.key_id != key_id || r.valid_until.is_some() || r.valid_from != now
+                });
+                for record in history.records.iter_mut() {
+                    if record.key.key_id == key_id && record.valid_until.is_none() {
+                        record.valid_until = Some(now);
+                    }
+                }
+
+                let Some(entry) = entry else {
+                    return Ok(());
+                };
+
+                if history.records.is_full() {
+                    let (index, expired_at) = history
+                        .records
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, r)| r.valid_until.map(|u| (i, u)))
+                        .min_by_key(|(_, u)| *u)
+                        .ok_or(Error::<T>::TooManyPublicKeys)?;
+                    history.records.remove(index);
+                    history.complete_from = history.complete_from.max(expired_at);
+                }
+
+                history
+                    .records
+                    .try_push(KeyRecord {
+                        key: entry.clone(),
+                        valid_from: now,
+                        valid_until: None,
+                    })
+                    .map_err(|_| Error::<T>::TooManyPublicKeys)?;
+
+                Ok(())
+            })
+        }
+
+        /// Move the deposits of `did` to its current controller
+        fn update_controller_deposits(did: &T::DidIdentifier) -> DispatchResult {
+            Self::update_did_deposit(did)?;
+            if Institutions::<T>::contains_key(did) {
+                Self::update_institution_deposit(did)?;
+            }
+
+            T::IssuedCredentials::transfer_deposits(did)
+        }
+
+        /// Resize the deposit of an institution to its registration and profile,
+        /// held from its DID controller
+        fn update_institution_deposit(did: &T::DidIdentifier) -> DispatchResult {
+            let institution = Institutions::<T>::get(did).ok_or(Error::<T>::InstitutionNotFound)?;
+            let controller = DidDocuments::<T>::get(did)
+                .ok_or(Error::<T>::DidNotFound)?
+                .controller;
+            let bytes = institution
+                .encoded_size()
+                .saturating_add(InstitutionProfiles::<T>::get(did).map_or(0, |p| p.encoded_size()));
+
+            let deposit = Self::update_deposit(
+                &HoldReason::InstitutionDeposit.into(),
+                InstitutionDeposits::<T>::get(did),
+                &controller,
+                Self::storage_deposit(T::DidDeposit::get(), bytes),
+            )?;
+            InstitutionDeposits::<T>::insert(did, deposit);
+
+            Ok(())
+        }
+
+        fn validate_profile(profile: &InstitutionProfile) -> DispatchResult {
+            if let Some(country) = profile.country {
+                ensure!(
+                    country.iter().all(u8::is_ascii_uppercase),
+                    Error::<T>::InvalidCountryCode
+                );
+            }
+
+            if let Some(legal_identifier) = &profile.legal_identifier {
+                ensure!(
+                    !legal_identifier.is_empty()
+                        && legal_identifier
+                            .iter()
+                            .all(|c| c.is_ascii_alphanumeric() || b" -/.".contains(c)),
+                    Error::<T>::InvalidLegalIdentifier
+                );
+            }
+
+            if let Some(website) = &profile.website {
+                ensure!(
+                    Self::is_valid_domain(website),
+                    Error::<T>::InvalidWebsiteDomain
+                );
+            }
+
+            for (index, reference) in profile.accreditations.iter().enumerate() {
+                ensure!(
+                    !reference.is_empty()
+                        && reference.iter().all(|c| c.is_ascii_graphic() || *c == b' ')
+                        && !profile.accreditations[..index].contains(reference),
+                    Error::<T>::InvalidAccreditationReference
+                );
+            }
+
+            for (index, sector) in profile.sectors.iter().enumerate() {
+                ensure!(
+                    !sector.is_empty()
+                        && !profile.sectors[..index]
+                            .iter()
+                            .any(|other| other.eq_ignore_ascii_case(sector)),
+                    Error::<T>::InvalidSector
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Lowercase dot-separated labels of letters, digits and inner hyphens,
+        /// with at least two labels
+        fn is_valid_domain(domain: &[u8]) -> bool {
+            let mut labels = 0;
+            let all_valid = domain.split(|c| *c == b'.').all(|label| {
+                labels += 1;
+                !label.is_empty()
+                    && label.len() <= 63
+                    && label
+                        .iter()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'-')
+                    && !label.starts_with(b"-")
+                    && !label.ends_with(b"-")
+            });
+
+            all_valid && labels >= 2
+        }
+
+        /// Whether `key_id` is the only authentication key in `public_keys`
+        fn is_last_authentication_key(public_keys: &[PublicKeyEntry], key_id: &[u8; 32]) -> bool {
+            let mut authentication_keys = public_keys
+                .iter()
+                .filter(|k| k.has_relationship(VerificationRelationship::Authentication));
+
+            matches!(
+                (authentication_keys.next(), authentication_keys.next()),
+                (Some(k), None) if k.key_id == *key_id
+            )
+        }
     }
 }
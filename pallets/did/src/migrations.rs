@@ -0,0 +1,1282 @@
+// pallets/did/src/migrations.rs
+use crate::{
+    pallet, AccountDids, AccreditationBody, ActiveRecovery, BalanceOf, Config, Deposit,
+    DidDocument, Institution, InstitutionProfile, KeyType, LinkedAccounts, ProfileField, PublicKey,
+    PublicKeyEntry, RecoveryConfig, ServiceEndpoint, VerificationRelationship,
+};
+use frame::{
+    deps::frame_support::migrations::VersionedMigration, prelude::*,
+    traits::UncheckedOnRuntimeUpgrade,
+};
+#[cfg(feature = "try-runtime")]
+use frame::{deps::sp_io, hashing, try_runtime::TryRuntimeError};
+
+/// Decode the state `pre_upgrade` handed to `post_upgrade`
+#[cfg(feature = "try-runtime")]
+fn decode_state<S: Decode>(state: Vec<u8>) -> Result<S, TryRuntimeError> {
+    S::decode(&mut &state[..]).map_err(|_| "cannot decode pre-upgrade state".into())
+}
+
+/// Whether re-keying `entries` entries of pallet `P` fits in the upgrade block
+///
+/// Each entry is read, written under its new key and removed under its old
+/// one, and the proof carries every key and value of the pallet. With
+/// `RocksDbWeight` and two-second blocks that is about 8,800 entries; larger
+/// states have to be migrated over several blocks instead.
+#[cfg(feature = "try-runtime")]
+pub fn fits_in_block<T: frame_system::Config, P: PalletInfoAccess>(entries: u64) -> bool {
+    let prefix = hashing::twox_128(P::name().as_bytes());
+    let mut bytes = 0u64;
+    let mut key = prefix.to_vec();
+    while let Some(next) = sp_io::storage::next_key(&key) {
+        if !next.starts_with(&prefix) {
+            break;
+        }
+        let value = sp_io::storage::get(&next).map_or(0, |value| value.len());
+        bytes.saturating_accrue(next.len().saturating_add(value) as u64);
+        key = next;
+    }
+
+    let weight = T::DbWeight::get()
+        .reads_writes(entries, entries.saturating_mul(2))
+        .saturating_add(Weight::from_parts(0, bytes));
+    frame::log::info!(
+        target: "runtime::did",
+        "re-keying {} entries of {} needs {:?}",
+        entries,
+        P::name(),
+        weight,
+    );
+    weight.all_lte(T::BlockWeights::get().max_block)
+}
+
+/// Adds verification relationships to every stored public key.
+pub mod v1 {
+    use super::*;
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct OldPublicKeyEntry {
+        pub key_id: [u8; 32],
+        pub key_type: KeyType,
+        pub public_key: [u8; 32],
+    }
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct OldDidDocument<T: Config> {
+        pub controller: T::AccountId,
+        pub public_keys: BoundedVec<OldPublicKeyEntry, T::MaxPublicKeys>,
+        pub created_at: BlockNumberFor<T>,
+        pub updated_at: BlockNumberFor<T>,
+        pub active: bool,
+    }
+
+    /// Public key entry layout at storage version 1
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct PublicKeyEntryV1 {
+        pub key_id: [u8; 32],
+        pub key_type: KeyType,
+        pub public_key: [u8; 32],
+        pub relationships: BoundedVec<VerificationRelationship, crate::MaxRelationships>,
+    }
+
+    /// DID document layout at storage version 1
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct DidDocumentV1<T: Config> {
+        pub controller: T::AccountId,
+        pub public_keys: BoundedVec<PublicKeyEntryV1, T::MaxPublicKeys>,
+        pub created_at: BlockNumberFor<T>,
+        pub updated_at: BlockNumberFor<T>,
+        pub active: bool,
+    }
+
+    #[frame::storage_alias]
+    pub type DidDocuments<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        DidDocumentV1<T>,
+    >;
+
+    pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            DidDocuments::<T>::translate::<OldDidDocument<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                // Before relationships existed any key could authenticate and sign,
+                // so existing keys keep both capabilities.
+                let public_keys = old
+                    .public_keys
+                    .into_iter()
+                    .map(|k| PublicKeyEntryV1 {
+                        key_id: k.key_id,
+                        key_type: k.key_type,
+                        public_key: k.public_key,
+                        relationships: BoundedVec::truncate_from(vec![
+                            VerificationRelationship::Authentication,
+                            VerificationRelationship::AssertionMethod,
+                        ]),
+                    })
+                    .collect::<Vec<_>>();
+
+                Some(DidDocumentV1 {
+                    controller: old.controller,
+                    public_keys: BoundedVec::truncate_from(public_keys),
+                    created_at: old.created_at,
+                    updated_at: old.updated_at,
+                    active: old.active,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((DidDocuments::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let documents: u64 = decode_state(state)?;
+            ensure!(
+                DidDocuments::<T>::iter_values().count() as u64 == documents,
+                "v1: DID documents lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 0 to version 1
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        InnerMigrateV0ToV1<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Replaces fixed 32-byte key material with the typed `PublicKey`.
+///
+/// ECDSA keys were truncated to 32 bytes and cannot be recovered. DIDs left
+/// without an authentication key are deactivated; their controller can
+/// reactivate them and add a new key.
+pub mod v2 {
+    use super::*;
+    use v1::{DidDocumentV1, PublicKeyEntryV1};
+
+    /// DID documents keyed by account, until version 7
+    #[frame::storage_alias]
+    pub type DidDocuments<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        DidDocument<T>,
+    >;
+
+    pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+    impl<T: Config> InnerMigrateV1ToV2<T> {
+        /// ECDSA keys never fit the old 32-byte field, so they cannot be carried over
+        fn migrate_key(old: PublicKeyEntryV1) -> Option<PublicKeyEntry> {
+            let public_key = match old.key_type {
+                KeyType::Ed25519 => PublicKey::Ed25519(old.public_key),
+                KeyType::Sr25519 => PublicKey::Sr25519(old.public_key),
+                KeyType::X25519 => PublicKey::X25519(old.public_key),
+                KeyType::Ecdsa | KeyType::P256 => {
+                    frame::log::warn!(
+                        target: "runtime::did",
+                        "dropping unrepresentable {:?} key {:?}",
+                        old.key_type,
+                        old.key_id,
+                    );
+                    return None;
+                }
+            };
+
+            // Version 1 gave every key every relationship, but X25519 keys cannot sign
+            let relationships = if public_key.can_sign() {
+                old.relationships
+            } else {
+                BoundedVec::truncate_from(vec![VerificationRelationship::KeyAgreement])
+            };
+
+            Some(PublicKeyEntry {
+                key_id: old.key_id,
+                public_key,
+                relationships,
+            })
+        }
+
+        /// Whether a migrated document still has a key that can authenticate it
+        fn can_authenticate(did_doc: &DidDocument<T>) -> bool {
+            did_doc
+                .public_keys
+                .iter()
+                .any(|k| k.has_relationship(VerificationRelationship::Authentication))
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            DidDocuments::<T>::translate::<DidDocumentV1<T>, _>(|account, old| {
+                translated.saturating_inc();
+
+                let public_keys = old
+                    .public_keys
+                    .into_iter()
+                    .filter_map(Self::migrate_key)
+                    .collect::<Vec<_>>();
+
+                let mut did_doc = DidDocument {
+                    controller: old.controller,
+                    public_keys: BoundedVec::truncate_from(public_keys),
+                    created_at: old.created_at,
+                    updated_at: old.updated_at,
+                    active: old.active,
+                };
+
+                if did_doc.active && !Self::can_authenticate(&did_doc) {
+                    frame::log::warn!(
+                        target: "runtime::did",
+                        "deactivating DID {:?}, left without an authentication key",
+                        account,
+                    );
+                    did_doc.active = false;
+                }
+
+                Some(did_doc)
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let mut documents = 0u64;
+            let mut kept = 0u64;
+            let mut dropped = 0u64;
+            let mut emptied = 0u64;
+
+            for did_doc in v1::DidDocuments::<T>::iter_values() {
+                documents.saturating_inc();
+                let keys = did_doc.public_keys.len() as u64;
+                let representable = did_doc
+                    .public_keys
+                    .into_iter()
+                    .filter(|key| !matches!(key.key_type, KeyType::Ecdsa | KeyType::P256))
+                    .count() as u64;
+                kept.saturating_accrue(representable);
+                dropped.saturating_accrue(keys.saturating_sub(representable));
+                if keys > 0 && representable == 0 {
+                    emptied.saturating_inc();
+                }
+            }
+
+            if dropped > 0 {
+                frame::log::warn!(
+                    target: "runtime::did",
+                    "v2 drops {} ECDSA or P-256 keys, leaving {} DIDs without keys to deactivate",
+                    dropped,
+                    emptied,
+                );
+            }
+
+            Ok((documents, kept).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let (documents, kept): (u64, u64) = decode_state(state)?;
+
+            let mut migrated = 0u64;
+            let mut keys = 0u64;
+            for did_doc in DidDocuments::<T>::iter_values() {
+                migrated.saturating_inc();
+                keys.saturating_accrue(did_doc.public_keys.len() as u64);
+                ensure!(
+                    !did_doc.active || Self::can_authenticate(&did_doc),
+                    "v2: active DID left without an authentication key"
+                );
+            }
+
+            ensure!(
+                migrated == documents,
+                "v2: DID documents lost in translation"
+            );
+            ensure!(
+                keys == kept,
+                "v2: keys other than ECDSA and P-256 keys were dropped"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 1 to version 2
+    pub type MigrateV1ToV2<T> = VersionedMigration<
+        1,
+        2,
+        InnerMigrateV1ToV2<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Builds the `DidByKeyId` reverse index from the signing keys of existing DID
+/// documents.
+pub mod v3 {
+    use super::*;
+    use v2::DidDocuments;
+
+    /// Key index pointing at accounts, until version 7
+    #[frame::storage_alias]
+    pub type DidByKeyId<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        [u8; 32],
+        <T as frame_system::Config>::AccountId,
+    >;
+
+    pub struct InnerMigrateV2ToV3<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV2ToV3<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            for (did, did_doc) in DidDocuments::<T>::iter() {
+                reads.saturating_inc();
+
+                // Key-agreement keys were added without proof and are not indexed
+                for key in did_doc
+                    .public_keys
+                    .iter()
+                    .filter(|k| k.public_key.can_sign())
+                {
+                    reads.saturating_inc();
+
+                    // Keys were not unique across DIDs before; the first holder keeps it
+                    if DidByKeyId::<T>::contains_key(key.key_id) {
+                        frame::log::warn!(
+                            target: "runtime::did",
+                            "key {:?} is shared by several DIDs, not indexing it for {:?}",
+                            key.key_id,
+                            did,
+                        );
+                        continue;
+                    }
+
+                    DidByKeyId::<T>::insert(key.key_id, &did);
+                    writes.saturating_inc();
+                }
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// Migrate storage from version 2 to version 3
+    pub type MigrateV2ToV3<T> = VersionedMigration<
+        2,
+        3,
+        InnerMigrateV2ToV3<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Records which accreditation body verified each institution.
+pub mod v4 {
+    use super::*;
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct OldInstitution<T: Config> {
+        pub name: BoundedVec<u8, T::MaxDocumentSize>,
+        pub did: T::AccountId,
+        pub verified: bool,
+        pub registered_at: BlockNumberFor<T>,
+    }
+
+    /// Institution layout at storage version 4
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct InstitutionV4<T: Config> {
+        pub name: BoundedVec<u8, T::MaxDocumentSize>,
+        pub did: T::AccountId,
+        pub verified: bool,
+        pub registered_at: BlockNumberFor<T>,
+        pub accreditor: Option<T::AccountId>,
+    }
+
+    #[frame::storage_alias]
+    pub type Institutions<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        InstitutionV4<T>,
+    >;
+
+    pub struct InnerMigrateV3ToV4<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV3ToV4<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            // Every existing verification was granted by governance
+            Institutions::<T>::translate::<OldInstitution<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                Some(InstitutionV4 {
+                    name: old.name,
+                    did: old.did,
+                    verified: old.verified,
+                    registered_at: old.registered_at,
+                    accreditor: None,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((Institutions::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let institutions: u64 = decode_state(state)?;
+            ensure!(
+                Institutions::<T>::iter_values().count() as u64 == institutions,
+                "v4: institutions lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 3 to version 4
+    pub type MigrateV3ToV4<T> = VersionedMigration<
+        3,
+        4,
+        InnerMigrateV3ToV4<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Gives verified institutions a `verified_until`, starting a full
+/// `VerificationPeriod` at the upgrade.
+pub mod v5 {
+    use super::*;
+    use v4::InstitutionV4;
+
+    /// Institution layout from storage version 5 until version 7
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct InstitutionV5<T: Config> {
+        pub name: BoundedVec<u8, T::MaxDocumentSize>,
+        pub did: T::AccountId,
+        pub verified: bool,
+        pub registered_at: BlockNumberFor<T>,
+        pub accreditor: Option<T::AccountId>,
+        pub verified_until: Option<BlockNumberFor<T>>,
+    }
+
+    #[frame::storage_alias]
+    pub type Institutions<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        InstitutionV5<T>,
+    >;
+
+    #[frame::storage_alias]
+    pub type VerificationExpiries<T: Config> = StorageDoubleMap<
+        pallet::Pallet<T>,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        (),
+    >;
+
+    pub struct InnerMigrateV4ToV5<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV4ToV5<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+            let mut scheduled = 0u64;
+            let start = frame_system::Pallet::<T>::block_number()
+                .saturating_add(T::VerificationPeriod::get());
+            let per_block = u64::from(T::MaxLapsesPerBlock::get().max(1));
+
+            Institutions::<T>::translate::<InstitutionV4<T>, _>(|did, old| {
+                translated.saturating_inc();
+
+                // Spread the lapses so no block has more than it can process
+                let verified_until = old.verified.then(|| {
+                    let until = start
+                        .saturating_add(BlockNumberFor::<T>::saturated_from(scheduled / per_block));
+                    scheduled.saturating_inc();
+                    VerificationExpiries::<T>::insert(until, &did, ());
+                    until
+                });
+
+                Some(InstitutionV5 {
+                    name: old.name,
+                    did: old.did,
+                    verified: old.verified,
+                    registered_at: old.registered_at,
+                    accreditor: old.accreditor,
+                    verified_until,
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated.saturating_add(scheduled))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let mut institutions = 0u64;
+            let mut verified = 0u64;
+            for institution in v4::Institutions::<T>::iter_values() {
+                institutions.saturating_inc();
+                if institution.verified {
+                    verified.saturating_inc();
+                }
+            }
+
+            Ok((institutions, verified).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let (institutions, verified): (u64, u64) = decode_state(state)?;
+
+            let mut migrated = 0u64;
+            for institution in Institutions::<T>::iter_values() {
+                migrated.saturating_inc();
+                ensure!(
+                    institution.verified == institution.verified_until.is_some(),
+                    "v5: verified institution without a verification expiry"
+                );
+            }
+            ensure!(
+                migrated == institutions,
+                "v5: institutions lost in translation"
+            );
+
+            let mut per_block = alloc::collections::BTreeMap::<BlockNumberFor<T>, u32>::new();
+            for (until, _) in VerificationExpiries::<T>::iter_keys() {
+                per_block.entry(until).or_default().saturating_inc();
+            }
+            ensure!(
+                per_block
+                    .values()
+                    .map(|lapses| u64::from(*lapses))
+                    .sum::<u64>()
+                    == verified,
+                "v5: verification expiry not queued for every verified institution"
+            );
+            ensure!(
+                per_block
+                    .values()
+                    .all(|lapses| *lapses <= T::MaxLapsesPerBlock::get().max(1)),
+                "v5: more lapses queued in a block than it can process"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 4 to version 5
+    pub type MigrateV4ToV5<T> = VersionedMigration<
+        4,
+        5,
+        InnerMigrateV4ToV5<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Builds the `InstitutionNames` index from verified institutions.
+pub mod v6 {
+    use super::*;
+    use v5::Institutions;
+
+    /// Name index pointing at accounts, until version 7
+    #[frame::storage_alias]
+    pub type InstitutionNames<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        [u8; 32],
+        <T as frame_system::Config>::AccountId,
+    >;
+
+    pub struct InnerMigrateV5ToV6<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV5ToV6<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut reads = 0u64;
+            let mut writes = 0u64;
+
+            for (did, institution) in Institutions::<T>::iter() {
+                reads.saturating_inc();
+                if !institution.verified {
+                    continue;
+                }
+
+                // Names were not unique before; the first institution keeps it
+                let name_hash = pallet::Pallet::<T>::institution_name_hash(&institution.name);
+                reads.saturating_inc();
+                if InstitutionNames::<T>::contains_key(name_hash) {
+                    frame::log::warn!(
+                        target: "runtime::did",
+                        "institution name of {:?} is already taken, not indexing it",
+                        did,
+                    );
+                    continue;
+                }
+
+                InstitutionNames::<T>::insert(name_hash, &did);
+                writes.saturating_inc();
+            }
+
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+    }
+
+    /// Migrate storage from version 5 to version 6
+    pub type MigrateV5ToV6<T> = VersionedMigration<
+        5,
+        6,
+        InnerMigrateV5ToV6<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Keys every DID by a `DidIdentifier` derived from the account it was keyed by,
+/// and links that account to it.
+///
+/// Every entry is moved in the upgrade block; `pre_upgrade` checks that they
+/// fit in it.
+pub mod v7 {
+    use super::*;
+    use v2::DidDocuments;
+    use v3::DidByKeyId;
+    use v5::{Institutions, VerificationExpiries};
+    use v6::InstitutionNames;
+
+    #[frame::storage_alias]
+    pub type ServiceEndpoints<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<ServiceEndpoint<T>, <T as Config>::MaxServiceEndpoints>,
+    >;
+
+    #[frame::storage_alias]
+    pub type DidDeposits<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Deposit<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+    >;
+
+    #[frame::storage_alias]
+    pub type InstitutionDeposits<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Deposit<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+    >;
+
+    #[frame::storage_alias]
+    pub type RecoveryConfigs<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        RecoveryConfig<T>,
+    >;
+
+    /// Recovery layout until storage version 11
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct ActiveRecoveryV7<T: Config> {
+        pub new_account: T::AccountId,
+        pub vouchers: BoundedVec<T::AccountId, T::MaxGuardians>,
+        pub approved_at: Option<BlockNumberFor<T>>,
+    }
+
+    #[frame::storage_alias]
+    pub type ActiveRecoveries<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        ActiveRecoveryV7<T>,
+    >;
+
+    #[frame::storage_alias]
+    pub type AccreditationBodies<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        AccreditationBody<T>,
+    >;
+
+    #[frame::storage_alias]
+    pub type AccreditedInstitutions<T: Config> = StorageDoubleMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        (),
+    >;
+
+    /// Institution profile layout until storage version 9
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    pub struct InstitutionProfileV7 {
+        pub country: Option<[u8; 2]>,
+        pub legal_identifier: Option<ProfileField>,
+        pub website: Option<ProfileField>,
+        pub logo_hash: Option<[u8; 32]>,
+        pub accreditations: BoundedVec<ProfileField, crate::MaxAccreditationReferences>,
+    }
+
+    #[frame::storage_alias]
+    pub type InstitutionProfiles<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        InstitutionProfileV7,
+    >;
+
+    #[frame::storage_alias]
+    pub type ClosedInstitutions<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BlockNumberFor<T>,
+    >;
+
+    #[frame::storage_alias]
+    pub type Successors<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::AccountId,
+    >;
+
+    #[frame::storage_alias]
+    pub type Tombstones<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BlockNumberFor<T>,
+    >;
+
+    #[frame::storage_alias]
+    pub type KeyNonces<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        u64,
+    >;
+
+    #[frame::storage_alias]
+    pub type PendingControllers<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        <T as frame_system::Config>::AccountId,
+    >;
+
+    pub struct InnerMigrateV6ToV7<T>(PhantomData<T>);
+
+    impl<T: Config> InnerMigrateV6ToV7<T> {
+        fn did_of(account: T::AccountId) -> T::DidIdentifier {
+            T::DidIdentifierOf::convert(account)
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV6ToV7<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut moved = 0u64;
+
+            // Each map is drained before re-inserting, as an identifier may encode
+            // to the same key as the account it replaces.
+            let documents = DidDocuments::<T>::drain().collect::<Vec<_>>();
+            for (account, did_doc) in documents {
+                let did = Self::did_of(account.clone());
+                crate::DidDocuments::<T>::insert(&did, did_doc);
+                LinkedAccounts::<T>::insert(&did, BoundedVec::truncate_from(vec![account.clone()]));
+                AccountDids::<T>::insert(account, did);
+                moved.saturating_accrue(3);
+            }
+
+            let institutions = Institutions::<T>::drain().collect::<Vec<_>>();
+            for (account, old) in institutions {
+                crate::Institutions::<T>::insert(
+                    Self::did_of(account),
+                    Institution {
+                        name: old.name,
+                        did: Self::did_of(old.did),
+                        verified: old.verified,
+                        registered_at: old.registered_at,
+                        accreditor: old.accreditor.map(Self::did_of),
+                        verified_until: old.verified_until,
+                    },
+                );
+                moved.saturating_inc();
+            }
+
+            let services = ServiceEndpoints::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in services {
+                crate::ServiceEndpoints::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let deposits = DidDeposits::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in deposits {
+                crate::DidDeposits::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let deposits = InstitutionDeposits::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in deposits {
+                crate::InstitutionDeposits::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let configs = RecoveryConfigs::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in configs {
+                crate::RecoveryConfigs::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let recoveries = ActiveRecoveries::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in recoveries {
+                v11::ActiveRecoveries::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let bodies = AccreditationBodies::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in bodies {
+                crate::AccreditationBodies::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let accredited = AccreditedInstitutions::<T>::drain().collect::<Vec<_>>();
+            for (body, institution, ()) in accredited {
+                crate::AccreditedInstitutions::<T>::insert(
+                    Self::did_of(body),
+                    Self::did_of(institution),
+                    (),
+                );
+                moved.saturating_inc();
+            }
+
+            let profiles = InstitutionProfiles::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in profiles {
+                v9::InstitutionProfiles::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let expiries = VerificationExpiries::<T>::drain().collect::<Vec<_>>();
+            for (block, account, ()) in expiries {
+                crate::VerificationExpiries::<T>::insert(block, Self::did_of(account), ());
+                moved.saturating_inc();
+            }
+
+            let names = InstitutionNames::<T>::drain().collect::<Vec<_>>();
+            for (name_hash, account) in names {
+                crate::InstitutionNames::<T>::insert(name_hash, Self::did_of(account));
+                moved.saturating_inc();
+            }
+
+            let keys = DidByKeyId::<T>::drain().collect::<Vec<_>>();
+            for (key_id, account) in keys {
+                crate::DidByKeyId::<T>::insert(key_id, Self::did_of(account));
+                moved.saturating_inc();
+            }
+
+            let closed = ClosedInstitutions::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in closed {
+                crate::ClosedInstitutions::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let successors = Successors::<T>::drain().collect::<Vec<_>>();
+            for (account, successor) in successors {
+                crate::Successors::<T>::insert(Self::did_of(account), Self::did_of(successor));
+                moved.saturating_inc();
+            }
+
+            let tombstones = Tombstones::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in tombstones {
+                crate::Tombstones::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let nonces = KeyNonces::<T>::drain().collect::<Vec<_>>();
+            for (account, value) in nonces {
+                crate::KeyNonces::<T>::insert(Self::did_of(account), value);
+                moved.saturating_inc();
+            }
+
+            let pending = PendingControllers::<T>::drain().collect::<Vec<_>>();
+            for (account, controller) in pending {
+                crate::PendingControllers::<T>::insert(Self::did_of(account), controller);
+                moved.saturating_inc();
+            }
+
+            T::DbWeight::get().reads_writes(moved, moved.saturating_mul(2))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let counts: Vec<u64> = vec![
+                DidDocuments::<T>::iter_keys().count() as u64,
+                Institutions::<T>::iter_keys().count() as u64,
+                ServiceEndpoints::<T>::iter_keys().count() as u64,
+                DidDeposits::<T>::iter_keys().count() as u64,
+                InstitutionDeposits::<T>::iter_keys().count() as u64,
+                RecoveryConfigs::<T>::iter_keys().count() as u64,
+                ActiveRecoveries::<T>::iter_keys().count() as u64,
+                AccreditationBodies::<T>::iter_keys().count() as u64,
+                AccreditedInstitutions::<T>::iter_keys().count() as u64,
+                InstitutionProfiles::<T>::iter_keys().count() as u64,
+                VerificationExpiries::<T>::iter_keys().count() as u64,
+                InstitutionNames::<T>::iter_keys().count() as u64,
+                DidByKeyId::<T>::iter_keys().count() as u64,
+                ClosedInstitutions::<T>::iter_keys().count() as u64,
+                Successors::<T>::iter_keys().count() as u64,
+                Tombstones::<T>::iter_keys().count() as u64,
+                KeyNonces::<T>::iter_keys().count() as u64,
+                PendingControllers::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                fits_in_block::<T, pallet::Pallet<T>>(counts.iter().sum()),
+                "v7: too many entries to re-key in one block"
+            );
+
+            Ok(counts.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before: Vec<u64> = decode_state(state)?;
+
+            // Two accounts deriving the same identifier would collapse entries
+            let after: Vec<u64> = vec![
+                crate::DidDocuments::<T>::iter_keys().count() as u64,
+                crate::Institutions::<T>::iter_keys().count() as u64,
+                crate::ServiceEndpoints::<T>::iter_keys().count() as u64,
+                crate::DidDeposits::<T>::iter_keys().count() as u64,
+                crate::InstitutionDeposits::<T>::iter_keys().count() as u64,
+                crate::RecoveryConfigs::<T>::iter_keys().count() as u64,
+                v11::ActiveRecoveries::<T>::iter_keys().count() as u64,
+                crate::AccreditationBodies::<T>::iter_keys().count() as u64,
+                crate::AccreditedInstitutions::<T>::iter_keys().count() as u64,
+                v9::InstitutionProfiles::<T>::iter_keys().count() as u64,
+                crate::VerificationExpiries::<T>::iter_keys().count() as u64,
+                crate::InstitutionNames::<T>::iter_keys().count() as u64,
+                crate::DidByKeyId::<T>::iter_keys().count() as u64,
+                crate::ClosedInstitutions::<T>::iter_keys().count() as u64,
+                crate::Successors::<T>::iter_keys().count() as u64,
+                crate::Tombstones::<T>::iter_keys().count() as u64,
+                crate::KeyNonces::<T>::iter_keys().count() as u64,
+                crate::PendingControllers::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                after == before,
+                "v7: entries lost re-keying by DID identifier"
+            );
+
+            for did in crate::DidDocuments::<T>::iter_keys() {
+                ensure!(
+                    LinkedAccounts::<T>::get(&did)
+                        .iter()
+                        .all(|account| AccountDids::<T>::get(account).as_ref() == Some(&did)),
+                    "v7: account not linked to the DID derived from it"
+                );
+            }
+            ensure!(
+                AccountDids::<T>::iter_keys().count() as u64 == before[0],
+                "v7: not every account keying a DID was linked to it"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 6 to version 7
+    pub type MigrateV6ToV7<T> = VersionedMigration<
+        6,
+        7,
+        InnerMigrateV6ToV7<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Starts the key history of every DID from its current keys.
+pub mod v8 {
+    use super::*;
+    use crate::{DidDocuments, KeyHistories, KeyHistory, KeyRecord};
+
+    pub struct InnerMigrateV7ToV8<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV7ToV8<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut migrated = 0u64;
+
+            for (did, did_doc) in DidDocuments::<T>::iter() {
+                migrated.saturating_inc();
+
+                // A document never updated still has its initial keys; otherwise
+                // when the current keys were added is unknown
+                let since = if did_doc.updated_at == did_doc.created_at {
+                    did_doc.created_at
+                } else {
+                    now
+                };
+                let records = did_doc
+                    .public_keys
+                    .into_iter()
+                    .map(|key| KeyRecord {
+                        key,
+                        valid_from: since,
+                        valid_until: None,
+                    })
+                    .collect::<Vec<_>>();
+
+                KeyHistories::<T>::insert(
+                    &did,
+                    KeyHistory {
+                        records: BoundedVec::truncate_from(records),
+                        complete_from: since,
+                    },
+                );
+            }
+
+            T::DbWeight::get().reads_writes(migrated, migrated)
+        }
+    }
+
+    /// Migrate storage from version 7 to version 8
+    pub type MigrateV7ToV8<T> = VersionedMigration<
+        7,
+        8,
+        InnerMigrateV7ToV8<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Adds the sectors an institution offers to its profile.
+pub mod v9 {
+    use super::*;
+    use v7::InstitutionProfileV7;
+
+    /// Profiles keyed by DID, until version 9
+    #[frame::storage_alias]
+    pub type InstitutionProfiles<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as Config>::DidIdentifier,
+        InstitutionProfileV7,
+    >;
+
+    pub struct InnerMigrateV8ToV9<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV8ToV9<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            crate::InstitutionProfiles::<T>::translate::<InstitutionProfileV7, _>(|_, old| {
+                translated.saturating_inc();
+                Some(InstitutionProfile {
+                    country: old.country,
+                    legal_identifier: old.legal_identifier,
+                    website: old.website,
+                    logo_hash: old.logo_hash,
+                    accreditations: old.accreditations,
+                    sectors: BoundedVec::new(),
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            Ok((InstitutionProfiles::<T>::iter_keys().count() as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let profiles: u64 = decode_state(state)?;
+            ensure!(
+                crate::InstitutionProfiles::<T>::iter_values().count() as u64 == profiles,
+                "v9: institution profiles lost in translation"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 8 to version 9
+    pub type MigrateV8ToV9<T> = VersionedMigration<
+        8,
+        9,
+        InnerMigrateV8ToV9<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Starts the verification expiry cursor at the current block.
+pub mod v10 {
+    use super::*;
+    use crate::VerificationExpiryCursor;
+
+    pub struct InnerMigrateV9ToV10<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV9ToV10<T> {
+        fn on_runtime_upgrade() -> Weight {
+            // Every earlier lapse was processed in the block it was due
+            VerificationExpiryCursor::<T>::put(frame_system::Pallet::<T>::block_number());
+
+            T::DbWeight::get().reads_writes(1, 1)
+        }
+    }
+
+    /// Migrate storage from version 9 to version 10
+    pub type MigrateV9ToV10<T> = VersionedMigration<
+        9,
+        10,
+        InnerMigrateV9ToV10<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Counts recovery vouches per account, so guardians can vouch for different
+/// accounts.
+pub mod v11 {
+    use super::*;
+    use v7::ActiveRecoveryV7;
+
+    /// Recoveries keyed by DID, until version 11
+    #[frame::storage_alias]
+    pub type ActiveRecoveries<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as Config>::DidIdentifier,
+        ActiveRecoveryV7<T>,
+    >;
+
+    pub struct InnerMigrateV10ToV11<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV10ToV11<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut translated = 0u64;
+
+            crate::ActiveRecoveries::<T>::translate::<ActiveRecoveryV7<T>, _>(|_, old| {
+                translated.saturating_inc();
+
+                let vouches = old
+                    .vouchers
+                    .into_iter()
+                    .map(|guardian| (guardian, old.new_account.clone()))
+                    .collect::<Vec<_>>();
+                Some(ActiveRecovery {
+                    vouches: BoundedVec::truncate_from(vouches),
+                    approved: old.approved_at.map(|at| (old.new_account, at)),
+                })
+            });
+
+            T::DbWeight::get().reads_writes(translated, translated)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let mut recoveries = 0u64;
+            let mut vouches = 0u64;
+            for recovery in ActiveRecoveries::<T>::iter_values() {
+                recoveries.saturating_inc();
+                vouches.saturating_accrue(recovery.vouchers.len() as u64);
+            }
+
+            Ok((recoveries, vouches).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let (recoveries, vouches): (u64, u64) = decode_state(state)?;
+
+            let mut migrated = 0u64;
+            let mut migrated_vouches = 0u64;
+            for recovery in crate::ActiveRecoveries::<T>::iter_values() {
+                migrated.saturating_inc();
+                migrated_vouches.saturating_accrue(recovery.vouches.len() as u64);
+            }
+
+            ensure!(
+                migrated == recoveries,
+                "v11: recoveries lost in translation"
+            );
+            ensure!(migrated_vouches == vouches, "v11: recovery vouches lost");
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 10 to version 11
+    pub type MigrateV10ToV11<T> = VersionedMigration<
+        10,
+        11,
+        InnerMigrateV10ToV11<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Indexes the institutions each institution succeeds.
+pub mod v12 {
+    use super::*;
+    use crate::{Predecessors, Successors};
+
+    pub struct InnerMigrateV11ToV12<T>(PhantomData<T>);
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV11ToV12<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut indexed = 0u64;
+
+            for (institution, successor) in Successors::<T>::iter() {
+                Predecessors::<T>::insert(successor, institution, ());
+                indexed.saturating_inc();
+            }
+
+            T::DbWeight::get().reads_writes(indexed, indexed)
+        }
+    }
+
+    /// Migrate storage from version 11 to version 12
+    pub type MigrateV11ToV12<T> = VersionedMigration<
+        11,
+        12,
+        InnerMigrateV11ToV12<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -1,5 +1,13 @@
 use crate as did;
-use frame::{prelude::*, runtime::prelude::*, testing_prelude::*};
+use frame::{
+    deps::{
+        sp_core::{ecdsa, ed25519, sr25519, Pair},
+        sp_runtime::traits::Identity,
+    },
+    prelude::*,
+    runtime::prelude::*,
+    testing_prelude::*,
+};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -24,6 +32,9 @@ mod runtime {
     pub type System = frame_system;
 
     #[runtime::pallet_index(1)]
+    pub type Balances = pallet_balances;
+
+    #[runtime::pallet_index(2)]
     pub type Did = did;
 }
 
@@ -31,25 +42,203 @@ mod runtime {
 #[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
 impl frame_system::Config for Test {
     type Block = Block;
+    type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type AccountStore = System;
 }
 
 // DID pallet configuration
 parameter_types! {
     pub const MaxDocumentSize: u32 = 1024;
     pub const MaxPublicKeys: u32 = 10;
+    pub const MaxKeyHistory: u32 = 12;
+    pub const MaxCompromisedKeys: u32 = 4;
+    pub const MaxServiceEndpoints: u32 = 8;
+    pub const MaxUrlLength: u32 = 256;
+    pub const DidDeposit: u64 = 100;
+    pub const ByteDeposit: u64 = 1;
+    pub const MaxGuardians: u32 = 10;
+    pub const MaxLinkedAccounts: u32 = 4;
+    pub const RecoveryDelay: u64 = 10;
+    pub const VerificationPeriod: u64 = 1_000;
+    pub const MaxLapsesPerBlock: u32 = 2;
+}
+
+ord_parameter_types! {
+    pub const AccreditationCouncil: u64 = 99;
 }
 
 impl did::Config for Test {
     type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type DidIdentifier = u64;
+    type DidIdentifierOf = Identity;
+    type MaxLinkedAccounts = MaxLinkedAccounts;
     type MaxDocumentSize = MaxDocumentSize;
     type MaxPublicKeys = MaxPublicKeys;
+    type MaxKeyHistory = MaxKeyHistory;
+    type MaxCompromisedKeys = MaxCompromisedKeys;
+    type MaxServiceEndpoints = MaxServiceEndpoints;
+    type MaxUrlLength = MaxUrlLength;
+    type Currency = Balances;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type DidDeposit = DidDeposit;
+    type ByteDeposit = ByteDeposit;
+    type IssuedCredentials = ();
+    type MaxGuardians = MaxGuardians;
+    type RecoveryDelay = RecoveryDelay;
+    type VerifyOrigin = EnsureSignedBy<AccreditationCouncil, u64>;
+    type RevokeOrigin = EnsureSignedBy<AccreditationCouncil, u64>;
+    type VerificationPeriod = VerificationPeriod;
+    type MaxLapsesPerBlock = MaxLapsesPerBlock;
     type WeightInfo = did::weights::SubstrateWeight;
 }
 
+/// Balance given to each test account at genesis
+pub const INITIAL_BALANCE: u64 = 1_000_000;
+
 // Test externalities initialization
 pub fn new_test_ext() -> TestExternalities {
-    frame_system::GenesisConfig::<Test>::default()
+    let mut t = frame_system::GenesisConfig::<Test>::default()
         .build_storage()
-        .unwrap()
-        .into()
+        .unwrap();
+
+    pallet_balances::GenesisConfig::<Test> {
+        balances: (1..=20).map(|account| (account, INITIAL_BALANCE)).collect(),
+        ..Default::default()
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    t.into()
+}
+
+/// Origin of the governance body deciding institution accreditation
+pub fn council() -> RuntimeOrigin {
+    RuntimeOrigin::signed(AccreditationCouncil::get())
+}
+
+// Key helpers: `seed` deterministically derives a key pair of the given type
+
+fn p256_signing_key(seed: [u8; 32]) -> p256::ecdsa::SigningKey {
+    p256::ecdsa::SigningKey::from_slice(&seed).unwrap()
+}
+
+/// Public key as stored on-chain for the key derived from `seed`
+///
+/// X25519 keys cannot sign, so the seed is used as the key material directly.
+pub fn test_public_key(seed: [u8; 32], key_type: did::KeyType) -> did::PublicKey {
+    match key_type {
+        did::KeyType::Ed25519 => {
+            did::PublicKey::Ed25519(ed25519::Pair::from_seed(&seed).public().0)
+        }
+        did::KeyType::Sr25519 => {
+            did::PublicKey::Sr25519(sr25519::Pair::from_seed(&seed).public().0)
+        }
+        did::KeyType::Ecdsa => did::PublicKey::Ecdsa(ecdsa::Pair::from_seed(&seed).public().0),
+        did::KeyType::X25519 => did::PublicKey::X25519(seed),
+        did::KeyType::P256 => {
+            let point = p256_signing_key(seed)
+                .verifying_key()
+                .to_encoded_point(true);
+            did::PublicKey::P256(point.as_bytes().try_into().unwrap())
+        }
+    }
+}
+
+/// Key id of the key derived from `seed`
+pub fn test_key_id(seed: [u8; 32], key_type: did::KeyType) -> [u8; 32] {
+    test_public_key(seed, key_type).key_id()
+}
+
+/// Proof of possession of the key derived from `seed` for the next key added to `did`
+pub fn test_key_proof(did: u64, seed: [u8; 32], key_type: did::KeyType) -> did::KeyProof {
+    let payload = Did::key_possession_payload(&did);
+    match key_type {
+        did::KeyType::Ed25519 => ed25519::Pair::from_seed(&seed).sign(&payload).into(),
+        did::KeyType::Sr25519 => sr25519::Pair::from_seed(&seed).sign(&payload).into(),
+        did::KeyType::Ecdsa => ecdsa::Pair::from_seed(&seed).sign(&payload).into(),
+        did::KeyType::X25519 => did::KeyProof::None,
+        did::KeyType::P256 => {
+            use p256::ecdsa::{signature::Signer, Signature};
+            let signature: Signature = p256_signing_key(seed).sign(&payload);
+            did::KeyProof::P256(signature.to_bytes().into())
+        }
+    }
+}
+
+/// Create a DID for `account` whose initial key is derived from `seed`
+pub fn create_did_with_key(account: u64, seed: [u8; 32], key_type: did::KeyType) -> DispatchResult {
+    Did::create_did(
+        RuntimeOrigin::signed(account),
+        test_public_key(seed, key_type),
+        test_key_proof(account, seed, key_type),
+    )
+}
+
+/// Add the key derived from `seed` to `did`, signed by `who`
+pub fn add_key(who: u64, did: u64, seed: [u8; 32], key_type: did::KeyType) -> DispatchResult {
+    Did::add_public_key(
+        RuntimeOrigin::signed(who),
+        did,
+        test_public_key(seed, key_type),
+        test_key_proof(did, seed, key_type),
+    )
+}
+
+/// Advertise a `LinkedDomains` service with the given id on `did`
+pub fn add_service(who: u64, did: u64, id: &[u8], url: &[u8]) -> DispatchResult {
+    Did::add_service_endpoint(
+        RuntimeOrigin::signed(who),
+        did,
+        id.to_vec().try_into().unwrap(),
+        b"LinkedDomains".to_vec().try_into().unwrap(),
+        url.to_vec().try_into().unwrap(),
+    )
+}
+
+/// Signature by the Ed25519 key derived from `seed` authorising `call` for `did`
+pub fn sign_did_call(
+    did: u64,
+    call: &RuntimeCall,
+    nonce: u64,
+    submitter: u64,
+    seed: [u8; 32],
+) -> did::KeyProof {
+    let payload = Did::did_call_payload(&did, call, nonce, &submitter);
+    ed25519::Pair::from_seed(&seed).sign(&payload).into()
+}
+
+/// Submit `call` for `did` from `submitter`, signed with the DID's Ed25519 key derived from `seed`
+pub fn submit_did_call(
+    submitter: u64,
+    did: u64,
+    call: RuntimeCall,
+    seed: [u8; 32],
+) -> DispatchResultWithPostInfo {
+    let nonce = Did::did_call_nonces(did);
+    let signature = sign_did_call(did, &call, nonce, submitter, seed);
+    Did::submit_did_call(
+        RuntimeOrigin::signed(submitter),
+        did,
+        Box::new(call),
+        nonce,
+        test_key_id(seed, did::KeyType::Ed25519),
+        signature,
+    )
+}
+
+/// Link `account` to `did`, consented to by the DID's Ed25519 key derived from `seed`
+pub fn link_account(account: u64, did: u64, seed: [u8; 32]) -> DispatchResult {
+    let payload = Did::account_link_payload(&did, &account);
+    Did::link_account(
+        RuntimeOrigin::signed(account),
+        did,
+        test_key_id(seed, did::KeyType::Ed25519),
+        ed25519::Pair::from_seed(&seed).sign(&payload).into(),
+    )
 }
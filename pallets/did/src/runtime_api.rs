@@ -0,0 +1,76 @@
+// pallets/did/src/runtime_api.rs
+//! Runtime API for resolving DID documents off-chain.
+
+use crate::{PublicKey, PublicKeyEntry};
+use codec::Codec;
+use frame::prelude::*;
+
+/// Service endpoint as returned by DID resolution
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ResolvedService {
+    pub id: Vec<u8>,
+    pub service_type: Vec<u8>,
+    pub url: Vec<u8>,
+}
+
+/// Everything needed to render a W3C DID document for a DID subject
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct DidResolution<DidIdentifier, AccountId, BlockNumber> {
+    pub did: DidIdentifier,
+    pub controller: AccountId,
+    pub public_keys: Vec<PublicKeyEntry>,
+    pub services: Vec<ResolvedService>,
+    pub created_at: BlockNumber,
+    pub updated_at: BlockNumber,
+    pub active: bool,
+}
+
+/// Whether a signature made by a DID key at some block can be trusted
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum KeyStatus<BlockNumber> {
+    /// The key was part of the DID document and not compromised
+    Valid,
+    /// The key was reported compromised as of `since`
+    Compromised { since: BlockNumber },
+    /// The key was not part of the DID document
+    NotInDocument,
+    /// The DID's key history does not reach back to the block
+    Unknown,
+}
+
+frame::deps::sp_api::decl_runtime_apis! {
+    /// Resolve DID documents from chain state
+    ///
+    /// Resolving at a historical block is done by calling the API at that
+    /// block's hash, while `did_document_at` answers from the key history kept
+    /// on-chain, without an archive node.
+    pub trait DidApi<DidIdentifier, AccountId, BlockNumber>
+    where
+        DidIdentifier: Codec,
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// Resolve `did`, or `None` if it has no DID document
+        fn resolve(did: DidIdentifier) -> Option<DidResolution<DidIdentifier, AccountId, BlockNumber>>;
+
+        /// DID that holds the signing key `public_key`, identifying the signer of
+        /// a signature
+        fn did_by_key(public_key: PublicKey) -> Option<DidIdentifier>;
+
+        /// Verified institution registered under `name`, compared ignoring case
+        /// and whitespace
+        fn institution_by_name(name: Vec<u8>) -> Option<DidIdentifier>;
+
+        /// DID that `account` is linked to and acts for
+        fn did_by_account(account: AccountId) -> Option<DidIdentifier>;
+
+        /// Keys `did` had at the end of `block`, to check that a key was valid
+        /// when it signed; `None` if the DID's key history does not reach back
+        /// that far
+        fn did_document_at(did: DidIdentifier, block: BlockNumber) -> Option<Vec<PublicKeyEntry>>;
+
+        /// Whether a credential or presentation signed by the key `key_id` of
+        /// `did` at `block` can be trusted
+        fn key_status_at(did: DidIdentifier, key_id: [u8; 32], block: BlockNumber) -> KeyStatus<BlockNumber>;
+    }
+}
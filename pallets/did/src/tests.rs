@@ -1,5 +1,12 @@
-use crate::{mock::*, DidDocuments, Error, Event, Institutions, KeyType};
-use frame::testing_prelude::*;
+use crate::{
+    mock::*, AccountDids, AccreditationBodies, AccreditationScope, AccreditedInstitutions,
+    ActiveRecoveries, ClosedInstitutions, CompromisedKeys, DidByKeyId, DidCallNonces, DidDeposits,
+    DidDocuments, Error, Event, HoldReason, InstitutionDeposits, InstitutionNames,
+    InstitutionProfile, InstitutionProfiles, Institutions, KeyHistories, KeyNonces, KeyType,
+    LinkedAccounts, PendingControllers, Predecessors, PublicKey, ServiceEndpoints, ServiceId,
+    Successors, Tombstones, VerificationExpiries, VerificationRelationship,
+};
+use frame::{testing_prelude::*, traits::fungible::InspectHold};
 
 //CREATE DID TESTS
 #[test]
@@ -11,11 +18,7 @@ fn create_did_works() {
         let public_key = [1u8; 32];
 
         //Create a DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         assert!(DidDocuments::<Test>::contains_key(account));
 
@@ -32,15 +35,11 @@ fn create_did_fails_if_already_exists() {
         let public_key = [1u8; 32];
 
         // Create first DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         // Try to create second DID for same account - should fail
         assert_noop!(
-            Did::create_did(RuntimeOrigin::signed(account), [2u8; 32], KeyType::Sr25519),
+            create_did_with_key(account, [2u8; 32], KeyType::Sr25519),
             Error::<Test>::DidAlreadyExists
         );
     });
@@ -54,19 +53,21 @@ fn create_did_stores_correct_data() {
         let account = 1u64;
         let public_key = [1u8; 32];
 
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         // Verify stored data
         let did_doc = DidDocuments::<Test>::get(account).unwrap();
         assert_eq!(did_doc.controller, account);
         assert_eq!(did_doc.public_keys.len(), 1);
-        assert_eq!(did_doc.public_keys[0].public_key, public_key);
-        assert_eq!(did_doc.public_keys[0].key_type, KeyType::Ed25519);
-        assert_eq!(did_doc.active, true);
+        assert_eq!(
+            did_doc.public_keys[0].public_key,
+            test_public_key(public_key, KeyType::Ed25519)
+        );
+        assert_eq!(
+            did_doc.public_keys[0].public_key.key_type(),
+            KeyType::Ed25519
+        );
+        assert!(did_doc.active);
         assert_eq!(did_doc.created_at, 1);
         assert_eq!(did_doc.updated_at, 1);
     });
@@ -82,20 +83,12 @@ fn add_public_key_works() {
         let second_key = [2u8; 32];
 
         // Create DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            first_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, first_key, KeyType::Ed25519));
 
         System::set_block_number(2);
 
         // Add second key
-        assert_ok!(Did::add_public_key(
-            RuntimeOrigin::signed(account),
-            second_key,
-            KeyType::Sr25519
-        ));
+        assert_ok!(add_key(account, account, second_key, KeyType::Sr25519));
 
         // Verify key was added
         let did_doc = DidDocuments::<Test>::get(account).unwrap();
@@ -114,7 +107,7 @@ fn add_public_key_fails_if_did_not_found() {
 
         // Try to add key without creating DID first
         assert_noop!(
-            Did::add_public_key(RuntimeOrigin::signed(account), public_key, KeyType::Ed25519),
+            add_key(account, account, public_key, KeyType::Ed25519),
             Error::<Test>::DidNotFound
         );
     });
@@ -130,18 +123,10 @@ fn add_public_key_fails_if_not_authorized() {
         let public_key = [1u8; 32];
 
         // Create DID with owner
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(owner),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(owner, public_key, KeyType::Ed25519));
 
         // Create DID for attacker as well
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(attacker),
-            [3u8; 32],
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(attacker, [3u8; 32], KeyType::Ed25519));
 
         // Manually modify the attacker's DID to point to owner's DID
         // This simulates an authorization attack scenario
@@ -154,7 +139,7 @@ fn add_public_key_fails_if_not_authorized() {
 
         // Now try to add key - attacker has a DID but is not the controller
         assert_noop!(
-            Did::add_public_key(RuntimeOrigin::signed(attacker), [2u8; 32], KeyType::Sr25519),
+            add_key(attacker, attacker, [2u8; 32], KeyType::Sr25519),
             Error::<Test>::NotAuthorized
         );
     });
@@ -169,17 +154,13 @@ fn add_public_key_fails_if_did_inactive() {
         let public_key = [1u8; 32];
 
         // Create and deactivate DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account), account));
 
         // Try to add key to inactive DID
         assert_noop!(
-            Did::add_public_key(RuntimeOrigin::signed(account), [2u8; 32], KeyType::Sr25519),
+            add_key(account, account, [2u8; 32], KeyType::Sr25519),
             Error::<Test>::DidInactive
         );
     });
@@ -194,15 +175,11 @@ fn add_public_key_fails_if_key_already_exists() {
         let public_key = [1u8; 32];
 
         // Create DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         // Try to add same key again
         assert_noop!(
-            Did::add_public_key(RuntimeOrigin::signed(account), public_key, KeyType::Ed25519),
+            add_key(account, account, public_key, KeyType::Ed25519),
             Error::<Test>::PublicKeyAlreadyExists
         );
     });
@@ -216,29 +193,259 @@ fn add_public_key_fails_if_max_keys_reached() {
         let account = 1u64;
 
         // Create DID with first key
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            [1u8; 32],
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
 
         // Add keys up to the limit (MaxPublicKeys = 10, already have 1)
         for i in 2u8..=10u8 {
-            assert_ok!(Did::add_public_key(
-                RuntimeOrigin::signed(account),
-                [i; 32],
-                KeyType::Ed25519
-            ));
+            assert_ok!(add_key(account, account, [i; 32], KeyType::Ed25519));
         }
 
         // Try to add one more key - should fail
         assert_noop!(
-            Did::add_public_key(RuntimeOrigin::signed(account), [11u8; 32], KeyType::Ed25519),
+            add_key(account, account, [11u8; 32], KeyType::Ed25519),
             Error::<Test>::TooManyPublicKeys
         );
     });
 }
 
+// ============================================================
+// PROOF OF POSSESSION TESTS
+// ============================================================
+
+#[test]
+fn create_did_fails_with_proof_from_other_key() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        // Claim someone else's public key, signing with our own key
+        assert_noop!(
+            Did::create_did(
+                RuntimeOrigin::signed(account),
+                test_public_key([1u8; 32], KeyType::Ed25519),
+                test_key_proof(account, [2u8; 32], KeyType::Ed25519)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn create_did_fails_with_mismatched_key_type() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_noop!(
+            Did::create_did(
+                RuntimeOrigin::signed(account),
+                test_public_key([1u8; 32], KeyType::Sr25519),
+                test_key_proof(account, [1u8; 32], KeyType::Ed25519)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn key_proof_is_bound_to_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let alice = 1u64;
+        let bob = 2u64;
+
+        // A proof made for Alice's DID cannot be used by Bob
+        assert_noop!(
+            Did::create_did(
+                RuntimeOrigin::signed(bob),
+                test_public_key([1u8; 32], KeyType::Ed25519),
+                test_key_proof(alice, [1u8; 32], KeyType::Ed25519)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn key_proof_cannot_be_replayed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let key_type = KeyType::Sr25519;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_eq!(KeyNonces::<Test>::get(account), 1);
+
+        let proof = test_key_proof(account, [2u8; 32], key_type);
+        assert_ok!(Did::add_public_key(
+            RuntimeOrigin::signed(account),
+            account,
+            test_public_key([2u8; 32], key_type),
+            proof.clone()
+        ));
+        assert_eq!(KeyNonces::<Test>::get(account), 2);
+
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(account),
+            account,
+            test_key_id([2u8; 32], key_type)
+        ));
+
+        // Re-adding the key with the old proof fails since the nonce moved on
+        assert_noop!(
+            Did::add_public_key(
+                RuntimeOrigin::signed(account),
+                account,
+                test_public_key([2u8; 32], key_type),
+                proof
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn key_proof_works_for_every_key_type() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ecdsa));
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(account, account, [3u8; 32], KeyType::Sr25519));
+
+        // An ECDSA proof does not verify against a different ECDSA key
+        assert_noop!(
+            Did::add_public_key(
+                RuntimeOrigin::signed(account),
+                account,
+                test_public_key([4u8; 32], KeyType::Ecdsa),
+                test_key_proof(account, [5u8; 32], KeyType::Ecdsa)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys.len(), 3);
+        assert_eq!(
+            did_doc.public_keys[0].key_id,
+            test_key_id([1u8; 32], KeyType::Ecdsa)
+        );
+    });
+}
+
+// ============================================================
+// KEY MATERIAL TESTS
+// ============================================================
+
+#[test]
+fn ecdsa_and_p256_keys_store_compressed_points() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::P256));
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Ecdsa));
+
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys[0].public_key.key_type(), KeyType::P256);
+        assert_eq!(did_doc.public_keys[0].public_key.as_bytes().len(), 33);
+        assert_eq!(did_doc.public_keys[1].public_key.key_type(), KeyType::Ecdsa);
+        assert_eq!(did_doc.public_keys[1].public_key.as_bytes().len(), 33);
+    });
+}
+
+#[test]
+fn p256_proof_from_other_key_fails() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_noop!(
+            Did::create_did(
+                RuntimeOrigin::signed(account),
+                test_public_key([1u8; 32], KeyType::P256),
+                test_key_proof(account, [2u8; 32], KeyType::P256)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+    });
+}
+
+#[test]
+fn malformed_compressed_key_is_rejected() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let mut key = [7u8; 33];
+        key[0] = 0x04;
+
+        assert_noop!(
+            Did::create_did(
+                RuntimeOrigin::signed(account),
+                PublicKey::Ecdsa(key),
+                test_key_proof(account, [1u8; 32], KeyType::Ecdsa)
+            ),
+            Error::<Test>::InvalidPublicKey
+        );
+    });
+}
+
+#[test]
+fn x25519_key_is_key_agreement_only() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let key_id = test_key_id([2u8; 32], KeyType::X25519);
+
+        // An encryption key cannot be the initial (authentication) key
+        assert_noop!(
+            create_did_with_key(account, [2u8; 32], KeyType::X25519),
+            Error::<Test>::RelationshipNotSupported
+        );
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        // Signatures cannot accompany a key that cannot sign
+        assert_noop!(
+            Did::add_public_key(
+                RuntimeOrigin::signed(account),
+                account,
+                test_public_key([2u8; 32], KeyType::X25519),
+                test_key_proof(account, [2u8; 32], KeyType::Ed25519)
+            ),
+            Error::<Test>::InvalidKeyProof
+        );
+
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::X25519));
+
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(account),
+            account,
+            key_id,
+            VerificationRelationship::KeyAgreement
+        ));
+        assert_noop!(
+            Did::add_verification_relationship(
+                RuntimeOrigin::signed(account),
+                account,
+                key_id,
+                VerificationRelationship::Authentication
+            ),
+            Error::<Test>::RelationshipNotSupported
+        );
+    });
+}
+
 // ============================================================
 // DEACTIVATE/REACTIVATE DID TESTS
 // ============================================================
@@ -252,20 +459,16 @@ fn deactivate_did_works() {
         let public_key = [1u8; 32];
 
         // Create DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         System::set_block_number(2);
 
         // Deactivate DID
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account), account));
 
         // Verify DID is inactive
         let did_doc = DidDocuments::<Test>::get(account).unwrap();
-        assert_eq!(did_doc.active, false);
+        assert!(!did_doc.active);
         assert_eq!(did_doc.updated_at, 2);
 
         // Verify event
@@ -282,22 +485,18 @@ fn reactivate_did_works() {
         let public_key = [1u8; 32];
 
         // Create and deactivate DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account), account));
 
         System::set_block_number(2);
 
         // Reactivate DID
-        assert_ok!(Did::reactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::reactivate_did(RuntimeOrigin::signed(account), account));
 
         // Verify DID is active again
         let did_doc = DidDocuments::<Test>::get(account).unwrap();
-        assert_eq!(did_doc.active, true);
+        assert!(did_doc.active);
         assert_eq!(did_doc.updated_at, 2);
     });
 }
@@ -315,11 +514,7 @@ fn register_institution_works() {
         let public_key = [1u8; 32];
 
         // Create DID first
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         // Register as institution
         let name = b"Harvard University".to_vec();
@@ -335,7 +530,7 @@ fn register_institution_works() {
 
         let institution = Institutions::<Test>::get(account).unwrap();
         assert_eq!(institution.did, account);
-        assert_eq!(institution.verified, false);
+        assert!(!institution.verified);
         assert_eq!(institution.name.to_vec(), name);
     });
 }
@@ -366,11 +561,7 @@ fn register_institution_fails_if_already_registered() {
         let public_key = [1u8; 32];
 
         // Create DID and register
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         let name = b"Harvard University".to_vec();
         let bounded_name = name.clone().try_into().unwrap();
@@ -398,11 +589,7 @@ fn register_institution_fails_with_empty_name() {
         let public_key = [1u8; 32];
 
         // Create DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         // Try to register with empty name
         let empty_name = vec![].try_into().unwrap();
@@ -422,11 +609,7 @@ fn verify_institution_works() {
         let public_key = [1u8; 32];
 
         // Create DID and register institution
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         let name = b"Harvard University".to_vec().try_into().unwrap();
         assert_ok!(Did::register_institution(
@@ -434,20 +617,26 @@ fn verify_institution_works() {
             name
         ));
 
-        // Verify institution (requires root)
-        assert_ok!(Did::verify_institution(RuntimeOrigin::root(), account));
+        // Verify institution (requires the accreditation council)
+        assert_ok!(Did::verify_institution(council(), account));
 
         // Check verification status
         let institution = Institutions::<Test>::get(account).unwrap();
-        assert_eq!(institution.verified, true);
+        assert!(institution.verified);
 
         // Verify event
-        System::assert_last_event(Event::InstitutionVerified { did: account }.into());
+        System::assert_last_event(
+            Event::InstitutionVerified {
+                did: account,
+                accreditor: None,
+            }
+            .into(),
+        );
     });
 }
 
 #[test]
-fn verify_institution_fails_for_non_root() {
+fn verify_institution_fails_for_non_council() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
 
@@ -455,11 +644,7 @@ fn verify_institution_fails_for_non_root() {
         let public_key = [1u8; 32];
 
         // Create DID and register institution
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         let name = b"Harvard University".to_vec().try_into().unwrap();
         assert_ok!(Did::register_institution(
@@ -467,11 +652,15 @@ fn verify_institution_fails_for_non_root() {
             name
         ));
 
-        // Try to verify from non-root account
+        // Neither the institution itself nor sudo can verify
         assert_noop!(
             Did::verify_institution(RuntimeOrigin::signed(account), account),
             DispatchError::BadOrigin
         );
+        assert_noop!(
+            Did::verify_institution(RuntimeOrigin::root(), account),
+            DispatchError::BadOrigin
+        );
     });
 }
 
@@ -484,11 +673,7 @@ fn revoke_institution_works() {
         let public_key = [1u8; 32];
 
         // Create DID, register and verify institution
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         let name = b"Harvard University".to_vec().try_into().unwrap();
         assert_ok!(Did::register_institution(
@@ -496,14 +681,14 @@ fn revoke_institution_works() {
             name
         ));
 
-        assert_ok!(Did::verify_institution(RuntimeOrigin::root(), account));
+        assert_ok!(Did::verify_institution(council(), account));
 
         // Revoke verification
-        assert_ok!(Did::revoke_institution(RuntimeOrigin::root(), account));
+        assert_ok!(Did::revoke_institution(council(), account));
 
         // Check verification status
         let institution = Institutions::<Test>::get(account).unwrap();
-        assert_eq!(institution.verified, false);
+        assert!(!institution.verified);
 
         // Verify event
         System::assert_last_event(Event::InstitutionRevoked { did: account }.into());
@@ -511,7 +696,7 @@ fn revoke_institution_works() {
 }
 
 #[test]
-fn revoke_institution_fails_for_non_root() {
+fn revoke_institution_fails_for_non_council() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
 
@@ -519,11 +704,7 @@ fn revoke_institution_fails_for_non_root() {
         let public_key = [1u8; 32];
 
         // Create DID, register and verify institution
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            public_key,
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(account, public_key, KeyType::Ed25519));
 
         let name = b"Harvard University".to_vec().try_into().unwrap();
         assert_ok!(Did::register_institution(
@@ -531,13 +712,17 @@ fn revoke_institution_fails_for_non_root() {
             name
         ));
 
-        assert_ok!(Did::verify_institution(RuntimeOrigin::root(), account));
+        assert_ok!(Did::verify_institution(council(), account));
 
-        // Try to revoke from non-root account
+        // Neither the institution itself nor sudo can revoke
         assert_noop!(
             Did::revoke_institution(RuntimeOrigin::signed(account), account),
             DispatchError::BadOrigin
         );
+        assert_noop!(
+            Did::revoke_institution(RuntimeOrigin::root(), account),
+            DispatchError::BadOrigin
+        );
     });
 }
 
@@ -550,43 +735,3402 @@ fn revoke_institution_fails_if_not_found() {
 
         // Try to revoke non-existent institution
         assert_noop!(
-            Did::revoke_institution(RuntimeOrigin::root(), account),
+            Did::revoke_institution(council(), account),
             Error::<Test>::InstitutionNotFound
         );
     });
 }
 
 // ============================================================
-// INTEGRATION TESTS
+// CONTROLLER TESTS
 // ============================================================
 
 #[test]
-fn complete_did_lifecycle() {
+fn transfer_controller_requires_acceptance() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
 
-        let account = 1u64;
+        let subject = 1u64;
+        let it_department = 2u64;
 
-        // 1. Create DID
-        assert_ok!(Did::create_did(
-            RuntimeOrigin::signed(account),
-            [1u8; 32],
-            KeyType::Ed25519
-        ));
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
 
-        // 2. Add multiple keys
-        assert_ok!(Did::add_public_key(
-            RuntimeOrigin::signed(account),
-            [2u8; 32],
-            KeyType::Sr25519
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(subject),
+            subject,
+            it_department
         ));
 
-        assert_ok!(Did::add_public_key(
-            RuntimeOrigin::signed(account),
-            [3u8; 32],
-            KeyType::Ecdsa
+        // Controller does not change until the transfer is accepted
+        assert_eq!(
+            DidDocuments::<Test>::get(subject).unwrap().controller,
+            subject
+        );
+        assert_eq!(
+            PendingControllers::<Test>::get(subject),
+            Some(it_department)
+        );
+        System::assert_last_event(
+            Event::ControllerTransferInitiated {
+                did: subject,
+                new_controller: it_department,
+            }
+            .into(),
+        );
+
+        System::set_block_number(2);
+
+        assert_ok!(Did::accept_controller(
+            RuntimeOrigin::signed(it_department),
+            subject
         ));
 
+        let did_doc = DidDocuments::<Test>::get(subject).unwrap();
+        assert_eq!(did_doc.controller, it_department);
+        assert_eq!(did_doc.updated_at, 2);
+        assert!(PendingControllers::<Test>::get(subject).is_none());
+        System::assert_last_event(
+            Event::ControllerTransferred {
+                did: subject,
+                old_controller: subject,
+                new_controller: it_department,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn transfer_controller_fails_if_not_controller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let subject = 1u64;
+        let attacker = 2u64;
+
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::transfer_controller(RuntimeOrigin::signed(attacker), subject, attacker),
+            Error::<Test>::NotAuthorized
+        );
+
+        assert_noop!(
+            Did::transfer_controller(RuntimeOrigin::signed(subject), subject, subject),
+            Error::<Test>::AlreadyController
+        );
+    });
+}
+
+#[test]
+fn accept_controller_fails_for_other_account() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let subject = 1u64;
+        let it_department = 2u64;
+        let attacker = 3u64;
+
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
+
+        // Nothing to accept yet
+        assert_noop!(
+            Did::accept_controller(RuntimeOrigin::signed(it_department), subject),
+            Error::<Test>::NoPendingControllerTransfer
+        );
+
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(subject),
+            subject,
+            it_department
+        ));
+
+        assert_noop!(
+            Did::accept_controller(RuntimeOrigin::signed(attacker), subject),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn cancel_controller_transfer_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let subject = 1u64;
+        let it_department = 2u64;
+
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
+
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(subject),
+            subject,
+            it_department
+        ));
+
+        assert_ok!(Did::cancel_controller_transfer(
+            RuntimeOrigin::signed(subject),
+            subject
+        ));
+
+        assert!(PendingControllers::<Test>::get(subject).is_none());
+        System::assert_last_event(Event::ControllerTransferCancelled { did: subject }.into());
+
+        assert_noop!(
+            Did::accept_controller(RuntimeOrigin::signed(it_department), subject),
+            Error::<Test>::NoPendingControllerTransfer
+        );
+    });
+}
+
+#[test]
+fn controller_manages_did_on_behalf_of_subject() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let subject = 1u64;
+        let it_department = 2u64;
+
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
+
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(subject),
+            subject,
+            it_department
+        ));
+        assert_ok!(Did::accept_controller(
+            RuntimeOrigin::signed(it_department),
+            subject
+        ));
+
+        // The controller can manage keys and status of the subject's DID
+        assert_ok!(add_key(it_department, subject, [2u8; 32], KeyType::Sr25519));
+        System::assert_last_event(
+            Event::PublicKeyAdded {
+                owner: subject,
+                key_id: test_key_id([2u8; 32], KeyType::Sr25519),
+            }
+            .into(),
+        );
+
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(it_department),
+            subject,
+            test_key_id([2u8; 32], KeyType::Sr25519)
+        ));
+        assert_ok!(Did::deactivate_did(
+            RuntimeOrigin::signed(it_department),
+            subject
+        ));
+        assert_ok!(Did::reactivate_did(
+            RuntimeOrigin::signed(it_department),
+            subject
+        ));
+
+        // The subject account no longer controls its DID
+        assert_noop!(
+            add_key(subject, subject, [3u8; 32], KeyType::Ed25519),
+            Error::<Test>::NotAuthorized
+        );
+        assert_noop!(
+            Did::deactivate_did(RuntimeOrigin::signed(subject), subject),
+            Error::<Test>::NotAuthorized
+        );
+
+        let did_doc = DidDocuments::<Test>::get(subject).unwrap();
+        assert_eq!(did_doc.public_keys.len(), 1);
+        assert!(did_doc.active);
+    });
+}
+
+// ============================================================
+// VERIFICATION RELATIONSHIP TESTS
+// ============================================================
+
+#[test]
+fn create_did_assigns_authentication_and_assertion() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        let key = &did_doc.public_keys[0];
+        assert!(key.has_relationship(VerificationRelationship::Authentication));
+        assert!(key.has_relationship(VerificationRelationship::AssertionMethod));
+        assert!(!key.has_relationship(VerificationRelationship::KeyAgreement));
+        assert!(!key.has_relationship(VerificationRelationship::CapabilityDelegation));
+
+        // Added keys start without any relationship
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert!(did_doc.public_keys[1].relationships.is_empty());
+    });
+}
+
+#[test]
+fn add_verification_relationship_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let key_id = test_key_id([2u8; 32], KeyType::Sr25519);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(account),
+            account,
+            key_id,
+            VerificationRelationship::KeyAgreement
+        ));
+
+        System::assert_last_event(
+            Event::VerificationRelationshipAdded {
+                owner: account,
+                key_id,
+                relationship: VerificationRelationship::KeyAgreement,
+            }
+            .into(),
+        );
+
+        let agreement_keys =
+            Did::keys_with_relationship(&account, VerificationRelationship::KeyAgreement);
+        assert_eq!(agreement_keys.len(), 1);
+        assert_eq!(agreement_keys[0].key_id, key_id);
+
+        assert_noop!(
+            Did::add_verification_relationship(
+                RuntimeOrigin::signed(account),
+                account,
+                key_id,
+                VerificationRelationship::KeyAgreement
+            ),
+            Error::<Test>::RelationshipAlreadyAssigned
+        );
+    });
+}
+
+#[test]
+fn add_verification_relationship_fails_for_unknown_key_or_non_controller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let other = 2u64;
+        let key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::add_verification_relationship(
+                RuntimeOrigin::signed(account),
+                account,
+                [9u8; 32],
+                VerificationRelationship::KeyAgreement
+            ),
+            Error::<Test>::PublicKeyNotFound
+        );
+
+        assert_noop!(
+            Did::add_verification_relationship(
+                RuntimeOrigin::signed(other),
+                account,
+                key_id,
+                VerificationRelationship::CapabilityDelegation
+            ),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn remove_verification_relationship_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        assert_ok!(Did::remove_verification_relationship(
+            RuntimeOrigin::signed(account),
+            account,
+            key_id,
+            VerificationRelationship::AssertionMethod
+        ));
+
+        System::assert_last_event(
+            Event::VerificationRelationshipRemoved {
+                owner: account,
+                key_id,
+                relationship: VerificationRelationship::AssertionMethod,
+            }
+            .into(),
+        );
+        assert!(
+            Did::keys_with_relationship(&account, VerificationRelationship::AssertionMethod)
+                .is_empty()
+        );
+
+        assert_noop!(
+            Did::remove_verification_relationship(
+                RuntimeOrigin::signed(account),
+                account,
+                key_id,
+                VerificationRelationship::AssertionMethod
+            ),
+            Error::<Test>::RelationshipNotAssigned
+        );
+    });
+}
+
+#[test]
+fn cannot_remove_last_authentication_key() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let first_key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+        let second_key_id = test_key_id([2u8; 32], KeyType::Sr25519);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::remove_public_key(RuntimeOrigin::signed(account), account, first_key_id),
+            Error::<Test>::LastAuthenticationKey
+        );
+        assert_noop!(
+            Did::remove_verification_relationship(
+                RuntimeOrigin::signed(account),
+                account,
+                first_key_id,
+                VerificationRelationship::Authentication
+            ),
+            Error::<Test>::LastAuthenticationKey
+        );
+
+        // Rotate authentication to a second key, then the first can go
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(account),
+            account,
+            second_key_id,
+            VerificationRelationship::Authentication
+        ));
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(account),
+            account,
+            first_key_id
+        ));
+
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys.len(), 1);
+        assert_eq!(did_doc.public_keys[0].key_id, second_key_id);
+    });
+}
+
+#[test]
+fn remove_public_key_fails_if_key_not_found() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::remove_public_key(RuntimeOrigin::signed(account), account, [9u8; 32]),
+            Error::<Test>::PublicKeyNotFound
+        );
+    });
+}
+
+#[test]
+fn migration_v1_assigns_default_relationships() {
+    use crate::migrations::v1::{self, OldDidDocument, OldPublicKeyEntry};
+    use frame::deps::frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+    new_test_ext().execute_with(|| {
+        let account = 1u64;
+        let old_doc = OldDidDocument::<Test> {
+            controller: account,
+            public_keys: vec![OldPublicKeyEntry {
+                key_id: [7u8; 32],
+                key_type: KeyType::Sr25519,
+                public_key: [8u8; 32],
+            }]
+            .try_into()
+            .unwrap(),
+            created_at: 1,
+            updated_at: 1,
+            active: true,
+        };
+        unhashed::put(&DidDocuments::<Test>::hashed_key_for(account), &old_doc);
+        StorageVersion::new(0).put::<Did>();
+
+        v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+        let did_doc = v1::DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys[0].public_key, [8u8; 32]);
+        assert!(did_doc.public_keys[0]
+            .relationships
+            .contains(&VerificationRelationship::Authentication));
+        assert!(did_doc.public_keys[0]
+            .relationships
+            .contains(&VerificationRelationship::AssertionMethod));
+        assert_eq!(StorageVersion::get::<Did>(), 1);
+    });
+}
+
+#[test]
+fn migration_v2_converts_key_material() {
+    use crate::migrations::{
+        v1::{self, DidDocumentV1, PublicKeyEntryV1},
+        v2,
+    };
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let account = 1u64;
+        let relationships: BoundedVec<_, _> = vec![VerificationRelationship::Authentication]
+            .try_into()
+            .unwrap();
+        let old_doc = DidDocumentV1::<Test> {
+            controller: account,
+            public_keys: vec![
+                PublicKeyEntryV1 {
+                    key_id: [7u8; 32],
+                    key_type: KeyType::Ed25519,
+                    public_key: [8u8; 32],
+                    relationships: relationships.clone(),
+                },
+                PublicKeyEntryV1 {
+                    key_id: [9u8; 32],
+                    key_type: KeyType::Ecdsa,
+                    public_key: [10u8; 32],
+                    relationships: BoundedVec::new(),
+                },
+            ]
+            .try_into()
+            .unwrap(),
+            created_at: 1,
+            updated_at: 2,
+            active: true,
+        };
+        v1::DidDocuments::<Test>::insert(account, old_doc);
+        StorageVersion::new(1).put::<Did>();
+
+        v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+        // The truncated ECDSA key cannot be represented and is dropped
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys.len(), 1);
+        assert_eq!(did_doc.public_keys[0].key_id, [7u8; 32]);
+        assert_eq!(
+            did_doc.public_keys[0].public_key,
+            PublicKey::Ed25519([8u8; 32])
+        );
+        assert_eq!(did_doc.public_keys[0].relationships, relationships);
+        assert_eq!(did_doc.updated_at, 2);
+        assert_eq!(StorageVersion::get::<Did>(), 2);
+    });
+}
+
+#[test]
+fn migration_v2_deactivates_dids_left_without_authentication_key() {
+    use crate::migrations::{
+        v1::{self, DidDocumentV1, PublicKeyEntryV1},
+        v2,
+    };
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let account = 1u64;
+        let relationships: BoundedVec<_, _> = vec![
+            VerificationRelationship::Authentication,
+            VerificationRelationship::AssertionMethod,
+        ]
+        .try_into()
+        .unwrap();
+        let old_doc = DidDocumentV1::<Test> {
+            controller: account,
+            public_keys: vec![
+                PublicKeyEntryV1 {
+                    key_id: [7u8; 32],
+                    key_type: KeyType::Ecdsa,
+                    public_key: [8u8; 32],
+                    relationships: relationships.clone(),
+                },
+                PublicKeyEntryV1 {
+                    key_id: [9u8; 32],
+                    key_type: KeyType::X25519,
+                    public_key: [10u8; 32],
+                    relationships,
+                },
+            ]
+            .try_into()
+            .unwrap(),
+            created_at: 1,
+            updated_at: 2,
+            active: true,
+        };
+        v1::DidDocuments::<Test>::insert(account, old_doc);
+        StorageVersion::new(1).put::<Did>();
+
+        v2::MigrateV1ToV2::<Test>::on_runtime_upgrade();
+
+        // The X25519 key is kept for key agreement only, which cannot authenticate
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        assert_eq!(did_doc.public_keys.len(), 1);
+        assert_eq!(
+            did_doc.public_keys[0].public_key,
+            PublicKey::X25519([10u8; 32])
+        );
+        assert_eq!(
+            did_doc.public_keys[0].relationships.to_vec(),
+            vec![VerificationRelationship::KeyAgreement]
+        );
+        assert!(!did_doc.active);
+    });
+}
+
+#[test]
+fn migration_v3_indexes_existing_keys() {
+    use crate::migrations::v3;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(create_did_with_key(1u64, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(1u64, 1u64, [2u8; 32], KeyType::Sr25519));
+        assert_ok!(add_key(1u64, 1u64, [4u8; 32], KeyType::X25519));
+        assert_ok!(create_did_with_key(2u64, [3u8; 32], KeyType::Ed25519));
+
+        // Simulate a pre-index chain
+        let _ = DidByKeyId::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(2).put::<Did>();
+
+        v3::MigrateV2ToV3::<Test>::on_runtime_upgrade();
+
+        for (did, seed, key_type) in [
+            (1u64, [1u8; 32], KeyType::Ed25519),
+            (1u64, [2u8; 32], KeyType::Sr25519),
+            (2u64, [3u8; 32], KeyType::Ed25519),
+        ] {
+            assert_eq!(
+                DidByKeyId::<Test>::get(test_key_id(seed, key_type)),
+                Some(did)
+            );
+        }
+        assert!(!DidByKeyId::<Test>::contains_key(test_key_id(
+            [4u8; 32],
+            KeyType::X25519
+        )));
+        assert_eq!(StorageVersion::get::<Did>(), 3);
+    });
+}
+
+#[test]
+fn migration_v4_adds_empty_accreditor() {
+    use crate::migrations::v4::{self, OldInstitution};
+    use frame::deps::frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+    new_test_ext().execute_with(|| {
+        let account = 1u64;
+        let old_institution = OldInstitution::<Test> {
+            name: b"MIT".to_vec().try_into().unwrap(),
+            did: account,
+            verified: true,
+            registered_at: 5,
+        };
+        unhashed::put(
+            &Institutions::<Test>::hashed_key_for(account),
+            &old_institution,
+        );
+        StorageVersion::new(3).put::<Did>();
+
+        v4::MigrateV3ToV4::<Test>::on_runtime_upgrade();
+
+        let institution = v4::Institutions::<Test>::get(account).unwrap();
+        assert!(institution.verified);
+        assert_eq!(institution.registered_at, 5);
+        assert_eq!(institution.accreditor, None);
+        assert_eq!(StorageVersion::get::<Did>(), 4);
+    });
+}
+
+#[test]
+fn migration_v5_schedules_verified_institutions() {
+    use crate::migrations::{
+        v4::{self, InstitutionV4},
+        v5,
+    };
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+
+        for (account, verified) in [(1u64, true), (2u64, false), (3u64, true), (4u64, true)] {
+            v4::Institutions::<Test>::insert(
+                account,
+                InstitutionV4::<Test> {
+                    name: b"MIT".to_vec().try_into().unwrap(),
+                    did: account,
+                    verified,
+                    registered_at: 1,
+                    accreditor: None,
+                },
+            );
+        }
+        StorageVersion::new(4).put::<Did>();
+
+        v5::MigrateV4ToV5::<Test>::on_runtime_upgrade();
+
+        for account in [1u64, 3, 4] {
+            let until = Institutions::<Test>::get(account)
+                .unwrap()
+                .verified_until
+                .unwrap();
+            assert!(VerificationExpiries::<Test>::contains_key(until, account));
+        }
+        assert_eq!(
+            Institutions::<Test>::get(2u64).unwrap().verified_until,
+            None
+        );
+
+        // Lapses are spread so each block stays within its limit
+        let until = 3 + VerificationPeriod::get();
+        assert_eq!(
+            VerificationExpiries::<Test>::iter_prefix(until).count() as u32,
+            MaxLapsesPerBlock::get()
+        );
+        assert_eq!(
+            VerificationExpiries::<Test>::iter_prefix(until + 1).count(),
+            1
+        );
+        assert_eq!(StorageVersion::get::<Did>(), 5);
+    });
+}
+
+// ============================================================
+// SERVICE ENDPOINT TESTS
+// ============================================================
+
+#[test]
+fn add_service_endpoint_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let service_id: ServiceId = b"status-list".to_vec().try_into().unwrap();
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        System::set_block_number(5);
+        assert_ok!(add_service(
+            account,
+            account,
+            b"status-list",
+            b"https://uni.example/status"
+        ));
+
+        let services = ServiceEndpoints::<Test>::get(account);
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].id, service_id);
+        assert_eq!(
+            services[0].url.to_vec(),
+            b"https://uni.example/status".to_vec()
+        );
+        assert_eq!(DidDocuments::<Test>::get(account).unwrap().updated_at, 5);
+
+        System::assert_last_event(
+            Event::ServiceEndpointAdded {
+                owner: account,
+                service_id,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn add_service_endpoint_fails_for_duplicate_or_invalid_service() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_service(
+            account,
+            account,
+            b"inbox",
+            b"https://uni.example/didcomm"
+        ));
+
+        assert_noop!(
+            add_service(account, account, b"inbox", b"https://other.example"),
+            Error::<Test>::ServiceEndpointAlreadyExists
+        );
+
+        assert_noop!(
+            add_service(account, account, b"registrar", b""),
+            Error::<Test>::InvalidServiceEndpoint
+        );
+
+        assert_noop!(
+            add_service(2u64, account, b"registrar", b"mailto:registrar@uni.example"),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn add_service_endpoint_fails_if_max_services_reached() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        for i in 0..MaxServiceEndpoints::get() {
+            assert_ok!(add_service(
+                account,
+                account,
+                &[b'a' + i as u8],
+                b"https://uni.example"
+            ));
+        }
+
+        assert_noop!(
+            add_service(account, account, b"overflow", b"https://uni.example"),
+            Error::<Test>::TooManyServiceEndpoints
+        );
+    });
+}
+
+#[test]
+fn remove_service_endpoint_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let service_id: ServiceId = b"status-list".to_vec().try_into().unwrap();
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_service(
+            account,
+            account,
+            b"status-list",
+            b"https://uni.example/status"
+        ));
+
+        assert_ok!(Did::remove_service_endpoint(
+            RuntimeOrigin::signed(account),
+            account,
+            service_id.clone()
+        ));
+
+        assert!(ServiceEndpoints::<Test>::get(account).is_empty());
+        System::assert_last_event(
+            Event::ServiceEndpointRemoved {
+                owner: account,
+                service_id: service_id.clone(),
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            Did::remove_service_endpoint(RuntimeOrigin::signed(account), account, service_id),
+            Error::<Test>::ServiceEndpointNotFound
+        );
+    });
+}
+
+#[test]
+fn resolve_collects_document_and_services() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_eq!(Did::resolve(account), None);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_service(
+            account,
+            account,
+            b"inbox",
+            b"https://uni.example/didcomm"
+        ));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account), account));
+
+        let resolution = Did::resolve(account).unwrap();
+        assert_eq!(resolution.did, account);
+        assert_eq!(resolution.controller, account);
+        assert_eq!(
+            resolution.public_keys,
+            DidDocuments::<Test>::get(account)
+                .unwrap()
+                .public_keys
+                .into_inner()
+        );
+        assert_eq!(resolution.services.len(), 1);
+        assert_eq!(resolution.services[0].id, b"inbox".to_vec());
+        assert_eq!(
+            resolution.services[0].service_type,
+            b"LinkedDomains".to_vec()
+        );
+        assert!(!resolution.active);
+    });
+}
+
+// ============================================================
+// DEPOSIT TESTS
+// ============================================================
+
+fn did_deposit_held(account: u64) -> u64 {
+    Balances::balance_on_hold(&HoldReason::DidDeposit.into(), &account)
+}
+
+#[test]
+fn create_did_holds_deposit_sized_by_document() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        let did_doc = DidDocuments::<Test>::get(account).unwrap();
+        let linked = LinkedAccounts::<Test>::get(account).encoded_size() as u64;
+        let history = KeyHistories::<Test>::get(account).unwrap().encoded_size() as u64;
+        // The empty service and compromised key lists still encode as one byte each
+        let expected = DidDeposit::get() + did_doc.encoded_size() as u64 + 2 + linked + history;
+
+        let deposit = DidDeposits::<Test>::get(account).unwrap();
+        assert_eq!(deposit.owner, account);
+        assert_eq!(deposit.amount, expected);
+        assert_eq!(did_deposit_held(account), expected);
+        assert_eq!(Balances::free_balance(account), INITIAL_BALANCE - expected);
+    });
+}
+
+#[test]
+fn create_did_fails_without_funds_for_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let unfunded = 99u64;
+
+        assert!(create_did_with_key(unfunded, [1u8; 32], KeyType::Ed25519).is_err());
+        assert!(!DidDocuments::<Test>::contains_key(unfunded));
+    });
+}
+
+#[test]
+fn did_deposit_follows_document_size() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        let initial = did_deposit_held(account);
+
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+        assert_ok!(add_service(
+            account,
+            account,
+            b"inbox",
+            b"https://uni.example"
+        ));
+        let grown = did_deposit_held(account);
+        assert!(grown > initial);
+        assert_eq!(DidDeposits::<Test>::get(account).unwrap().amount, grown);
+
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(account),
+            account,
+            test_key_id([2u8; 32], KeyType::Sr25519)
+        ));
+        assert_ok!(Did::remove_service_endpoint(
+            RuntimeOrigin::signed(account),
+            account,
+            b"inbox".to_vec().try_into().unwrap()
+        ));
+        assert_eq!(did_deposit_held(account), initial);
+    });
+}
+
+#[test]
+fn controller_transfer_moves_did_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let subject = 1u64;
+        let controller = 2u64;
+
+        assert_ok!(create_did_with_key(subject, [1u8; 32], KeyType::Ed25519));
+        let deposit = did_deposit_held(subject);
+
+        assert_ok!(Did::transfer_controller(
+            RuntimeOrigin::signed(subject),
+            subject,
+            controller
+        ));
+        assert_ok!(Did::accept_controller(
+            RuntimeOrigin::signed(controller),
+            subject
+        ));
+
+        assert_eq!(did_deposit_held(subject), 0);
+        assert_eq!(did_deposit_held(controller), deposit);
+        assert_eq!(DidDeposits::<Test>::get(subject).unwrap().owner, controller);
+    });
+}
+
+#[test]
+fn register_institution_holds_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(account),
+            b"MIT".to_vec().try_into().unwrap()
+        ));
+
+        let institution = Institutions::<Test>::get(account).unwrap();
+        let expected = DidDeposit::get() + institution.encoded_size() as u64;
+
+        assert_eq!(
+            InstitutionDeposits::<Test>::get(account).unwrap().amount,
+            expected
+        );
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::InstitutionDeposit.into(), &account),
+            expected
+        );
+    });
+}
+
+// ============================================================
+// DELETE DID TESTS
+// ============================================================
+
+#[test]
+fn delete_did_removes_document_and_releases_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_service(
+            account,
+            account,
+            b"inbox",
+            b"https://uni.example"
+        ));
+
+        System::set_block_number(3);
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(account), account));
+
+        assert!(!DidDocuments::<Test>::contains_key(account));
+        assert!(ServiceEndpoints::<Test>::get(account).is_empty());
+        assert!(DidDeposits::<Test>::get(account).is_none());
+        assert_eq!(did_deposit_held(account), 0);
+        assert_eq!(Balances::free_balance(account), INITIAL_BALANCE);
+        assert_eq!(Tombstones::<Test>::get(account), Some(3));
+
+        System::assert_last_event(Event::DidDeleted { owner: account }.into());
+    });
+}
+
+#[test]
+fn deleted_did_cannot_be_recreated() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(account), account));
+
+        assert_noop!(
+            create_did_with_key(account, [2u8; 32], KeyType::Ed25519),
+            Error::<Test>::DidTombstoned
+        );
+    });
+}
+
+#[test]
+fn delete_did_fails_if_not_controller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(account), account),
+            Error::<Test>::DidNotFound
+        );
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(2u64), account),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn delete_did_removes_institution_registration() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(account),
+            b"MIT".to_vec().try_into().unwrap()
+        ));
+
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(account), account));
+
+        assert!(!Institutions::<Test>::contains_key(account));
+        assert!(InstitutionDeposits::<Test>::get(account).is_none());
+        assert_eq!(Balances::free_balance(account), INITIAL_BALANCE);
+    });
+}
+
+// ============================================================
+// SOCIAL RECOVERY TESTS
+// ============================================================
+
+fn setup_recovery(did: u64, guardians: Vec<u64>, threshold: u32) {
+    assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+    assert_ok!(Did::set_recovery_config(
+        RuntimeOrigin::signed(did),
+        did,
+        guardians.try_into().unwrap(),
+        threshold
+    ));
+}
+
+#[test]
+fn set_recovery_config_validates_guardians() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        for (guardians, threshold) in [(vec![2u64, 3], 0), (vec![2, 3], 3), (vec![2, 2], 2)] {
+            assert_noop!(
+                Did::set_recovery_config(
+                    RuntimeOrigin::signed(account),
+                    account,
+                    guardians.try_into().unwrap(),
+                    threshold
+                ),
+                Error::<Test>::InvalidRecoveryConfig
+            );
+        }
+
+        assert_noop!(
+            Did::set_recovery_config(
+                RuntimeOrigin::signed(2u64),
+                account,
+                vec![2u64].try_into().unwrap(),
+                1
+            ),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn recovery_hands_did_to_new_account_after_delay() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let lost = 1u64;
+        let new_account = 5u64;
+
+        setup_recovery(lost, vec![2, 3, 4], 2);
+        assert_ok!(add_service(lost, lost, b"inbox", b"https://uni.example"));
+        let did_doc = DidDocuments::<Test>::get(lost).unwrap();
+
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(2u64),
+            lost,
+            new_account
+        ));
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(new_account), lost),
+            Error::<Test>::RecoveryNotApproved
+        );
+
+        System::set_block_number(2);
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(3u64),
+            lost,
+            new_account
+        ));
+        assert_eq!(
+            ActiveRecoveries::<Test>::get(lost).unwrap().approved,
+            Some((new_account, 2))
+        );
+
+        System::set_block_number(2 + RecoveryDelay::get() - 1);
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(new_account), lost),
+            Error::<Test>::RecoveryDelayNotElapsed
+        );
+
+        System::set_block_number(2 + RecoveryDelay::get());
+        assert_ok!(Did::claim_recovery(
+            RuntimeOrigin::signed(new_account),
+            lost
+        ));
+
+        // The DID keeps its identifier, keys and services under a new controller
+        let recovered = DidDocuments::<Test>::get(lost).unwrap();
+        assert_eq!(recovered.controller, new_account);
+        assert_eq!(recovered.public_keys, did_doc.public_keys);
+        assert_eq!(ServiceEndpoints::<Test>::get(lost).len(), 1);
+        assert!(Did::recovery_configs(lost).is_some());
+        assert!(ActiveRecoveries::<Test>::get(lost).is_none());
+        assert!(!Tombstones::<Test>::contains_key(lost));
+
+        // The lost account no longer acts for the DID
+        assert_eq!(
+            LinkedAccounts::<Test>::get(lost).into_inner(),
+            vec![new_account]
+        );
+        assert_eq!(AccountDids::<Test>::get(lost), None);
+        assert_eq!(AccountDids::<Test>::get(new_account), Some(lost));
+
+        // The deposit now comes from the new controller
+        assert_eq!(did_deposit_held(lost), 0);
+        assert_eq!(
+            did_deposit_held(new_account),
+            DidDeposits::<Test>::get(lost).unwrap().amount
+        );
+
+        System::assert_last_event(
+            Event::DidRecovered {
+                did: lost,
+                controller: new_account,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn vouch_recovery_checks_guardian_and_target() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        assert_noop!(
+            Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 5u64),
+            Error::<Test>::NoRecoveryConfig
+        );
+
+        setup_recovery(did, vec![2, 3], 2);
+
+        assert_noop!(
+            Did::vouch_recovery(RuntimeOrigin::signed(4u64), did, 5u64),
+            Error::<Test>::NotGuardian
+        );
+
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 5u64));
+
+        assert_noop!(
+            Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 5u64),
+            Error::<Test>::AlreadyVouched
+        );
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(5u64), did),
+            Error::<Test>::RecoveryNotApproved
+        );
+
+        // Once approved, the recovery cannot be redirected
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(3u64), did, 5u64));
+        assert_noop!(
+            Did::vouch_recovery(RuntimeOrigin::signed(3u64), did, 6u64),
+            Error::<Test>::RecoveryTargetMismatch
+        );
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(6u64), did),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn conflicting_first_vouch_does_not_block_recovery() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let attacker = 6u64;
+        let new_account = 5u64;
+
+        setup_recovery(did, vec![2, 3, 4], 2);
+
+        // A rogue guardian vouches first, for an account they control
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(2u64),
+            did,
+            attacker
+        ));
+
+        // The other guardians can still agree on the rightful account
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(3u64),
+            did,
+            new_account
+        ));
+        assert_eq!(ActiveRecoveries::<Test>::get(did).unwrap().approved, None);
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(4u64),
+            did,
+            new_account
+        ));
+        assert_eq!(
+            ActiveRecoveries::<Test>::get(did).unwrap().approved,
+            Some((new_account, 1))
+        );
+
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(attacker), did),
+            Error::<Test>::NotAuthorized
+        );
+        assert_ok!(Did::claim_recovery(RuntimeOrigin::signed(new_account), did));
+        assert_eq!(
+            DidDocuments::<Test>::get(did).unwrap().controller,
+            new_account
+        );
+    });
+}
+
+#[test]
+fn guardian_can_move_vouch_to_another_account() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        setup_recovery(did, vec![2, 3], 2);
+
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 6u64));
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(3u64), did, 5u64));
+        assert_eq!(ActiveRecoveries::<Test>::get(did).unwrap().approved, None);
+
+        // The moved vouch no longer counts towards the first account
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 5u64));
+        let recovery = ActiveRecoveries::<Test>::get(did).unwrap();
+        assert_eq!(recovery.vouches.len(), 2);
+        assert_eq!(recovery.approved, Some((5u64, 1)));
+    });
+}
+
+#[test]
+fn controller_can_cancel_recovery() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        setup_recovery(did, vec![2], 1);
+        assert_ok!(Did::vouch_recovery(RuntimeOrigin::signed(2u64), did, 5u64));
+
+        assert_noop!(
+            Did::cancel_recovery(RuntimeOrigin::signed(5u64), did),
+            Error::<Test>::NotAuthorized
+        );
+        assert_ok!(Did::cancel_recovery(RuntimeOrigin::signed(did), did));
+        System::assert_last_event(Event::RecoveryCancelled { did }.into());
+
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(5u64), did),
+            Error::<Test>::NoActiveRecovery
+        );
+    });
+}
+
+#[test]
+fn recovery_cannot_overwrite_existing_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let new_account = 5u64;
+
+        setup_recovery(did, vec![2], 1);
+        assert_ok!(create_did_with_key(
+            new_account,
+            [5u8; 32],
+            KeyType::Ed25519
+        ));
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(2u64),
+            did,
+            new_account
+        ));
+
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_noop!(
+            Did::claim_recovery(RuntimeOrigin::signed(new_account), did),
+            Error::<Test>::DidAlreadyExists
+        );
+    });
+}
+
+// ============================================================
+// KEY INDEX TESTS
+// ============================================================
+
+#[test]
+fn key_index_follows_key_changes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+        let first_key = test_key_id([1u8; 32], KeyType::Ed25519);
+        let second_key = test_key_id([2u8; 32], KeyType::Sr25519);
+
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+        assert_eq!(DidByKeyId::<Test>::get(first_key), Some(account));
+        assert_eq!(
+            Did::did_by_key(&test_public_key([1u8; 32], KeyType::Ed25519)),
+            Some(account)
+        );
+
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+        assert_eq!(DidByKeyId::<Test>::get(second_key), Some(account));
+
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(account),
+            account,
+            second_key
+        ));
+        assert!(!DidByKeyId::<Test>::contains_key(second_key));
+
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(account), account));
+        assert!(!DidByKeyId::<Test>::contains_key(first_key));
+    });
+}
+
+#[test]
+fn key_cannot_belong_to_two_dids() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(create_did_with_key(1u64, [1u8; 32], KeyType::Ed25519));
+        assert_noop!(
+            create_did_with_key(2u64, [1u8; 32], KeyType::Ed25519),
+            Error::<Test>::KeyAlreadyInUse
+        );
+
+        assert_ok!(create_did_with_key(2u64, [2u8; 32], KeyType::Ed25519));
+        assert_noop!(
+            add_key(2u64, 2u64, [1u8; 32], KeyType::Ed25519),
+            Error::<Test>::KeyAlreadyInUse
+        );
+    });
+}
+
+#[test]
+fn key_agreement_key_cannot_be_squatted() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let victim = 1u64;
+        let squatter = 2u64;
+        let key_id = test_key_id([3u8; 32], KeyType::X25519);
+
+        // The squatter copies the victim's published key-agreement key first
+        assert_ok!(create_did_with_key(squatter, [2u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(squatter, squatter, [3u8; 32], KeyType::X25519));
+        assert!(!DidByKeyId::<Test>::contains_key(key_id));
+
+        assert_ok!(create_did_with_key(victim, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(victim, victim, [3u8; 32], KeyType::X25519));
+
+        // Removing the copy leaves the victim's key alone
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(squatter),
+            squatter,
+            key_id
+        ));
+        assert!(Did::did_documents(victim)
+            .unwrap()
+            .public_keys
+            .iter()
+            .any(|k| k.key_id == key_id));
+    });
+}
+
+#[test]
+fn key_index_survives_recovery() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let new_account = 5u64;
+
+        setup_recovery(did, vec![2], 1);
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(2u64),
+            did,
+            new_account
+        ));
+
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_ok!(Did::claim_recovery(RuntimeOrigin::signed(new_account), did));
+
+        assert_eq!(
+            DidByKeyId::<Test>::get(test_key_id([1u8; 32], KeyType::Ed25519)),
+            Some(did)
+        );
+    });
+}
+
+// ============================================================
+// ACCREDITATION BODY TESTS
+// ============================================================
+
+fn setup_accreditation_body(body: u64, scope: AccreditationScope) {
+    assert_ok!(create_did_with_key(
+        body,
+        [body as u8; 32],
+        KeyType::Ed25519
+    ));
+    assert_ok!(Did::register_accreditation_body(
+        council(),
+        body,
+        b"Accreditor".to_vec().try_into().unwrap(),
+        scope
+    ));
+}
+
+fn setup_institution(account: u64) {
+    assert_ok!(create_did_with_key(
+        account,
+        [account as u8; 32],
+        KeyType::Ed25519
+    ));
+    assert_ok!(Did::register_institution(
+        RuntimeOrigin::signed(account),
+        b"University".to_vec().try_into().unwrap()
+    ));
+}
+
+fn setup_profile(account: u64, country: [u8; 2], sectors: &[&[u8]]) {
+    let sectors = sectors
+        .iter()
+        .map(|sector| sector.to_vec().try_into().unwrap())
+        .collect::<Vec<_>>();
+    assert_ok!(Did::update_institution_profile(
+        RuntimeOrigin::signed(account),
+        account,
+        InstitutionProfile {
+            country: Some(country),
+            sectors: sectors.try_into().unwrap(),
+            ..Default::default()
+        }
+    ));
+}
+
+#[test]
+fn register_accreditation_body_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let scope = AccreditationScope::Country(*b"GB");
+
+        setup_accreditation_body(body, scope.clone());
+
+        let registered = AccreditationBodies::<Test>::get(body).unwrap();
+        assert_eq!(registered.scope, scope);
+        assert_eq!(registered.registered_at, 1);
+        System::assert_last_event(Event::AccreditationBodyRegistered { body, scope }.into());
+    });
+}
+
+#[test]
+fn register_accreditation_body_validates_input() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let name: BoundedVec<_, _> = b"Accreditor".to_vec().try_into().unwrap();
+        let scope = AccreditationScope::Country(*b"GB");
+
+        assert_noop!(
+            Did::register_accreditation_body(council(), body, name.clone(), scope.clone()),
+            Error::<Test>::DidNotFound
+        );
+
+        assert_ok!(create_did_with_key(body, [10u8; 32], KeyType::Ed25519));
+        assert_noop!(
+            Did::register_accreditation_body(
+                RuntimeOrigin::signed(body),
+                body,
+                name.clone(),
+                scope.clone()
+            ),
+            DispatchError::BadOrigin
+        );
+
+        for invalid in [
+            AccreditationScope::Country(*b"gb"),
+            AccreditationScope::Sector(BoundedVec::new()),
+        ] {
+            assert_noop!(
+                Did::register_accreditation_body(council(), body, name.clone(), invalid),
+                Error::<Test>::InvalidAccreditationScope
+            );
+        }
+
+        assert_ok!(Did::register_accreditation_body(
+            council(),
+            body,
+            name.clone(),
+            scope.clone()
+        ));
+        assert_noop!(
+            Did::register_accreditation_body(council(), body, name, scope),
+            Error::<Test>::AccreditationBodyAlreadyRegistered
+        );
+    });
+}
+
+#[test]
+fn accreditation_body_verifies_and_revokes_institution() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let institution = 1u64;
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+
+        let record = Institutions::<Test>::get(institution).unwrap();
+        assert!(record.verified);
+        assert_eq!(record.accreditor, Some(body));
+        assert!(AccreditedInstitutions::<Test>::contains_key(
+            body,
+            institution
+        ));
+        System::assert_last_event(
+            Event::InstitutionVerified {
+                did: institution,
+                accreditor: Some(body),
+            }
+            .into(),
+        );
+
+        assert_ok!(Did::revoke_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+
+        let record = Institutions::<Test>::get(institution).unwrap();
+        assert!(!record.verified);
+        assert_eq!(record.accreditor, None);
+        assert!(!AccreditedInstitutions::<Test>::contains_key(
+            body,
+            institution
+        ));
+    });
+}
+
+#[test]
+fn accreditation_body_cannot_override_another_verification() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let first_body = 10u64;
+        let second_body = 11u64;
+        let institution = 1u64;
+
+        setup_accreditation_body(first_body, AccreditationScope::Country(*b"GB"));
+        setup_accreditation_body(
+            second_body,
+            AccreditationScope::Sector(b"medicine".to_vec().try_into().unwrap()),
+        );
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(first_body),
+            institution
+        ));
+
+        assert_noop!(
+            Did::verify_institution(RuntimeOrigin::signed(second_body), institution),
+            Error::<Test>::NotAccreditor
+        );
+        assert_noop!(
+            Did::revoke_institution(RuntimeOrigin::signed(second_body), institution),
+            Error::<Test>::NotAccreditor
+        );
+
+        // Governance can take over any verification
+        assert_ok!(Did::verify_institution(council(), institution));
+        assert_eq!(
+            Institutions::<Test>::get(institution).unwrap().accreditor,
+            None
+        );
+        assert!(!AccreditedInstitutions::<Test>::contains_key(
+            first_body,
+            institution
+        ));
+    });
+}
+
+#[test]
+fn accreditation_body_verifies_within_scope() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let country_body = 10u64;
+        let sector_body = 11u64;
+        let first = 1u64;
+        let second = 2u64;
+
+        setup_accreditation_body(country_body, AccreditationScope::Country(*b"GB"));
+        setup_accreditation_body(
+            sector_body,
+            AccreditationScope::Sector(b"medicine".to_vec().try_into().unwrap()),
+        );
+        setup_institution(first);
+        setup_profile(first, *b"GB", &[]);
+        assert_ok!(create_did_with_key(second, [2u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(second),
+            b"Medical School".to_vec().try_into().unwrap()
+        ));
+        setup_profile(second, *b"FR", &[b"law", b"Medicine"]);
+
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(country_body),
+            first
+        ));
+        assert_ok!(Did::renew_institution_verification(
+            RuntimeOrigin::signed(country_body),
+            first
+        ));
+
+        // Sectors match regardless of case
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(sector_body),
+            second
+        ));
+        assert_eq!(
+            Institutions::<Test>::get(second).unwrap().accreditor,
+            Some(sector_body)
+        );
+    });
+}
+
+#[test]
+fn accreditation_body_cannot_act_out_of_scope() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let institution = 1u64;
+        let unprofiled = 2u64;
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_institution(institution);
+        setup_profile(institution, *b"FR", &[]);
+        setup_institution(unprofiled);
+
+        assert_noop!(
+            Did::verify_institution(RuntimeOrigin::signed(body), institution),
+            Error::<Test>::OutOfAccreditationScope
+        );
+        assert_noop!(
+            Did::verify_institution(RuntimeOrigin::signed(body), unprofiled),
+            Error::<Test>::OutOfAccreditationScope
+        );
+
+        // Governance is not bound by a scope
+        assert_ok!(Did::verify_institution(council(), institution));
+
+        // Once verified, the institution cannot leave its accreditor's scope
+        setup_profile(institution, *b"GB", &[]);
+        assert_ok!(Did::revoke_institution(council(), institution));
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+        assert_noop!(
+            Did::update_institution_profile(
+                RuntimeOrigin::signed(institution),
+                institution,
+                InstitutionProfile {
+                    country: Some(*b"FR"),
+                    ..Default::default()
+                }
+            ),
+            Error::<Test>::OutOfAccreditationScope
+        );
+    });
+}
+
+#[test]
+fn removed_accreditation_body_cannot_verify() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let institution = 1u64;
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+
+        assert_noop!(
+            Did::remove_accreditation_body(RuntimeOrigin::signed(body), body),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Did::remove_accreditation_body(council(), body));
+        System::assert_last_event(Event::AccreditationBodyRemoved { body }.into());
+
+        assert_noop!(
+            Did::verify_institution(RuntimeOrigin::signed(body), institution),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Did::remove_accreditation_body(council(), body),
+            Error::<Test>::AccreditationBodyNotFound
+        );
+    });
+}
+
+// ============================================================
+// VERIFICATION EXPIRY TESTS
+// ============================================================
+
+#[test]
+fn institution_verification_lapses() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        let until = 1 + VerificationPeriod::get();
+
+        setup_institution(institution);
+        assert_ok!(Did::verify_institution(council(), institution));
+        assert_eq!(
+            Institutions::<Test>::get(institution)
+                .unwrap()
+                .verified_until,
+            Some(until)
+        );
+
+        Did::on_initialize(until - 1);
+        assert!(Institutions::<Test>::get(institution).unwrap().verified);
+
+        System::set_block_number(until);
+        Did::on_initialize(until);
+
+        let record = Institutions::<Test>::get(institution).unwrap();
+        assert!(!record.verified);
+        assert_eq!(record.verified_until, Some(until));
+        assert!(!VerificationExpiries::<Test>::contains_key(
+            until,
+            institution
+        ));
+        System::assert_last_event(Event::InstitutionVerificationLapsed { did: institution }.into());
+    });
+}
+
+#[test]
+fn verification_lapse_backlog_is_bounded_and_worked_off_on_idle() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let until = 1 + VerificationPeriod::get();
+        let institutions = [1u64, 2, 3];
+        for (index, account) in institutions.into_iter().enumerate() {
+            assert_ok!(create_did_with_key(
+                account,
+                [account as u8; 32],
+                KeyType::Ed25519
+            ));
+            assert_ok!(Did::register_institution(
+                RuntimeOrigin::signed(account),
+                vec![b'A' + index as u8; 3].try_into().unwrap()
+            ));
+            assert_ok!(Did::verify_institution(council(), account));
+        }
+        let lapsed = || {
+            institutions
+                .iter()
+                .filter(|account| !Institutions::<Test>::get(*account).unwrap().verified)
+                .count() as u32
+        };
+
+        System::set_block_number(until);
+        Did::on_initialize(until);
+        assert_eq!(lapsed(), MaxLapsesPerBlock::get());
+
+        Did::on_idle(until, Weight::MAX);
+        assert_eq!(lapsed(), 3);
+        assert_eq!(Did::verification_expiry_cursor(), until + 1);
+
+        // The idle hook stays within the weight it is given
+        assert_eq!(Did::on_idle(until + 1, Weight::zero()), Weight::zero());
+        assert_eq!(Did::verification_expiry_cursor(), until + 1);
+    });
+}
+
+#[test]
+fn migration_v11_counts_vouches_per_account() {
+    use crate::migrations::{v11, v7::ActiveRecoveryV7};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        v11::ActiveRecoveries::<Test>::insert(
+            1u64,
+            ActiveRecoveryV7::<Test> {
+                new_account: 5,
+                vouchers: vec![2u64, 3].try_into().unwrap(),
+                approved_at: Some(4),
+            },
+        );
+        v11::ActiveRecoveries::<Test>::insert(
+            2u64,
+            ActiveRecoveryV7::<Test> {
+                new_account: 6,
+                vouchers: vec![3u64].try_into().unwrap(),
+                approved_at: None,
+            },
+        );
+        StorageVersion::new(10).put::<Did>();
+
+        v11::MigrateV10ToV11::<Test>::on_runtime_upgrade();
+
+        let approved = ActiveRecoveries::<Test>::get(1u64).unwrap();
+        assert_eq!(approved.vouches.into_inner(), vec![(2, 5), (3, 5)]);
+        assert_eq!(approved.approved, Some((5, 4)));
+        let pending = ActiveRecoveries::<Test>::get(2u64).unwrap();
+        assert_eq!(pending.vouches.into_inner(), vec![(3, 6)]);
+        assert_eq!(pending.approved, None);
+        assert_eq!(StorageVersion::get::<Did>(), 11);
+    });
+}
+
+#[test]
+fn migration_v10_starts_expiry_cursor() {
+    use crate::migrations::v10;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(50);
+        StorageVersion::new(9).put::<Did>();
+
+        v10::MigrateV9ToV10::<Test>::on_runtime_upgrade();
+
+        assert_eq!(Did::verification_expiry_cursor(), 50);
+        assert_eq!(StorageVersion::get::<Did>(), 10);
+    });
+}
+
+#[test]
+fn renew_institution_verification_extends_and_restores() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let institution = 1u64;
+        let first_until = 1 + VerificationPeriod::get();
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+
+        // Renewing before the lapse moves the scheduled lapse
+        System::set_block_number(50);
+        assert_ok!(Did::renew_institution_verification(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+        let second_until = 50 + VerificationPeriod::get();
+        assert!(!VerificationExpiries::<Test>::contains_key(
+            first_until,
+            institution
+        ));
+        assert!(VerificationExpiries::<Test>::contains_key(
+            second_until,
+            institution
+        ));
+        System::assert_last_event(
+            Event::InstitutionVerificationRenewed {
+                did: institution,
+                verified_until: second_until,
+            }
+            .into(),
+        );
+
+        Did::on_initialize(first_until);
+        assert!(Institutions::<Test>::get(institution).unwrap().verified);
+
+        // A lapsed verification can be renewed
+        System::set_block_number(second_until);
+        Did::on_initialize(second_until);
+        assert!(!Institutions::<Test>::get(institution).unwrap().verified);
+
+        assert_ok!(Did::renew_institution_verification(council(), institution));
+        let record = Institutions::<Test>::get(institution).unwrap();
+        assert!(record.verified);
+        assert_eq!(record.accreditor, Some(body));
+        assert_eq!(
+            record.verified_until,
+            Some(second_until + VerificationPeriod::get())
+        );
+    });
+}
+
+#[test]
+fn renew_institution_verification_checks_caller_and_state() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let other_body = 11u64;
+        let institution = 1u64;
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_accreditation_body(other_body, AccreditationScope::Country(*b"FR"));
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+
+        assert_noop!(
+            Did::renew_institution_verification(council(), institution),
+            Error::<Test>::InstitutionNotVerified
+        );
+
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+        assert_noop!(
+            Did::renew_institution_verification(RuntimeOrigin::signed(other_body), institution),
+            Error::<Test>::NotAccreditor
+        );
+        assert_noop!(
+            Did::renew_institution_verification(RuntimeOrigin::signed(institution), institution),
+            DispatchError::BadOrigin
+        );
+
+        // Revocation clears the schedule, so there is nothing left to renew
+        assert_ok!(Did::revoke_institution(council(), institution));
+        assert_eq!(
+            VerificationExpiries::<Test>::iter_prefix(1 + VerificationPeriod::get()).count(),
+            0
+        );
+        assert_noop!(
+            Did::renew_institution_verification(council(), institution),
+            Error::<Test>::InstitutionNotVerified
+        );
+    });
+}
+
+// ============================================================
+// INSTITUTION PROFILE TESTS
+// ============================================================
+
+fn field(value: &[u8]) -> crate::ProfileField {
+    value.to_vec().try_into().unwrap()
+}
+
+fn valid_profile() -> InstitutionProfile {
+    InstitutionProfile {
+        country: Some(*b"GB"),
+        legal_identifier: Some(field(b"RC000656")),
+        website: Some(field(b"ox.ac.uk")),
+        logo_hash: Some([7u8; 32]),
+        accreditations: vec![field(b"QAA-2023-0042")].try_into().unwrap(),
+        sectors: vec![b"law".to_vec().try_into().unwrap()]
+            .try_into()
+            .unwrap(),
+    }
+}
+
+#[test]
+fn update_institution_profile_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        let profile = valid_profile();
+
+        setup_institution(institution);
+        let registration_deposit = InstitutionDeposits::<Test>::get(institution)
+            .unwrap()
+            .amount;
+
+        assert_ok!(Did::update_institution_profile(
+            RuntimeOrigin::signed(institution),
+            institution,
+            profile.clone()
+        ));
+
+        assert_eq!(
+            InstitutionProfiles::<Test>::get(institution),
+            Some(profile.clone())
+        );
+        assert_eq!(
+            InstitutionDeposits::<Test>::get(institution)
+                .unwrap()
+                .amount,
+            registration_deposit + profile.encoded_size() as u64
+        );
+        System::assert_last_event(Event::InstitutionProfileUpdated { did: institution }.into());
+
+        // Clearing the profile shrinks the deposit again
+        assert_ok!(Did::update_institution_profile(
+            RuntimeOrigin::signed(institution),
+            institution,
+            InstitutionProfile::default()
+        ));
+        assert_eq!(
+            InstitutionDeposits::<Test>::get(institution)
+                .unwrap()
+                .amount,
+            registration_deposit + InstitutionProfile::default().encoded_size() as u64
+        );
+    });
+}
+
+#[test]
+fn update_institution_profile_checks_caller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+
+        assert_ok!(create_did_with_key(
+            institution,
+            [1u8; 32],
+            KeyType::Ed25519
+        ));
+        assert_noop!(
+            Did::update_institution_profile(
+                RuntimeOrigin::signed(institution),
+                institution,
+                valid_profile()
+            ),
+            Error::<Test>::InstitutionNotFound
+        );
+
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(institution),
+            b"MIT".to_vec().try_into().unwrap()
+        ));
+        assert_noop!(
+            Did::update_institution_profile(
+                RuntimeOrigin::signed(2u64),
+                institution,
+                valid_profile()
+            ),
+            Error::<Test>::NotAuthorized
+        );
+    });
+}
+
+#[test]
+fn update_institution_profile_validates_fields() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        setup_institution(institution);
+
+        let invalid_profiles = [
+            (
+                InstitutionProfile {
+                    country: Some(*b"g1"),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidCountryCode,
+            ),
+            (
+                InstitutionProfile {
+                    legal_identifier: Some(field(b"")),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidLegalIdentifier,
+            ),
+            (
+                InstitutionProfile {
+                    legal_identifier: Some(field(b"RC<000>")),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidLegalIdentifier,
+            ),
+            (
+                InstitutionProfile {
+                    website: Some(field(b"https://ox.ac.uk")),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidWebsiteDomain,
+            ),
+            (
+                InstitutionProfile {
+                    website: Some(field(b"localhost")),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidWebsiteDomain,
+            ),
+            (
+                InstitutionProfile {
+                    website: Some(field(b"-ox.ac.uk")),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidWebsiteDomain,
+            ),
+            (
+                InstitutionProfile {
+                    accreditations: vec![field(b"QAA-1"), field(b"QAA-1")].try_into().unwrap(),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidAccreditationReference,
+            ),
+            (
+                InstitutionProfile {
+                    accreditations: vec![field(b"")].try_into().unwrap(),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidAccreditationReference,
+            ),
+            (
+                InstitutionProfile {
+                    sectors: vec![BoundedVec::new()].try_into().unwrap(),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidSector,
+            ),
+            (
+                InstitutionProfile {
+                    sectors: vec![
+                        b"law".to_vec().try_into().unwrap(),
+                        b"LAW".to_vec().try_into().unwrap(),
+                    ]
+                    .try_into()
+                    .unwrap(),
+                    ..valid_profile()
+                },
+                Error::<Test>::InvalidSector,
+            ),
+        ];
+
+        for (profile, error) in invalid_profiles {
+            assert_noop!(
+                Did::update_institution_profile(
+                    RuntimeOrigin::signed(institution),
+                    institution,
+                    profile
+                ),
+                error
+            );
+        }
+    });
+}
+
+#[test]
+fn delete_did_removes_institution_profile() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+
+        setup_institution(institution);
+        assert_ok!(Did::update_institution_profile(
+            RuntimeOrigin::signed(institution),
+            institution,
+            valid_profile()
+        ));
+
+        assert_ok!(Did::delete_did(
+            RuntimeOrigin::signed(institution),
+            institution
+        ));
+
+        assert!(!InstitutionProfiles::<Test>::contains_key(institution));
+        assert_eq!(Balances::free_balance(institution), INITIAL_BALANCE);
+    });
+}
+
+// ============================================================
+// INSTITUTION NAME TESTS
+// ============================================================
+
+fn register_named_institution(account: u64, name: &[u8]) -> DispatchResult {
+    assert_ok!(create_did_with_key(
+        account,
+        [account as u8; 32],
+        KeyType::Ed25519
+    ));
+    Did::register_institution(
+        RuntimeOrigin::signed(account),
+        name.to_vec().try_into().unwrap(),
+    )
+}
+
+#[test]
+fn verified_institution_name_is_unique() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let harvard = 1u64;
+        let impostor = 2u64;
+
+        // Unverified registrations may share a name
+        assert_ok!(register_named_institution(harvard, b"Harvard University"));
+        assert_ok!(register_named_institution(
+            impostor,
+            b"harvard  UNIVERSITY "
+        ));
+
+        assert_ok!(Did::verify_institution(council(), harvard));
+        assert_eq!(
+            Did::institution_by_name(b"Harvard University"),
+            Some(harvard)
+        );
+        assert_eq!(
+            Did::institution_by_name(b" HARVARD university"),
+            Some(harvard)
+        );
+
+        assert_noop!(
+            Did::verify_institution(council(), impostor),
+            Error::<Test>::InstitutionNameTaken
+        );
+        assert_ok!(create_did_with_key(3u64, [3u8; 32], KeyType::Ed25519));
+        assert_noop!(
+            Did::register_institution(
+                RuntimeOrigin::signed(3u64),
+                b"Harvard\tUniversity".to_vec().try_into().unwrap()
+            ),
+            Error::<Test>::InstitutionNameTaken
+        );
+    });
+}
+
+#[test]
+fn institution_name_is_freed_on_revocation_and_deletion() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let first = 1u64;
+        let second = 2u64;
+        let name_hash = Did::institution_name_hash(b"MIT");
+
+        assert_ok!(register_named_institution(first, b"MIT"));
+        assert_ok!(register_named_institution(second, b"MIT"));
+
+        assert_ok!(Did::verify_institution(council(), first));
+        assert_ok!(Did::revoke_institution(council(), first));
+        assert!(!InstitutionNames::<Test>::contains_key(name_hash));
+
+        assert_ok!(Did::verify_institution(council(), second));
+        assert_eq!(Did::institution_by_name(b"MIT"), Some(second));
+
+        // Deleting the unverified namesake leaves the holder in place
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(first), first));
+        assert_eq!(Did::institution_by_name(b"MIT"), Some(second));
+
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(second), second));
+        assert_eq!(Did::institution_by_name(b"MIT"), None);
+    });
+}
+
+#[test]
+fn institution_name_is_freed_when_verification_lapses() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let first = 1u64;
+        let second = 2u64;
+        let until = 1 + VerificationPeriod::get();
+
+        assert_ok!(register_named_institution(first, b"MIT"));
+        assert_ok!(register_named_institution(second, b"MIT"));
+        assert_ok!(Did::verify_institution(council(), first));
+
+        System::set_block_number(until);
+        Did::on_initialize(until);
+        assert_eq!(Did::institution_by_name(b"MIT"), None);
+
+        // Another institution may be verified under the name meanwhile
+        assert_ok!(Did::verify_institution(council(), second));
+        assert_noop!(
+            Did::verify_institution(council(), first),
+            Error::<Test>::InstitutionNameTaken
+        );
+
+        // Once free again, renewing claims it back
+        assert_ok!(Did::revoke_institution(council(), second));
+        assert_ok!(Did::verify_institution(council(), first));
+        assert_eq!(Did::institution_by_name(b"MIT"), Some(first));
+    });
+}
+
+#[test]
+fn migration_v6_indexes_verified_names() {
+    use crate::migrations::v6;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(register_named_institution(1u64, b"MIT"));
+        assert_ok!(register_named_institution(2u64, b"Oxford"));
+        assert_ok!(Did::verify_institution(council(), 1u64));
+
+        // Simulate a pre-index chain
+        let _ = InstitutionNames::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(5).put::<Did>();
+
+        v6::MigrateV5ToV6::<Test>::on_runtime_upgrade();
+
+        assert_eq!(Did::institution_by_name(b"MIT"), Some(1u64));
+        assert_eq!(Did::institution_by_name(b"Oxford"), None);
+        assert_eq!(StorageVersion::get::<Did>(), 6);
+    });
+}
+
+// ============================================================
+// INSTITUTION CLOSURE TESTS
+// ============================================================
+
+#[test]
+fn deregister_institution_closes_it() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        let until = 1 + VerificationPeriod::get();
+
+        assert_ok!(register_named_institution(institution, b"MIT"));
+        assert_ok!(Did::verify_institution(council(), institution));
+
+        System::set_block_number(7);
+        assert_ok!(Did::deregister_institution(RuntimeOrigin::signed(
+            institution
+        )));
+
+        let record = Institutions::<Test>::get(institution).unwrap();
+        assert!(!record.verified);
+        assert_eq!(record.verified_until, None);
+        assert_eq!(ClosedInstitutions::<Test>::get(institution), Some(7));
+        assert!(!VerificationExpiries::<Test>::contains_key(
+            until,
+            institution
+        ));
+        assert_eq!(Did::institution_by_name(b"MIT"), None);
+        System::assert_last_event(Event::InstitutionDeregistered { did: institution }.into());
+
+        assert_noop!(
+            Did::deregister_institution(RuntimeOrigin::signed(institution)),
+            Error::<Test>::InstitutionClosed
+        );
+        assert_noop!(
+            Did::deregister_institution(RuntimeOrigin::signed(2u64)),
+            Error::<Test>::InstitutionNotFound
+        );
+    });
+}
+
+#[test]
+fn close_institution_requires_governance() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+
+        setup_institution(institution);
+        assert_noop!(
+            Did::close_institution(RuntimeOrigin::signed(institution), institution),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Did::close_institution(council(), institution));
+        assert_eq!(ClosedInstitutions::<Test>::get(institution), Some(1));
+        System::assert_last_event(Event::InstitutionClosed { did: institution }.into());
+
+        assert_noop!(
+            Did::close_institution(council(), institution),
+            Error::<Test>::InstitutionClosed
+        );
+    });
+}
+
+#[test]
+fn closed_institution_cannot_be_verified_again() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+
+        setup_institution(institution);
+        assert_ok!(Did::verify_institution(council(), institution));
+        assert_ok!(Did::close_institution(council(), institution));
+
+        assert_noop!(
+            Did::verify_institution(council(), institution),
+            Error::<Test>::InstitutionClosed
+        );
+        assert_noop!(
+            Did::renew_institution_verification(council(), institution),
+            Error::<Test>::InstitutionClosed
+        );
+        assert_noop!(
+            Did::register_institution(
+                RuntimeOrigin::signed(institution),
+                b"University".to_vec().try_into().unwrap()
+            ),
+            Error::<Test>::InstitutionAlreadyRegistered
+        );
+    });
+}
+
+// ============================================================
+// INSTITUTION SUCCESSION TESTS
+// ============================================================
+
+#[test]
+fn set_successor_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let merged = 1u64;
+        let successor = 2u64;
+
+        assert_ok!(register_named_institution(merged, b"Old College"));
+        assert_ok!(register_named_institution(successor, b"New University"));
+
+        assert_noop!(
+            Did::set_successor(RuntimeOrigin::signed(merged), merged, successor),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Did::set_successor(council(), merged, successor));
+        assert_eq!(Successors::<Test>::get(merged), Some(successor));
+        assert!(Did::is_successor(&successor, &merged));
+        assert!(!Did::is_successor(&merged, &successor));
+        System::assert_last_event(
+            Event::SuccessorSet {
+                institution: merged,
+                successor,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn set_successor_follows_chain_and_rejects_cycles() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(register_named_institution(1, b"First"));
+        assert_ok!(register_named_institution(2, b"Second"));
+        assert_ok!(register_named_institution(3, b"Third"));
+
+        assert_noop!(
+            Did::set_successor(council(), 1, 1),
+            Error::<Test>::InvalidSuccessor
+        );
+
+        assert_ok!(Did::set_successor(council(), 1, 2));
+        assert_ok!(Did::set_successor(council(), 2, 3));
+        assert!(Did::is_successor(&3, &1));
+
+        assert_noop!(
+            Did::set_successor(council(), 3, 1),
+            Error::<Test>::InvalidSuccessor
+        );
+        assert_noop!(
+            Did::set_successor(council(), 2, 1),
+            Error::<Test>::InvalidSuccessor
+        );
+    });
+}
+
+#[test]
+fn delete_did_refuses_while_referenced() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let body = 10u64;
+        let institution = 1u64;
+
+        setup_accreditation_body(body, AccreditationScope::Country(*b"GB"));
+        setup_institution(institution);
+        setup_profile(institution, *b"GB", &[]);
+        assert_ok!(Did::verify_institution(
+            RuntimeOrigin::signed(body),
+            institution
+        ));
+
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(body), body),
+            Error::<Test>::DidStillReferenced
+        );
+
+        // A removed body is still the accreditor of record
+        assert_ok!(Did::remove_accreditation_body(council(), body));
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(body), body),
+            Error::<Test>::DidStillReferenced
+        );
+
+        assert_ok!(Did::revoke_institution(council(), institution));
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(body), body));
+
+        let merged = 2u64;
+        let successor = 3u64;
+        assert_ok!(register_named_institution(merged, b"Old College"));
+        assert_ok!(register_named_institution(successor, b"New University"));
+        assert_ok!(Did::set_successor(council(), merged, successor));
+
+        assert_noop!(
+            Did::delete_did(RuntimeOrigin::signed(successor), successor),
+            Error::<Test>::DidStillReferenced
+        );
+
+        // Deleting the merged institution drops the succession
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(merged), merged));
+        assert!(!Predecessors::<Test>::contains_key(successor, merged));
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(successor), successor));
+    });
+}
+
+#[test]
+fn set_successor_replaces_previous_successor() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(register_named_institution(1, b"First"));
+        assert_ok!(register_named_institution(2, b"Second"));
+        assert_ok!(register_named_institution(3, b"Third"));
+
+        assert_ok!(Did::set_successor(council(), 1, 2));
+        assert!(Predecessors::<Test>::contains_key(2, 1));
+
+        assert_ok!(Did::set_successor(council(), 1, 3));
+        assert!(!Predecessors::<Test>::contains_key(2, 1));
+        assert!(Predecessors::<Test>::contains_key(3, 1));
+    });
+}
+
+#[test]
+fn migration_v12_indexes_predecessors() {
+    use crate::migrations::v12;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        Successors::<Test>::insert(1u64, 2u64);
+        Successors::<Test>::insert(2u64, 3u64);
+        StorageVersion::new(11).put::<Did>();
+
+        v12::MigrateV11ToV12::<Test>::on_runtime_upgrade();
+
+        assert!(Predecessors::<Test>::contains_key(2u64, 1u64));
+        assert!(Predecessors::<Test>::contains_key(3u64, 2u64));
+        assert_eq!(StorageVersion::get::<Did>(), 12);
+    });
+}
+
+#[test]
+fn set_successor_requires_open_registered_institutions() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let merged = 1u64;
+        let successor = 2u64;
+
+        assert_ok!(register_named_institution(merged, b"Old College"));
+        assert_ok!(create_did_with_key(successor, [2u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::set_successor(council(), merged, successor),
+            Error::<Test>::InstitutionNotFound
+        );
+
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(successor),
+            b"New University".to_vec().try_into().unwrap()
+        ));
+        assert_ok!(Did::deregister_institution(RuntimeOrigin::signed(
+            successor
+        )));
+
+        assert_noop!(
+            Did::set_successor(council(), merged, successor),
+            Error::<Test>::InstitutionClosed
+        );
+    });
+}
+
+// ============================================================
+// ACCOUNT LINK TESTS
+// ============================================================
+
+#[test]
+fn link_account_lets_account_act_for_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let staff = 7u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        assert_eq!(AccountDids::<Test>::get(did), Some(did));
+        let deposit_before = DidDeposits::<Test>::get(did).unwrap().amount;
+        let nonce_before = KeyNonces::<Test>::get(did);
+
+        assert_ok!(link_account(staff, did, [1u8; 32]));
+
+        assert_eq!(
+            LinkedAccounts::<Test>::get(did).into_inner(),
+            vec![did, staff]
+        );
+        assert_eq!(AccountDids::<Test>::get(staff), Some(did));
+        assert_eq!(Did::did_by_account(&staff), Some(did));
+        assert_eq!(KeyNonces::<Test>::get(did), nonce_before + 1);
+        System::assert_last_event(
+            Event::AccountLinked {
+                did,
+                account: staff,
+            }
+            .into(),
+        );
+
+        // The controller pays for the link
+        let deposit = DidDeposits::<Test>::get(did).unwrap();
+        assert_eq!(deposit.owner, did);
+        assert_eq!(deposit.amount, deposit_before + 8);
+
+        // The linked account registers the DID, not itself, as an institution
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(staff),
+            b"MIT".to_vec().try_into().unwrap()
+        ));
+        assert!(Institutions::<Test>::contains_key(did));
+        assert!(!Institutions::<Test>::contains_key(staff));
+    });
+}
+
+#[test]
+fn link_account_requires_did_consent() {
+    use frame::deps::sp_core::{ed25519, Pair};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let staff = 7u64;
+        let key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+        let sign = |account: u64| {
+            let payload = Did::account_link_payload(&did, &account);
+            crate::KeyProof::from(ed25519::Pair::from_seed(&[1u8; 32]).sign(&payload))
+        };
+
+        assert_noop!(
+            link_account(staff, did, [1u8; 32]),
+            Error::<Test>::DidNotFound
+        );
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(create_did_with_key(2u64, [2u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            link_account(staff, did, [3u8; 32]),
+            Error::<Test>::PublicKeyNotFound
+        );
+        // Consent given for another account
+        assert_noop!(
+            Did::link_account(RuntimeOrigin::signed(staff), did, key_id, sign(8u64)),
+            Error::<Test>::InvalidKeyProof
+        );
+        // An account already acting for a DID
+        assert_noop!(
+            link_account(2u64, did, [1u8; 32]),
+            Error::<Test>::AccountAlreadyLinked
+        );
+
+        // Consent cannot be replayed once the account has been unlinked
+        let proof = sign(staff);
+        assert_ok!(Did::link_account(
+            RuntimeOrigin::signed(staff),
+            did,
+            key_id,
+            proof.clone()
+        ));
+        assert_ok!(Did::unlink_account(
+            RuntimeOrigin::signed(staff),
+            did,
+            staff
+        ));
+        assert_noop!(
+            Did::link_account(RuntimeOrigin::signed(staff), did, key_id, proof),
+            Error::<Test>::InvalidKeyProof
+        );
+
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(did), did));
+        assert_noop!(
+            link_account(staff, did, [1u8; 32]),
+            Error::<Test>::DidInactive
+        );
+    });
+}
+
+#[test]
+fn link_account_is_bounded() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        for account in 2..=MaxLinkedAccounts::get() as u64 {
+            assert_ok!(link_account(account, did, [1u8; 32]));
+        }
+
+        assert_noop!(
+            link_account(10u64, did, [1u8; 32]),
+            Error::<Test>::TooManyLinkedAccounts
+        );
+    });
+}
+
+#[test]
+fn unlink_account_by_account_or_controller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(link_account(7u64, did, [1u8; 32]));
+        assert_ok!(link_account(8u64, did, [1u8; 32]));
+
+        assert_noop!(
+            Did::unlink_account(RuntimeOrigin::signed(8u64), did, 7u64),
+            Error::<Test>::NotAuthorized
+        );
+        assert_noop!(
+            Did::unlink_account(RuntimeOrigin::signed(did), did, 9u64),
+            Error::<Test>::AccountNotLinked
+        );
+
+        assert_ok!(Did::unlink_account(RuntimeOrigin::signed(7u64), did, 7u64));
+        System::assert_last_event(Event::AccountUnlinked { did, account: 7u64 }.into());
+        assert_ok!(Did::unlink_account(RuntimeOrigin::signed(did), did, 8u64));
+
+        assert_eq!(LinkedAccounts::<Test>::get(did).into_inner(), vec![did]);
+        assert_eq!(AccountDids::<Test>::get(7u64), None);
+        assert_eq!(AccountDids::<Test>::get(8u64), None);
+
+        // Unlinked accounts no longer act for the DID
+        assert_noop!(
+            Did::register_institution(
+                RuntimeOrigin::signed(8u64),
+                b"MIT".to_vec().try_into().unwrap()
+            ),
+            Error::<Test>::DidNotFound
+        );
+    });
+}
+
+#[test]
+fn delete_did_unlinks_accounts() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(link_account(7u64, did, [1u8; 32]));
+
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(did), did));
+
+        assert!(!LinkedAccounts::<Test>::contains_key(did));
+        assert_eq!(AccountDids::<Test>::get(did), None);
+        assert_eq!(AccountDids::<Test>::get(7u64), None);
+    });
+}
+
+#[test]
+fn recovered_institution_keeps_its_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        let new_account = 5u64;
+
+        setup_recovery(institution, vec![2], 1);
+        assert_ok!(Did::register_institution(
+            RuntimeOrigin::signed(institution),
+            b"MIT".to_vec().try_into().unwrap()
+        ));
+        assert_ok!(Did::verify_institution(council(), institution));
+
+        assert_ok!(Did::vouch_recovery(
+            RuntimeOrigin::signed(2u64),
+            institution,
+            new_account
+        ));
+        System::set_block_number(1 + RecoveryDelay::get());
+        assert_ok!(Did::claim_recovery(
+            RuntimeOrigin::signed(new_account),
+            institution
+        ));
+
+        assert!(Institutions::<Test>::get(institution).unwrap().verified);
+        assert_eq!(Did::institution_by_name(b"MIT"), Some(institution));
+
+        // The registration deposit follows the controller
+        let deposit = InstitutionDeposits::<Test>::get(institution).unwrap();
+        assert_eq!(deposit.owner, new_account);
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::InstitutionDeposit.into(), &institution),
+            0
+        );
+        assert_eq!(
+            Balances::balance_on_hold(&HoldReason::InstitutionDeposit.into(), &new_account),
+            deposit.amount
+        );
+    });
+}
+
+// ============================================================
+// DID CALL TESTS
+// ============================================================
+
+#[test]
+fn submit_did_call_dispatches_with_did_origin() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let relayer = 9u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        // An unlinked fee payer registers the DID as an institution using its key
+        let call = RuntimeCall::Did(crate::Call::register_institution {
+            name: b"MIT".to_vec().try_into().unwrap(),
+        });
+        assert_ok!(submit_did_call(relayer, did, call, [1u8; 32]));
+
+        assert!(Institutions::<Test>::contains_key(did));
+        assert!(!Institutions::<Test>::contains_key(relayer));
+        assert_eq!(AccountDids::<Test>::get(relayer), None);
+        assert_eq!(DidCallNonces::<Test>::get(did), 1);
+        System::assert_last_event(
+            Event::DidCallDispatched {
+                did,
+                submitter: relayer,
+                result: Ok(()),
+            }
+            .into(),
+        );
+
+        // The controller, not the submitter, pays the registration deposit
+        assert_eq!(InstitutionDeposits::<Test>::get(did).unwrap().owner, did);
+    });
+}
+
+#[test]
+fn submit_did_call_checks_nonce_and_signature() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let relayer = 9u64;
+        let key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+        let call = RuntimeCall::Did(crate::Call::register_institution {
+            name: b"MIT".to_vec().try_into().unwrap(),
+        });
+        let submit = |nonce: u64, key_id: [u8; 32], signature: crate::KeyProof| {
+            Did::submit_did_call(
+                RuntimeOrigin::signed(relayer),
+                did,
+                Box::new(call.clone()),
+                nonce,
+                key_id,
+                signature,
+            )
+        };
+
+        assert_noop!(
+            submit(0, key_id, sign_did_call(did, &call, 0, relayer, [1u8; 32])),
+            Error::<Test>::DidNotFound
+        );
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            submit(1, key_id, sign_did_call(did, &call, 1, relayer, [1u8; 32])),
+            Error::<Test>::InvalidDidCallNonce
+        );
+
+        // Signed by another key, or for another submitter
+        assert_noop!(
+            submit(0, key_id, sign_did_call(did, &call, 0, relayer, [2u8; 32])),
+            Error::<Test>::InvalidDidCallSignature
+        );
+        assert_noop!(
+            submit(0, key_id, sign_did_call(did, &call, 0, 8u64, [1u8; 32])),
+            Error::<Test>::InvalidDidCallSignature
+        );
+
+        // Keys need an authentication or assertion relationship
+        assert_ok!(add_key(did, did, [2u8; 32], KeyType::Ed25519));
+        assert_noop!(
+            submit(
+                0,
+                test_key_id([2u8; 32], KeyType::Ed25519),
+                sign_did_call(did, &call, 0, relayer, [2u8; 32])
+            ),
+            Error::<Test>::RelationshipNotAssigned
+        );
+
+        // A signature cannot be replayed
+        let signature = sign_did_call(did, &call, 0, relayer, [1u8; 32]);
+        assert_ok!(submit(0, key_id, signature.clone()));
+        assert_noop!(
+            submit(0, key_id, signature),
+            Error::<Test>::InvalidDidCallNonce
+        );
+
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(did), did));
+        assert_noop!(
+            submit(1, key_id, sign_did_call(did, &call, 1, relayer, [1u8; 32])),
+            Error::<Test>::DidInactive
+        );
+    });
+}
+
+#[test]
+fn failed_did_call_consumes_nonce() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let relayer = 9u64;
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        // Controller-only calls still require the controller's account
+        let call = RuntimeCall::Did(crate::Call::delete_did { did });
+        assert_ok!(submit_did_call(relayer, did, call, [1u8; 32]));
+
+        assert!(DidDocuments::<Test>::contains_key(did));
+        assert_eq!(DidCallNonces::<Test>::get(did), 1);
+        System::assert_last_event(
+            Event::DidCallDispatched {
+                did,
+                submitter: relayer,
+                result: Err(DispatchError::BadOrigin),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn migration_v7_links_accounts_to_dids() {
+    use crate::migrations::{v2, v5, v7};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(register_named_institution(1u64, b"Old College"));
+        assert_ok!(register_named_institution(2u64, b"New University"));
+        assert_ok!(Did::verify_institution(council(), 2u64));
+        assert_ok!(Did::set_successor(council(), 1u64, 2u64));
+        let did_doc = DidDocuments::<Test>::get(1u64).unwrap();
+
+        // Simulate a chain from before accounts were linked
+        let _ = LinkedAccounts::<Test>::clear(u32::MAX, None);
+        let _ = AccountDids::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(6).put::<Did>();
+
+        v7::MigrateV6ToV7::<Test>::on_runtime_upgrade();
+
+        for account in [1u64, 2] {
+            assert_eq!(
+                LinkedAccounts::<Test>::get(account).into_inner(),
+                vec![account]
+            );
+            assert_eq!(AccountDids::<Test>::get(account), Some(account));
+        }
+        assert_eq!(
+            DidDocuments::<Test>::get(1u64).unwrap().public_keys,
+            did_doc.public_keys
+        );
+        assert!(v2::DidDocuments::<Test>::contains_key(1u64));
+        assert!(v5::Institutions::<Test>::contains_key(2u64));
+        assert_eq!(Institutions::<Test>::get(2u64).unwrap().did, 2u64);
+        assert_eq!(Successors::<Test>::get(1u64), Some(2u64));
+        assert_eq!(Did::institution_by_name(b"new university"), Some(2u64));
+        assert!(InstitutionDeposits::<Test>::contains_key(1u64));
+        assert_eq!(StorageVersion::get::<Did>(), 7);
+    });
+}
+
+// ============================================================
+// KEY HISTORY TESTS
+// ============================================================
+
+#[test]
+fn did_document_at_reconstructs_past_keys() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let first = test_key_id([1u8; 32], KeyType::Ed25519);
+        let second = test_key_id([2u8; 32], KeyType::Ed25519);
+        let key_ids = |block: u64| {
+            Did::did_document_at(&did, block).map(|keys| {
+                keys.into_iter()
+                    .map(|k| (k.key_id, k.relationships.into_inner()))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(Did::did_document_at(&did, 1), None);
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        System::set_block_number(5);
+        assert_ok!(add_key(did, did, [2u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(did),
+            did,
+            second,
+            VerificationRelationship::Authentication
+        ));
+
+        System::set_block_number(10);
+        assert_ok!(Did::remove_public_key(
+            RuntimeOrigin::signed(did),
+            did,
+            first
+        ));
+
+        let initial = vec![
+            VerificationRelationship::Authentication,
+            VerificationRelationship::AssertionMethod,
+        ];
+        let authentication = vec![VerificationRelationship::Authentication];
+
+        assert_eq!(key_ids(0), None);
+        assert_eq!(key_ids(1), Some(vec![(first, initial.clone())]));
+        assert_eq!(key_ids(4), Some(vec![(first, initial.clone())]));
+        // Changes within a block show as the state at its end
+        assert_eq!(
+            key_ids(5),
+            Some(vec![
+                (first, initial.clone()),
+                (second, authentication.clone())
+            ])
+        );
+        assert_eq!(key_ids(9).unwrap().len(), 2);
+        assert_eq!(key_ids(10), Some(vec![(second, authentication.clone())]));
+        assert_eq!(key_ids(100), Some(vec![(second, authentication)]));
+
+        // History goes with the DID
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(did), did));
+        assert_eq!(key_ids(1), None);
+        assert!(!KeyHistories::<Test>::contains_key(did));
+    });
+}
+
+#[test]
+fn key_history_drops_oldest_expired_records() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let first = test_key_id([1u8; 32], KeyType::Ed25519);
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        // Each key is added in one block and removed in the next, leaving an
+        // expired record
+        for i in 0..MaxKeyHistory::get() as u64 {
+            let seed = [i as u8 + 2; 32];
+            System::set_block_number(2 + 2 * i);
+            assert_ok!(add_key(did, did, seed, KeyType::Ed25519));
+            System::set_block_number(3 + 2 * i);
+            assert_ok!(Did::remove_public_key(
+                RuntimeOrigin::signed(did),
+                did,
+                test_key_id(seed, KeyType::Ed25519)
+            ));
+        }
+
+        let history = KeyHistories::<Test>::get(did).unwrap();
+        assert_eq!(history.records.len() as u32, MaxKeyHistory::get());
+        // The key expiring first was dropped, so earlier blocks are unknown
+        assert_eq!(history.complete_from, 3);
+        assert_eq!(Did::did_document_at(&did, 2), None);
+        assert_eq!(Did::did_document_at(&did, 3).unwrap().len(), 1);
+        assert_eq!(Did::did_document_at(&did, 4).unwrap().len(), 2);
+
+        // The current key is never dropped
+        let keys = Did::did_document_at(&did, 1000).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key_id, first);
+    });
+}
+
+#[test]
+fn migration_v8_starts_key_history() {
+    use crate::migrations::v8;
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(create_did_with_key(1u64, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(create_did_with_key(2u64, [2u8; 32], KeyType::Ed25519));
+        System::set_block_number(5);
+        assert_ok!(add_key(2u64, 2u64, [3u8; 32], KeyType::Ed25519));
+
+        // Simulate a chain from before key histories were kept
+        let _ = KeyHistories::<Test>::clear(u32::MAX, None);
+        StorageVersion::new(7).put::<Did>();
+
+        System::set_block_number(20);
+        v8::MigrateV7ToV8::<Test>::on_runtime_upgrade();
+
+        // A DID never updated has had its keys since creation
+        assert_eq!(KeyHistories::<Test>::get(1u64).unwrap().complete_from, 1);
+        assert_eq!(Did::did_document_at(&1u64, 1).unwrap().len(), 1);
+
+        // Otherwise its history starts at the upgrade
+        assert_eq!(Did::did_document_at(&2u64, 19), None);
+        assert_eq!(Did::did_document_at(&2u64, 20).unwrap().len(), 2);
+        assert_eq!(StorageVersion::get::<Did>(), 8);
+    });
+}
+
+#[test]
+fn migration_v9_adds_empty_sectors() {
+    use crate::migrations::{v7::InstitutionProfileV7, v9};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let institution = 1u64;
+        v9::InstitutionProfiles::<Test>::insert(
+            institution,
+            InstitutionProfileV7 {
+                country: Some(*b"GB"),
+                legal_identifier: None,
+                website: Some(field(b"ox.ac.uk")),
+                logo_hash: None,
+                accreditations: BoundedVec::new(),
+            },
+        );
+        StorageVersion::new(8).put::<Did>();
+
+        v9::MigrateV8ToV9::<Test>::on_runtime_upgrade();
+
+        let profile = InstitutionProfiles::<Test>::get(institution).unwrap();
+        assert_eq!(profile.country, Some(*b"GB"));
+        assert_eq!(profile.website, Some(field(b"ox.ac.uk")));
+        assert!(profile.sectors.is_empty());
+        assert_eq!(StorageVersion::get::<Did>(), 9);
+    });
+}
+
+// ============================================================
+// KEY COMPROMISE TESTS
+// ============================================================
+
+#[test]
+fn report_key_compromised_keeps_key_on_record() {
+    use crate::runtime_api::KeyStatus;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let did = 1u64;
+        let old_key = test_key_id([1u8; 32], KeyType::Ed25519);
+        let new_key = test_key_id([2u8; 32], KeyType::Ed25519);
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+        assert_ok!(add_key(did, did, [2u8; 32], KeyType::Ed25519));
+        assert_ok!(Did::add_verification_relationship(
+            RuntimeOrigin::signed(did),
+            did,
+            new_key,
+            VerificationRelationship::Authentication
+        ));
+
+        System::set_block_number(10);
+        assert_ok!(Did::report_key_compromised(
+            RuntimeOrigin::signed(did),
+            old_key,
+            5
+        ));
+        System::assert_last_event(
+            Event::KeyCompromised {
+                owner: did,
+                key_id: old_key,
+                compromised_since: 5,
+            }
+            .into(),
+        );
+
+        // Out of the document, but still on record and attributed to the DID
+        let did_doc = DidDocuments::<Test>::get(did).unwrap();
+        assert!(!did_doc.public_keys.iter().any(|k| k.key_id == old_key));
+        let compromised = CompromisedKeys::<Test>::get(did);
+        assert_eq!(compromised.len(), 1);
+        assert_eq!(compromised[0].key.key_id, old_key);
+        assert_eq!(compromised[0].reported_at, 10);
+        assert_eq!(DidByKeyId::<Test>::get(old_key), Some(did));
+
+        // Signatures before the compromise remain valid
+        assert_eq!(Did::key_status_at(&did, &old_key, 4), KeyStatus::Valid);
+        assert_eq!(
+            Did::key_status_at(&did, &old_key, 5),
+            KeyStatus::Compromised { since: 5 }
+        );
+        assert_eq!(
+            Did::key_status_at(&did, &old_key, 20),
+            KeyStatus::Compromised { since: 5 }
+        );
+        assert_eq!(Did::key_status_at(&did, &new_key, 20), KeyStatus::Valid);
+        assert_eq!(Did::key_status_at(&did, &new_key, 0), KeyStatus::Unknown);
+        assert_eq!(
+            Did::key_status_at(&did, &test_key_id([3u8; 32], KeyType::Ed25519), 4),
+            KeyStatus::NotInDocument
+        );
+        assert_eq!(Did::did_document_at(&did, 4).unwrap().len(), 2);
+        assert_eq!(Did::did_document_at(&did, 5).unwrap().len(), 1);
+
+        // The key cannot be used again
+        assert_noop!(
+            add_key(did, did, [1u8; 32], KeyType::Ed25519),
+            Error::<Test>::KeyAlreadyInUse
+        );
+
+        // Nor once the DID is deleted
+        assert_ok!(Did::delete_did(RuntimeOrigin::signed(did), did));
+        assert_eq!(CompromisedKeys::<Test>::get(did).len(), 1);
+        assert_eq!(DidByKeyId::<Test>::get(old_key), Some(did));
+        assert_eq!(DidByKeyId::<Test>::get(new_key), None);
+        assert_noop!(
+            create_did_with_key(2u64, [1u8; 32], KeyType::Ed25519),
+            Error::<Test>::KeyAlreadyInUse
+        );
+    });
+}
+
+#[test]
+fn report_key_compromised_checks_caller_and_key() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+
+        let did = 1u64;
+        let key_id = test_key_id([1u8; 32], KeyType::Ed25519);
+
+        assert_noop!(
+            Did::report_key_compromised(RuntimeOrigin::signed(did), key_id, 5),
+            Error::<Test>::PublicKeyNotFound
+        );
+
+        assert_ok!(create_did_with_key(did, [1u8; 32], KeyType::Ed25519));
+
+        assert_noop!(
+            Did::report_key_compromised(RuntimeOrigin::signed(2u64), key_id, 5),
+            Error::<Test>::NotAuthorized
+        );
+        assert_noop!(
+            Did::report_key_compromised(RuntimeOrigin::signed(did), key_id, 11),
+            Error::<Test>::InvalidCompromiseBlock
+        );
+        // A replacement authentication key must be added first
+        assert_noop!(
+            Did::report_key_compromised(RuntimeOrigin::signed(did), key_id, 5),
+            Error::<Test>::LastAuthenticationKey
+        );
+    });
+}
+
+// ============================================================
+// INTEGRATION TESTS
+// ============================================================
+
+#[test]
+fn complete_did_lifecycle() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let account = 1u64;
+
+        // 1. Create DID
+        assert_ok!(create_did_with_key(account, [1u8; 32], KeyType::Ed25519));
+
+        // 2. Add multiple keys
+        assert_ok!(add_key(account, account, [2u8; 32], KeyType::Sr25519));
+
+        assert_ok!(add_key(account, account, [3u8; 32], KeyType::Ecdsa));
+
         // 3. Register as institution
         let name = b"MIT".to_vec().try_into().unwrap();
         assert_ok!(Did::register_institution(
@@ -595,20 +4139,20 @@ fn complete_did_lifecycle() {
         ));
 
         // 4. Verify institution
-        assert_ok!(Did::verify_institution(RuntimeOrigin::root(), account));
+        assert_ok!(Did::verify_institution(council(), account));
 
         // 5. Deactivate DID
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(account), account));
 
         // 6. Reactivate DID
-        assert_ok!(Did::reactivate_did(RuntimeOrigin::signed(account)));
+        assert_ok!(Did::reactivate_did(RuntimeOrigin::signed(account), account));
 
         // Verify final state
         let did_doc = DidDocuments::<Test>::get(account).unwrap();
         assert_eq!(did_doc.public_keys.len(), 3);
-        assert_eq!(did_doc.active, true);
+        assert!(did_doc.active);
 
         let institution = Institutions::<Test>::get(account).unwrap();
-        assert_eq!(institution.verified, true);
+        assert!(institution.verified);
     });
 }
@@ -10,6 +10,31 @@ pub trait WeightInfo {
     fn register_institution() -> Weight;
     fn verify_institution() -> Weight;
     fn revoke_institution() -> Weight;
+    fn transfer_controller() -> Weight;
+    fn accept_controller() -> Weight;
+    fn cancel_controller_transfer() -> Weight;
+    fn add_verification_relationship() -> Weight;
+    fn remove_verification_relationship() -> Weight;
+    fn add_service_endpoint() -> Weight;
+    fn remove_service_endpoint() -> Weight;
+    fn delete_did() -> Weight;
+    fn set_recovery_config() -> Weight;
+    fn remove_recovery_config() -> Weight;
+    fn vouch_recovery() -> Weight;
+    fn cancel_recovery() -> Weight;
+    fn claim_recovery() -> Weight;
+    fn register_accreditation_body() -> Weight;
+    fn remove_accreditation_body() -> Weight;
+    fn renew_institution_verification() -> Weight;
+    fn lapse_institution_verification() -> Weight;
+    fn update_institution_profile() -> Weight;
+    fn deregister_institution() -> Weight;
+    fn close_institution() -> Weight;
+    fn set_successor() -> Weight;
+    fn link_account() -> Weight;
+    fn unlink_account() -> Weight;
+    fn submit_did_call() -> Weight;
+    fn report_key_compromised() -> Weight;
 }
 
 pub struct SubstrateWeight;
@@ -39,4 +64,79 @@ impl WeightInfo for SubstrateWeight {
     fn revoke_institution() -> Weight {
         Weight::from_parts(10_000_000, 0)
     }
+    fn transfer_controller() -> Weight {
+        Weight::from_parts(12_000_000, 0)
+    }
+    fn accept_controller() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn cancel_controller_transfer() -> Weight {
+        Weight::from_parts(10_000_000, 0)
+    }
+    fn add_verification_relationship() -> Weight {
+        Weight::from_parts(12_000_000, 0)
+    }
+    fn remove_verification_relationship() -> Weight {
+        Weight::from_parts(12_000_000, 0)
+    }
+    fn add_service_endpoint() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn remove_service_endpoint() -> Weight {
+        Weight::from_parts(12_000_000, 0)
+    }
+    fn delete_did() -> Weight {
+        Weight::from_parts(25_000_000, 0)
+    }
+    fn set_recovery_config() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn remove_recovery_config() -> Weight {
+        Weight::from_parts(10_000_000, 0)
+    }
+    fn vouch_recovery() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn cancel_recovery() -> Weight {
+        Weight::from_parts(10_000_000, 0)
+    }
+    fn claim_recovery() -> Weight {
+        Weight::from_parts(30_000_000, 0)
+    }
+    fn register_accreditation_body() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn remove_accreditation_body() -> Weight {
+        Weight::from_parts(10_000_000, 0)
+    }
+    fn renew_institution_verification() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn lapse_institution_verification() -> Weight {
+        Weight::from_parts(10_000_000, 0)
+    }
+    fn update_institution_profile() -> Weight {
+        Weight::from_parts(20_000_000, 0)
+    }
+    fn deregister_institution() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn close_institution() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn set_successor() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn link_account() -> Weight {
+        Weight::from_parts(30_000_000, 0)
+    }
+    fn unlink_account() -> Weight {
+        Weight::from_parts(15_000_000, 0)
+    }
+    fn submit_did_call() -> Weight {
+        Weight::from_parts(40_000_000, 0)
+    }
+    fn report_key_compromised() -> Weight {
+        Weight::from_parts(25_000_000, 0)
+    }
 }
@@ -11,18 +11,33 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod migrations;
 pub mod weights;
 pub use weights::WeightInfo;
 
 #[frame::pallet]
 pub mod pallet {
-    use frame::prelude::*;
     use crate::WeightInfo;
+    use frame::prelude::*;
+
+    /// The in-code storage version
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
-    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen, DecodeWithMemTracking)]
+    #[derive(
+        Clone,
+        Encode,
+        Decode,
+        Eq,
+        PartialEq,
+        RuntimeDebug,
+        TypeInfo,
+        MaxEncodedLen,
+        DecodeWithMemTracking,
+    )]
     pub enum EndorsementType {
         /// General professional endorsement
         Professional,
@@ -56,9 +71,9 @@ pub mod pallet {
     #[scale_info(skip_type_params(T))]
     pub struct Endorsement<T: Config> {
         /// Who gave the endorsement
-        pub endorser: T::AccountId,
+        pub endorser: T::DidIdentifier,
         /// Who received the endorsement
-        pub endorsee: T::AccountId,
+        pub endorsee: T::DidIdentifier,
         /// Type of endorsement
         pub endorsement_type: EndorsementType,
         /// Optional comment
@@ -88,32 +103,32 @@ pub mod pallet {
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         EndorsementCreated {
-            endorser: T::AccountId,
-            endorsee: T::AccountId,
+            endorser: T::DidIdentifier,
+            endorsee: T::DidIdentifier,
             endorsement_type: EndorsementType,
             weight: u8,
         },
         EndorsementRemoved {
-            endorser: T::AccountId,
-            endorsee: T::AccountId,
+            endorser: T::DidIdentifier,
+            endorsee: T::DidIdentifier,
         },
         ReputationUpdated {
-            account: T::AccountId,
+            did: T::DidIdentifier,
             new_score: u32,
         },
         CredentialIssuanceRecorded {
-            issuer: T::AccountId,
+            issuer: T::DidIdentifier,
         },
         CredentialVerificationRecorded {
-            verifier: T::AccountId,
-        }
+            verifier: T::DidIdentifier,
+        },
     }
 
     // ================== Errors ==================
 
     #[pallet::error]
     pub enum Error<T> {
-        /// Account has no DID
+        /// Account is not linked to a DID
         NoDid,
         /// DID is not active
         DidNotActive,
@@ -133,49 +148,43 @@ pub mod pallet {
         NotVerifiedInstitution,
     }
 
-
-    /// Reputation scores for each account
+    /// Reputation scores for each DID
     #[pallet::storage]
     #[pallet::getter(fn reputation_scores)]
-    pub type ReputationScores<T: Config> = StorageMap<
-        _,
-        Blake2_128Concat,
-        T::AccountId,
-        ReputationScore,
-        ValueQuery,
-    >;
+    pub type ReputationScores<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::DidIdentifier, ReputationScore, ValueQuery>;
 
-    /// Endorsements given by an account
+    /// Endorsements given by a DID
     #[pallet::storage]
     #[pallet::getter(fn endorsements_given)]
     pub type EndorsementsGiven<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
-        T::AccountId,
+        T::DidIdentifier,
         BoundedVec<Endorsement<T>, T::MaxEndorsements>,
         ValueQuery,
     >;
 
-    /// Endorsements received by an account
+    /// Endorsements received by a DID
     #[pallet::storage]
     #[pallet::getter(fn endorsements_received)]
     pub type EndorsementsReceived<T: Config> = StorageMap<
         _,
         Blake2_128Concat,
-        T::AccountId,
+        T::DidIdentifier,
         BoundedVec<Endorsement<T>, T::MaxEndorsements>,
         ValueQuery,
     >;
 
-    /// Track if one account has already endorsed another
+    /// Track if one DID has already endorsed another
     #[pallet::storage]
     #[pallet::getter(fn has_endorsed)]
     pub type HasEndorsed<T: Config> = StorageDoubleMap<
         _,
         Blake2_128Concat,
-        T::AccountId, // endorser
+        T::DidIdentifier, // endorser
         Blake2_128Concat,
-        T::AccountId, // endorsee
+        T::DidIdentifier, // endorsee
         bool,
         ValueQuery,
     >;
@@ -183,11 +192,11 @@ pub mod pallet {
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Create an endorsement for another account
+        /// Endorse another DID as the DID the caller is linked to
         ///
         /// # Arguments
-        /// * `origin` - Must have an active DID
-        /// * `endorsee` - Account to endorse
+        /// * `origin` - Must be linked to an active DID
+        /// * `endorsee` - DID to endorse
         /// * `endorsement_type` - Type of endorsement
         /// * `comment` - Optional comment
         /// * `weight` - Strength of endorsement (1-10)
@@ -196,12 +205,12 @@ pub mod pallet {
 
         pub fn endorse(
             origin: OriginFor<T>,
-            endorsee: T::AccountId,
+            endorsee: T::DidIdentifier,
             endorsement_type: EndorsementType,
             comment: BoundedVec<u8, ConstU32<256>>,
             weight: u8,
         ) -> DispatchResult {
-            let endorser = ensure_signed(origin)?;
+            let endorser = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NoDid)?;
 
             // Validate comment size
             ensure!(
@@ -213,16 +222,14 @@ pub mod pallet {
             ensure!(endorser != endorsee, Error::<T>::CannotEndorseSelf);
 
             // Validate weight (1-10)
-            ensure!(weight >= 1 && weight <= 10, Error::<T>::InvalidWeight);
+            ensure!((1..=10).contains(&weight), Error::<T>::InvalidWeight);
 
             // Check endorser has active DID
-            let endorser_did = did::DidDocuments::<T>::get(&endorser)
-                .ok_or(Error::<T>::NoDid)?;
+            let endorser_did = did::DidDocuments::<T>::get(&endorser).ok_or(Error::<T>::NoDid)?;
             ensure!(endorser_did.active, Error::<T>::DidNotActive);
 
             // Check endorsee has active DID
-            let endorsee_did = did::DidDocuments::<T>::get(&endorsee)
-                .ok_or(Error::<T>::NoDid)?;
+            let endorsee_did = did::DidDocuments::<T>::get(&endorsee).ok_or(Error::<T>::NoDid)?;
             ensure!(endorsee_did.active, Error::<T>::DidNotActive);
 
             // Check if already endorsed
@@ -266,7 +273,7 @@ pub mod pallet {
             ReputationScores::<T>::mutate(&endorsee, |score| {
                 score.endorsements_received = score.endorsements_received.saturating_add(1);
                 // Recalculate total score
-                score.total_score = Self::calculate_reputation_score(&score);
+                score.total_score = Self::calculate_reputation_score(score);
             });
 
             Self::deposit_event(Event::EndorsementCreated {
@@ -282,15 +289,15 @@ pub mod pallet {
         /// Remove an endorsement you previously gave
         ///
         /// # Arguments
-        /// * `origin` - Must be the endorser
-        /// * `endorsee` - Account that was endorsed
+        /// * `origin` - Must be linked to the endorser
+        /// * `endorsee` - DID that was endorsed
         #[pallet::call_index(1)]
         #[pallet::weight(<T as Config>::WeightInfo::remove_endorsement())]
         pub fn remove_endorsement(
             origin: OriginFor<T>,
-            endorsee: T::AccountId,
+            endorsee: T::DidIdentifier,
         ) -> DispatchResult {
-            let endorser = ensure_signed(origin)?;
+            let endorser = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NoDid)?;
 
             // Check if endorsement exists
             ensure!(
@@ -318,13 +325,10 @@ pub mod pallet {
 
             ReputationScores::<T>::mutate(&endorsee, |score| {
                 score.endorsements_received = score.endorsements_received.saturating_sub(1);
-                score.total_score = Self::calculate_reputation_score(&score);
+                score.total_score = Self::calculate_reputation_score(score);
             });
 
-            Self::deposit_event(Event::EndorsementRemoved {
-                endorser,
-                endorsee,
-            });
+            Self::deposit_event(Event::EndorsementRemoved { endorser, endorsee });
 
             Ok(())
         }
@@ -339,14 +343,14 @@ pub mod pallet {
         #[pallet::weight(<T as Config>::WeightInfo::update_reputation_score())]
         pub fn record_credential_issuance(
             origin: OriginFor<T>,
-            issuer: T::AccountId,
+            issuer: T::DidIdentifier,
         ) -> DispatchResult {
             ensure_root(origin)?;
 
             // Update issuer's reputation
             ReputationScores::<T>::mutate(&issuer, |score| {
                 score.credentials_issued = score.credentials_issued.saturating_add(1);
-                score.total_score = Self::calculate_reputation_score(&score);
+                score.total_score = Self::calculate_reputation_score(score);
             });
 
             Self::deposit_event(Event::CredentialIssuanceRecorded {
@@ -355,7 +359,7 @@ pub mod pallet {
 
             let new_score = ReputationScores::<T>::get(&issuer).total_score;
             Self::deposit_event(Event::ReputationUpdated {
-                account: issuer,
+                did: issuer,
                 new_score,
             });
 
@@ -366,18 +370,16 @@ pub mod pallet {
         /// This can be called by anyone or the credential pallet
         ///
         /// # Arguments
-        /// * `origin` - The account that verified a credential
+        /// * `origin` - An account linked to the DID that verified a credential
         #[pallet::call_index(3)]
         #[pallet::weight(<T as Config>::WeightInfo::update_reputation_score())]
-        pub fn record_credential_verification(
-            origin: OriginFor<T>,
-        ) -> DispatchResult {
-            let verifier = ensure_signed(origin)?;
+        pub fn record_credential_verification(origin: OriginFor<T>) -> DispatchResult {
+            let verifier = did::Pallet::<T>::acting_did(origin)?.ok_or(Error::<T>::NoDid)?;
 
             // Update verifier's reputation
             ReputationScores::<T>::mutate(&verifier, |score| {
                 score.credentials_verified = score.credentials_verified.saturating_add(1);
-                score.total_score = Self::calculate_reputation_score(&score);
+                score.total_score = Self::calculate_reputation_score(score);
             });
 
             Self::deposit_event(Event::CredentialVerificationRecorded {
@@ -386,7 +388,7 @@ pub mod pallet {
 
             let new_score = ReputationScores::<T>::get(&verifier).total_score;
             Self::deposit_event(Event::ReputationUpdated {
-                account: verifier,
+                did: verifier,
                 new_score,
             });
 
@@ -397,24 +399,21 @@ pub mod pallet {
         ///
         /// # Arguments
         /// * `origin` - Root origin
-        /// * `account` - Account to update
+        /// * `did` - DID to update
         #[pallet::call_index(4)]
         #[pallet::weight(<T as Config>::WeightInfo::update_reputation_score())]
         pub fn update_reputation_score(
             origin: OriginFor<T>,
-            account: T::AccountId,
+            did: T::DidIdentifier,
         ) -> DispatchResult {
             ensure_root(origin)?;
 
-            ReputationScores::<T>::mutate(&account, |score| {
-                score.total_score = Self::calculate_reputation_score(&score);
+            ReputationScores::<T>::mutate(&did, |score| {
+                score.total_score = Self::calculate_reputation_score(score);
             });
 
-            let new_score = ReputationScores::<T>::get(&account).total_score;
-            Self::deposit_event(Event::ReputationUpdated {
-                account,
-                new_score,
-            });
+            let new_score = ReputationScores::<T>::get(&did).total_score;
+            Self::deposit_event(Event::ReputationUpdated { did, new_score });
 
             Ok(())
         }
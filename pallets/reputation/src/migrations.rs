@@ -0,0 +1,175 @@
+// pallets/reputation/src/migrations.rs
+use crate::{pallet, Config, Endorsement, EndorsementType, ReputationScore};
+#[cfg(feature = "try-runtime")]
+use frame::try_runtime::TryRuntimeError;
+use frame::{
+    deps::frame_support::migrations::VersionedMigration, prelude::*,
+    traits::UncheckedOnRuntimeUpgrade,
+};
+
+/// References DIDs rather than the accounts they used to be keyed by.
+///
+/// Every entry is moved in the upgrade block; `pre_upgrade` checks that they
+/// fit in it.
+pub mod v1 {
+    use super::*;
+
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+    #[scale_info(skip_type_params(T))]
+    pub struct OldEndorsement<T: Config> {
+        pub endorser: T::AccountId,
+        pub endorsee: T::AccountId,
+        pub endorsement_type: EndorsementType,
+        pub comment: BoundedVec<u8, ConstU32<256>>,
+        pub created_at: BlockNumberFor<T>,
+        pub weight: u8,
+    }
+
+    #[frame::storage_alias]
+    pub type ReputationScores<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        ReputationScore,
+    >;
+
+    #[frame::storage_alias]
+    pub type EndorsementsGiven<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<OldEndorsement<T>, <T as Config>::MaxEndorsements>,
+    >;
+
+    #[frame::storage_alias]
+    pub type EndorsementsReceived<T: Config> = StorageMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        BoundedVec<OldEndorsement<T>, <T as Config>::MaxEndorsements>,
+    >;
+
+    #[frame::storage_alias]
+    pub type HasEndorsed<T: Config> = StorageDoubleMap<
+        pallet::Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        bool,
+    >;
+
+    pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+    impl<T: Config> InnerMigrateV0ToV1<T> {
+        fn did_of(account: T::AccountId) -> T::DidIdentifier {
+            <T as did::Config>::DidIdentifierOf::convert(account)
+        }
+
+        fn migrate_endorsements(
+            old: BoundedVec<OldEndorsement<T>, T::MaxEndorsements>,
+        ) -> BoundedVec<Endorsement<T>, T::MaxEndorsements> {
+            let endorsements = old
+                .into_iter()
+                .map(|e| Endorsement {
+                    endorser: Self::did_of(e.endorser),
+                    endorsee: Self::did_of(e.endorsee),
+                    endorsement_type: e.endorsement_type,
+                    comment: e.comment,
+                    created_at: e.created_at,
+                    weight: e.weight,
+                })
+                .collect::<Vec<_>>();
+
+            BoundedVec::truncate_from(endorsements)
+        }
+    }
+
+    impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut moved = 0u64;
+
+            // Each map is drained before re-inserting, as an identifier may encode
+            // to the same key as the account it replaces
+            let scores = ReputationScores::<T>::drain().collect::<Vec<_>>();
+            for (account, score) in scores {
+                crate::ReputationScores::<T>::insert(Self::did_of(account), score);
+                moved.saturating_inc();
+            }
+
+            let given = EndorsementsGiven::<T>::drain().collect::<Vec<_>>();
+            for (account, endorsements) in given {
+                crate::EndorsementsGiven::<T>::insert(
+                    Self::did_of(account),
+                    Self::migrate_endorsements(endorsements),
+                );
+                moved.saturating_inc();
+            }
+
+            let received = EndorsementsReceived::<T>::drain().collect::<Vec<_>>();
+            for (account, endorsements) in received {
+                crate::EndorsementsReceived::<T>::insert(
+                    Self::did_of(account),
+                    Self::migrate_endorsements(endorsements),
+                );
+                moved.saturating_inc();
+            }
+
+            let endorsed = HasEndorsed::<T>::drain().collect::<Vec<_>>();
+            for (endorser, endorsee, flag) in endorsed {
+                crate::HasEndorsed::<T>::insert(
+                    Self::did_of(endorser),
+                    Self::did_of(endorsee),
+                    flag,
+                );
+                moved.saturating_inc();
+            }
+
+            T::DbWeight::get().reads_writes(moved, moved.saturating_mul(2))
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let counts: Vec<u64> = vec![
+                ReputationScores::<T>::iter_keys().count() as u64,
+                EndorsementsGiven::<T>::iter_keys().count() as u64,
+                EndorsementsReceived::<T>::iter_keys().count() as u64,
+                HasEndorsed::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                did::migrations::fits_in_block::<T, pallet::Pallet<T>>(counts.iter().sum()),
+                "v1: too many entries to re-key in one block"
+            );
+
+            Ok(counts.encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = Vec::<u64>::decode(&mut &state[..])
+                .map_err(|_| "v1: cannot decode pre-upgrade state")?;
+
+            // Two accounts deriving the same identifier would collapse entries
+            let after: Vec<u64> = vec![
+                crate::ReputationScores::<T>::iter_keys().count() as u64,
+                crate::EndorsementsGiven::<T>::iter_keys().count() as u64,
+                crate::EndorsementsReceived::<T>::iter_keys().count() as u64,
+                crate::HasEndorsed::<T>::iter_keys().count() as u64,
+            ];
+            ensure!(
+                after == before,
+                "v1: entries lost re-keying by DID identifier"
+            );
+            Ok(())
+        }
+    }
+
+    /// Migrate storage from version 0 to version 1
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        InnerMigrateV0ToV1<T>,
+        pallet::Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
@@ -119,15 +119,17 @@ fn endorse_fails_if_endorsee_has_no_did() {
 fn endorse_fails_if_did_inactive() {
     new_test_ext().execute_with(|| {
         System::set_block_number(1);
-        
+
         create_did_for_account(INSTITUTION_A);
         create_did_for_account(INSTITUTION_B);
 
         // Deactivate institution B's DID
-        assert_ok!(Did::deactivate_did(RuntimeOrigin::signed(INSTITUTION_B)));
+        assert_ok!(Did::deactivate_did(
+            RuntimeOrigin::signed(INSTITUTION_B),
+            INSTITUTION_B
+        ));
 
-        let comment: BoundedVec<u8, ConstU32<256>> = 
-            b"Comment".to_vec().try_into().unwrap();
+        let comment: BoundedVec<u8, ConstU32<256>> = b"Comment".to_vec().try_into().unwrap();
 
         // Should fail because endorsee's DID is inactive
         assert_noop!(
@@ -279,6 +281,48 @@ fn multiple_endorsements_from_different_accounts() {
     });
 }
 
+#[test]
+fn linked_account_endorses_as_its_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let staff = 6u64;
+
+        create_did_for_account(INSTITUTION_A);
+        create_did_for_account(INSTITUTION_B);
+        link_account(staff, INSTITUTION_A);
+
+        assert_ok!(Reputation::endorse(
+            RuntimeOrigin::signed(staff),
+            INSTITUTION_B,
+            EndorsementType::Academic,
+            BoundedVec::new(),
+            6
+        ));
+
+        assert!(Reputation::has_endorsed(INSTITUTION_A, INSTITUTION_B));
+        assert!(Reputation::endorsements_given(staff).is_empty());
+
+        // The DID cannot endorse again through another of its accounts
+        assert_noop!(
+            Reputation::endorse(
+                RuntimeOrigin::signed(INSTITUTION_A),
+                INSTITUTION_B,
+                EndorsementType::Academic,
+                BoundedVec::new(),
+                6
+            ),
+            Error::<Test>::AlreadyEndorsed
+        );
+
+        assert_ok!(Reputation::remove_endorsement(
+            RuntimeOrigin::signed(INSTITUTION_A),
+            INSTITUTION_B
+        ));
+        assert!(!Reputation::has_endorsed(INSTITUTION_A, INSTITUTION_B));
+    });
+}
+
 // ================== Remove Endorsement Tests ==================
 
 #[test]
@@ -343,6 +387,46 @@ fn remove_endorsement_fails_if_not_found() {
     });
 }
 
+#[test]
+fn deleted_did_keeps_its_reputation() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        create_test_endorsement(INSTITUTION_A, INSTITUTION_B);
+        create_did_for_account(INSTITUTION_C);
+        let score = Reputation::reputation_scores(INSTITUTION_B);
+
+        assert_ok!(Did::delete_did(
+            RuntimeOrigin::signed(INSTITUTION_B),
+            INSTITUTION_B
+        ));
+
+        // Endorsements it received stay on record
+        assert_eq!(Reputation::reputation_scores(INSTITUTION_B), score);
+        assert_eq!(Reputation::endorsements_received(INSTITUTION_B).len(), 1);
+
+        // No new endorsements can be added
+        let comment: BoundedVec<u8, ConstU32<256>> = b"Comment".to_vec().try_into().unwrap();
+        assert_noop!(
+            Reputation::endorse(
+                RuntimeOrigin::signed(INSTITUTION_C),
+                INSTITUTION_B,
+                EndorsementType::Professional,
+                comment,
+                5
+            ),
+            Error::<Test>::NoDid
+        );
+
+        // The endorser can still withdraw its endorsement
+        assert_ok!(Reputation::remove_endorsement(
+            RuntimeOrigin::signed(INSTITUTION_A),
+            INSTITUTION_B
+        ));
+        assert!(Reputation::endorsements_received(INSTITUTION_B).is_empty());
+    });
+}
+
 // ================== Credential Issuance Recording Tests ==================
 
 #[test]
@@ -367,14 +451,16 @@ fn record_credential_issuance_works() {
         System::assert_has_event(
             Event::CredentialIssuanceRecorded {
                 issuer: INSTITUTION_A,
-            }.into()
+            }
+            .into(),
         );
 
         System::assert_last_event(
             Event::ReputationUpdated {
-                account: INSTITUTION_A,
+                did: INSTITUTION_A,
                 new_score: 10,
-            }.into()
+            }
+            .into(),
         );
     });
 }
@@ -438,16 +524,15 @@ fn record_credential_verification_works() {
 
         // Verify events
         System::assert_has_event(
-            Event::CredentialVerificationRecorded {
-                verifier: VERIFIER,
-            }.into()
+            Event::CredentialVerificationRecorded { verifier: VERIFIER }.into(),
         );
 
         System::assert_last_event(
             Event::ReputationUpdated {
-                account: VERIFIER,
+                did: VERIFIER,
                 new_score: 5,
-            }.into()
+            }
+            .into(),
         );
     });
 }
@@ -473,6 +558,18 @@ fn multiple_verifications_update_score() {
     });
 }
 
+#[test]
+fn record_credential_verification_requires_did() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_noop!(
+            Reputation::record_credential_verification(RuntimeOrigin::signed(VERIFIER)),
+            Error::<Test>::NoDid
+        );
+    });
+}
+
 // ================== Reputation Score Calculation Tests ==================
 
 #[test]
@@ -600,6 +697,47 @@ fn update_reputation_score_fails_for_non_root() {
     });
 }
 
+// ================== Migration Tests ==================
+
+#[test]
+fn migration_v1_keys_endorsements_by_did() {
+    use crate::migrations::v1::{self, OldEndorsement};
+    use frame::deps::frame_support::traits::OnRuntimeUpgrade;
+
+    new_test_ext().execute_with(|| {
+        let endorsement = OldEndorsement::<Test> {
+            endorser: INSTITUTION_A,
+            endorsee: INSTITUTION_B,
+            endorsement_type: EndorsementType::Research,
+            comment: BoundedVec::new(),
+            created_at: 3,
+            weight: 7,
+        };
+        v1::EndorsementsGiven::<Test>::insert(
+            INSTITUTION_A,
+            BoundedVec::truncate_from(vec![endorsement.clone()]),
+        );
+        v1::EndorsementsReceived::<Test>::insert(
+            INSTITUTION_B,
+            BoundedVec::truncate_from(vec![endorsement]),
+        );
+        v1::HasEndorsed::<Test>::insert(INSTITUTION_A, INSTITUTION_B, true);
+        StorageVersion::new(0).put::<Reputation>();
+
+        v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+        let given = Reputation::endorsements_given(INSTITUTION_A);
+        assert_eq!(given[0].endorsee, INSTITUTION_B);
+        assert_eq!(given[0].weight, 7);
+        assert_eq!(
+            Reputation::endorsements_received(INSTITUTION_B)[0].endorser,
+            INSTITUTION_A
+        );
+        assert!(Reputation::has_endorsed(INSTITUTION_A, INSTITUTION_B));
+        assert_eq!(StorageVersion::get::<Reputation>(), 1);
+    });
+}
+
 // ================== Integration Tests ==================
 
 #[test]
@@ -663,7 +801,7 @@ fn endorsement_types_are_stored_correctly() {
         create_did_for_account(INSTITUTION_A);
         create_did_for_account(INSTITUTION_B);
 
-        let types = vec![
+        let types = [
             EndorsementType::Professional,
             EndorsementType::Academic,
             EndorsementType::Research,
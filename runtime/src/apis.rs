@@ -45,9 +45,9 @@ use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-	AccountId, Balance, Block, ConsensusHook, Executive, InherentDataExt, Nonce, ParachainSystem,
-	Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys, System, TransactionPayment,
-	SLOT_DURATION, VERSION,
+	AccountId, Balance, Block, BlockNumber, ConsensusHook, Did, DidIdentifier, Executive,
+	InherentDataExt, Nonce, ParachainSystem, Runtime, RuntimeCall, RuntimeGenesisConfig, SessionKeys,
+	System, TransactionPayment, SLOT_DURATION, VERSION,
 };
 
 // we move some impls outside so we can easily use them with `docify`.
@@ -175,6 +175,38 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl did::runtime_api::DidApi<Block, DidIdentifier, AccountId, BlockNumber> for Runtime {
+		fn resolve(
+			did: DidIdentifier,
+		) -> Option<did::runtime_api::DidResolution<DidIdentifier, AccountId, BlockNumber>> {
+			Did::resolve(did)
+		}
+
+		fn did_by_key(public_key: did::PublicKey) -> Option<DidIdentifier> {
+			Did::did_by_key(&public_key)
+		}
+
+		fn institution_by_name(name: Vec<u8>) -> Option<DidIdentifier> {
+			Did::institution_by_name(&name)
+		}
+
+		fn did_by_account(account: AccountId) -> Option<DidIdentifier> {
+			Did::did_by_account(&account)
+		}
+
+		fn did_document_at(did: DidIdentifier, block: BlockNumber) -> Option<Vec<did::PublicKeyEntry>> {
+			Did::did_document_at(&did, block)
+		}
+
+		fn key_status_at(
+			did: DidIdentifier,
+			key_id: [u8; 32],
+			block: BlockNumber,
+		) -> did::runtime_api::KeyStatus<BlockNumber> {
+			Did::key_status_at(&did, &key_id, block)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
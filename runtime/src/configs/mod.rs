@@ -35,6 +35,7 @@ use cumulus_primitives_core::{AggregateMessageOrigin, ParaId};
 use frame_support::{
 	derive_impl,
 	dispatch::DispatchClass,
+	instances::Instance1,
 	parameter_types,
 	traits::{
 		ConstBool, ConstU32, ConstU64, ConstU8, EitherOfDiverse, TransformOrigin, VariantCountOf,
@@ -46,6 +47,7 @@ use frame_system::{
 	limits::{BlockLength, BlockWeights},
 	EnsureRoot,
 };
+use pallet_collective::{EnsureProportionAtLeast, EnsureProportionMoreThan};
 use pallet_xcm::{EnsureXcm, IsVoiceOfBody};
 use parachains_common::message_queue::{NarrowOriginToSibling, ParaIdToSibling};
 use polkadot_runtime_common::{
@@ -59,11 +61,11 @@ use xcm::latest::prelude::BodyId;
 // Local module imports
 use super::{
 	weights::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight},
-	AccountId, Aura, Balance, Balances, Block, BlockNumber, CollatorSelection, ConsensusHook, Hash,
-	MessageQueue, Nonce, PalletInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
+	AccountId, Aura, Balance, Balances, Block, BlockNumber, CollatorSelection, ConsensusHook,
+	DidIdentifier, DidIdentifierFromAccount, Hash, MessageQueue, Nonce, PalletInfo, ParachainSystem, Runtime, RuntimeCall, RuntimeEvent,
 	RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys,
-	System, WeightToFee, XcmpQueue, AVERAGE_ON_INITIALIZE_RATIO, EXISTENTIAL_DEPOSIT, HOURS,
-	MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
+	System, Timestamp, WeightToFee, XcmpQueue, AVERAGE_ON_INITIALIZE_RATIO, DAYS, EXISTENTIAL_DEPOSIT, HOURS,
+	MAXIMUM_BLOCK_WEIGHT, MICRO_UNIT, MILLI_SECS_PER_BLOCK, MILLI_UNIT, NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
 };
 use xcm_config::{RelayLocation, XcmOriginToTransactDispatchOrigin};
 
@@ -191,6 +193,32 @@ impl pallet_sudo::Config for Runtime {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+	pub MaxProposalWeight: Weight = Perbill::from_percent(50) * RuntimeBlockWeights::get().max_block;
+}
+
+/// The council deciding institution accreditation.
+pub type CouncilCollective = Instance1;
+
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+	type SetMembersOrigin = EnsureRoot<AccountId>;
+	type MaxProposalWeight = MaxProposalWeight;
+	type DisapproveOrigin = EnsureRoot<AccountId>;
+	type KillOrigin = EnsureRoot<AccountId>;
+	type Consideration = ();
+}
+
 parameter_types! {
 	pub const ReservedXcmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT.saturating_div(4);
 	pub const ReservedDmpWeight: Weight = MAXIMUM_BLOCK_WEIGHT.saturating_div(4);
@@ -326,12 +354,49 @@ impl pallet_parachain_template::Config for Runtime {
 parameter_types! {
     pub const MaxDocumentSize: u32 = 1024;
     pub const MaxPublicKeys: u32 = 10;
+    pub const MaxKeyHistory: u32 = 50;
+    pub const MaxCompromisedKeys: u32 = 20;
+    pub const MaxServiceEndpoints: u32 = 8;
+    pub const MaxUrlLength: u32 = 256;
+    pub const DidDeposit: Balance = 10 * MILLI_UNIT;
+    pub const ByteDeposit: Balance = 10 * MICRO_UNIT;
+    pub const MaxGuardians: u32 = 10;
+    pub const MaxLinkedAccounts: u32 = 8;
+    pub const RecoveryDelay: BlockNumber = 48 * HOURS;
+    pub const VerificationPeriod: BlockNumber = 365 * DAYS;
+    pub const MaxLapsesPerBlock: u32 = 50;
 }
 
+/// Accrediting an institution requires a council majority; a tie is not enough.
+pub type InstitutionVerifyOrigin = EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>;
+
+/// A third of the council can withdraw accreditation, so revocation is never slower than granting it.
+pub type InstitutionRevokeOrigin = EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 3>;
+
 impl did::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type DidIdentifier = DidIdentifier;
+    type DidIdentifierOf = DidIdentifierFromAccount;
+    type MaxLinkedAccounts = MaxLinkedAccounts;
     type MaxDocumentSize = MaxDocumentSize;
     type MaxPublicKeys = MaxPublicKeys;
+    type MaxKeyHistory = MaxKeyHistory;
+    type MaxCompromisedKeys = MaxCompromisedKeys;
+    type MaxServiceEndpoints = MaxServiceEndpoints;
+    type MaxUrlLength = MaxUrlLength;
+    type Currency = Balances;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type DidDeposit = DidDeposit;
+    type ByteDeposit = ByteDeposit;
+    type IssuedCredentials = credential::Pallet<Runtime>;
+    type MaxGuardians = MaxGuardians;
+    type RecoveryDelay = RecoveryDelay;
+    type VerifyOrigin = InstitutionVerifyOrigin;
+    type RevokeOrigin = InstitutionRevokeOrigin;
+    type VerificationPeriod = VerificationPeriod;
+    type MaxLapsesPerBlock = MaxLapsesPerBlock;
     type WeightInfo = did::weights::SubstrateWeight;
 }
 
@@ -339,12 +404,22 @@ impl did::Config for Runtime {
 parameter_types! {
     pub const MaxMetadataSize: u32 = 512;
     pub const MaxCredentialsPerHolder: u32 = 100;
+    pub const CredentialDeposit: Balance = 10 * MILLI_UNIT;
+    pub const DelegateDeposit: Balance = 5 * MILLI_UNIT;
+    pub const MaxExpiriesPerBlock: u32 = 50;
+    pub const MaxDelegates: u32 = 50;
 }
 
 impl credential::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type MaxMetadataSize = MaxMetadataSize;
     type MaxCredentialsPerHolder = MaxCredentialsPerHolder;
+    type CredentialDeposit = CredentialDeposit;
+    type DelegateDeposit = DelegateDeposit;
+    type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+    type MaxDelegates = MaxDelegates;
+    type TimeProvider = Timestamp;
+    type ExpectedBlockTime = ConstU64<MILLI_SECS_PER_BLOCK>;
     type WeightInfo = credential::weights::SubstrateWeight;
 }
 
@@ -19,19 +19,24 @@ use smallvec::smallvec;
 
 use polkadot_sdk::{staging_parachain_info as parachain_info, *};
 
+use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
 use sp_runtime::{
 	generic, impl_opaque_keys,
-	traits::{BlakeTwo256, IdentifyAccount, Verify},
-	MultiSignature,
+	traits::{BlakeTwo256, Convert, IdentifyAccount, Verify},
+	MultiSignature, RuntimeDebug,
 };
 
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 
-use frame_support::weights::{
-	constants::WEIGHT_REF_TIME_PER_SECOND, Weight, WeightToFeeCoefficient, WeightToFeeCoefficients,
-	WeightToFeePolynomial,
+use frame_support::{
+	instances::Instance1,
+	weights::{
+		constants::WEIGHT_REF_TIME_PER_SECOND, Weight, WeightToFeeCoefficient,
+		WeightToFeeCoefficients, WeightToFeePolynomial,
+	},
 };
 pub use genesis_config_presets::PARACHAIN_ID;
 pub use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -46,6 +51,38 @@ pub type Signature = MultiSignature;
 /// to the public key of our transaction signing scheme.
 pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
+/// Identifier of a DID.
+///
+/// Derived from the account that created the DID, but distinct from any account: the DID
+/// keeps it when that account is unlinked or control passes to another account.
+#[derive(
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	MaxEncodedLen,
+	TypeInfo,
+	RuntimeDebug,
+)]
+pub struct DidIdentifier(pub [u8; 32]);
+
+/// Derives the identifier of a new DID from the account creating it.
+///
+/// The account is hashed under a domain tag, so an identifier can never be used where the
+/// account is expected, or the other way round.
+pub struct DidIdentifierFromAccount;
+
+impl Convert<AccountId, DidIdentifier> for DidIdentifierFromAccount {
+	fn convert(account: AccountId) -> DidIdentifier {
+		DidIdentifier(sp_io::hashing::blake2_256(&(b"academic-did/identifier", account).encode()))
+	}
+}
+
 /// Balance of an account.
 pub type Balance = u128;
 
@@ -98,7 +135,26 @@ pub type UncheckedExtrinsic =
 ///
 /// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (
+	did::migrations::v1::MigrateV0ToV1<Runtime>,
+	did::migrations::v2::MigrateV1ToV2<Runtime>,
+	did::migrations::v3::MigrateV2ToV3<Runtime>,
+	did::migrations::v4::MigrateV3ToV4<Runtime>,
+	did::migrations::v5::MigrateV4ToV5<Runtime>,
+	did::migrations::v6::MigrateV5ToV6<Runtime>,
+	did::migrations::v7::MigrateV6ToV7<Runtime>,
+	did::migrations::v8::MigrateV7ToV8<Runtime>,
+	did::migrations::v9::MigrateV8ToV9<Runtime>,
+	did::migrations::v10::MigrateV9ToV10<Runtime>,
+	did::migrations::v11::MigrateV10ToV11<Runtime>,
+	did::migrations::v12::MigrateV11ToV12<Runtime>,
+	credential::migrations::v1::MigrateV0ToV1<Runtime>,
+	credential::migrations::v2::MigrateV1ToV2<Runtime>,
+	credential::migrations::v3::MigrateV2ToV3<Runtime>,
+	credential::migrations::v4::MigrateV3ToV4<Runtime>,
+	credential::migrations::v5::MigrateV4ToV5<Runtime>,
+	reputation::migrations::v1::MigrateV0ToV1<Runtime>,
+);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
@@ -170,10 +226,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: alloc::borrow::Cow::Borrowed("parachain-template-runtime"),
 	impl_name: alloc::borrow::Cow::Borrowed("parachain-template-runtime"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 0,
 	apis: apis::RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 	system_version: 1,
 };
 
@@ -287,6 +343,8 @@ mod runtime {
 	// Governance
 	#[runtime::pallet_index(15)]
 	pub type Sudo = pallet_sudo;
+	#[runtime::pallet_index(16)]
+	pub type Council = pallet_collective<Instance1>;
 
 	// Collator support. The order of these 4 are important and shall not change.
 	#[runtime::pallet_index(20)]